@@ -0,0 +1,21 @@
+use serde::Serialize;
+
+pub type Result<T> = core::result::Result<T, Error>;
+
+#[derive(Debug, Serialize, PartialEq)]
+pub enum Error {
+    HmacFailNewFromSlice,
+
+    KeyInvalidFormat,
+    SecretNotMatching,
+}
+
+// region:    --- Error Boilerplate
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+impl std::error::Error for Error {}
+// endregion: --- Error Boilerplate
@@ -0,0 +1,126 @@
+//! API keys (personal access tokens), in the `ak_<key_id>.<secret>` format.
+//!
+//! Only a hash of the secret is stored, the key is shown once at creation.
+
+mod error;
+
+pub use self::error::{Error, Result};
+
+use hmac::{Hmac, Mac};
+use lib_utils::b64::{b64u_decode, b64u_encode};
+use rand::RngCore;
+use sha2::Sha512;
+
+use crate::config::auth_config;
+
+pub const API_KEY_PREFIX: &str = "ak_";
+const SECRET_LEN: usize = 32;
+
+#[derive(Debug, PartialEq)]
+pub struct ApiKeyParts {
+    pub key_id: String,
+    pub secret: String,
+}
+
+/// Generate a new random secret, base64url encoded.
+pub fn generate_secret() -> String {
+    let mut secret = [0u8; SECRET_LEN];
+    rand::thread_rng().fill_bytes(&mut secret);
+
+    b64u_encode(secret)
+}
+
+/// Build the api key given to the user.
+pub fn to_api_key(key_id: &str, secret: &str) -> String {
+    format!("{API_KEY_PREFIX}{key_id}.{secret}")
+}
+
+/// Whether the bearer token is an api key (and not a jwt).
+pub fn is_api_key(token: &str) -> bool {
+    token.starts_with(API_KEY_PREFIX)
+}
+
+pub fn parse_api_key(api_key: &str) -> Result<ApiKeyParts> {
+    let (key_id, secret) = api_key
+        .strip_prefix(API_KEY_PREFIX)
+        .and_then(|key| key.split_once('.'))
+        .ok_or(Error::KeyInvalidFormat)?;
+
+    if key_id.is_empty() || secret.is_empty() {
+        return Err(Error::KeyInvalidFormat);
+    }
+
+    Ok(ApiKeyParts {
+        key_id: key_id.to_string(),
+        secret: secret.to_string(),
+    })
+}
+
+pub fn hash_secret(secret: &str) -> Result<String> {
+    let hmac_sha512 = new_hmac(secret)?;
+
+    Ok(b64u_encode(hmac_sha512.finalize().into_bytes()))
+}
+
+/// Validate the secret against its stored hash, in constant time.
+pub fn validate_secret(secret: &str, secret_hash: &str) -> Result<()> {
+    let secret_hash = b64u_decode(secret_hash).map_err(|_| Error::SecretNotMatching)?;
+
+    new_hmac(secret)?
+        .verify_slice(&secret_hash)
+        .map_err(|_| Error::SecretNotMatching)
+}
+
+fn new_hmac(secret: &str) -> Result<Hmac<Sha512>> {
+    let mut hmac_sha512 = Hmac::<Sha512>::new_from_slice(&auth_config().PWD_KEY)
+        .map_err(|_| Error::HmacFailNewFromSlice)?;
+    hmac_sha512.update(secret.as_bytes());
+
+    Ok(hmac_sha512)
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+
+    #[test]
+    fn test_api_key_roundtrip_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_key_id = "k5xq3t0b8m2vz1c9n4wd";
+        let secret = generate_secret();
+        let secret_hash = hash_secret(&secret)?;
+
+        // -- Exec
+        let api_key = to_api_key(fx_key_id, &secret);
+        let parts = parse_api_key(&api_key)?;
+
+        // -- Check
+        assert!(is_api_key(&api_key));
+        assert_eq!(parts.key_id, fx_key_id);
+        validate_secret(&parts.secret, &secret_hash)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_api_key_err() -> Result<()> {
+        // -- Setup & Fixtures
+        let secret_hash = hash_secret(&generate_secret())?;
+
+        // -- Exec & Check
+        assert_eq!(
+            parse_api_key("eyJhbGciOiJIUzUxMiJ9.e30.sig"),
+            Err(Error::KeyInvalidFormat)
+        );
+        assert_eq!(parse_api_key("ak_.secret"), Err(Error::KeyInvalidFormat));
+        assert_eq!(
+            validate_secret(&generate_secret(), &secret_hash),
+            Err(Error::SecretNotMatching)
+        );
+
+        Ok(())
+    }
+}
+// endregion: --- Tests
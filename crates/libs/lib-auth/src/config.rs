@@ -1,32 +1,230 @@
-use lib_utils::envs::{get_env_b64u_as_u8s, get_env_parse};
-use std::sync::OnceLock;
+use lib_utils::{
+    b64::b64u_decode,
+    config::{ConfigReader, ConfigSources, Error, Result, Secret},
+};
+use std::{collections::HashMap, path::Path, sync::OnceLock, time::Duration};
 
-pub fn auth_config() -> &'static AuthConfig {
-    static INSTANCE: OnceLock<AuthConfig> = OnceLock::new();
+static INSTANCE: OnceLock<AuthConfig> = OnceLock::new();
+
+/// Set the config loaded at startup, before any call to `auth_config`.
+pub fn init_auth_config(config: AuthConfig) -> Result<()> {
+    INSTANCE
+        .set(config)
+        .map_err(|_| Error::AlreadyInitialized("auth"))
+}
 
+/// The config set by `init_auth_config`, or else loaded from the defaults and the env
+/// (e.g., in the tests).
+pub fn auth_config() -> &'static AuthConfig {
     INSTANCE.get_or_init(|| {
-        AuthConfig::load_from_env()
-            .unwrap_or_else(|ex| panic!("FATAL - WHILE LOADING CONF - Cause: {ex:?}"))
+        let sources = ConfigSources::new().defaults(AuthConfig::DEFAULTS).env();
+        let mut reader = sources.reader();
+        let config = AuthConfig::load(&mut reader);
+        reader
+            .finish()
+            .unwrap_or_else(|ex| panic!("FATAL - WHILE LOADING CONF - Cause: {ex}"));
+        config
     })
 }
 
 #[allow(non_snake_case)]
+#[derive(Debug)]
 pub struct AuthConfig {
     // -- Crypt
-    pub PWD_KEY: Vec<u8>,
-    pub TOKEN_KEY: Vec<u8>,
-    pub TOKEN_DURATION_SEC: f64,
-    pub TOKEN_DURATION_SEC_USIZE: usize,
+    pub PWD_KEY: Secret<Vec<u8>>,
+    pub PWD_KEYS: Secret<HashMap<String, Vec<u8>>>,
+    pub PWD_KEY_ID: Option<String>,
+    pub TOKEN_KEY: Secret<Vec<u8>>,
+    pub TOKEN_DURATION: Duration,
+    pub PWD_RESET_DURATION: Duration,
+    pub EMAIL_VERIFICATION_DURATION: Duration,
+    pub MFA_TOKEN_DURATION: Duration,
+    pub IMPERSONATION_TOKEN_DURATION: Duration,
+
+    // -- Totp
+    pub TOTP_ISSUER: String,
+
+    // -- Argon2
+    pub PWD_ARGON2_M_COST: u32,
+    pub PWD_ARGON2_T_COST: u32,
+    pub PWD_ARGON2_P_COST: u32,
+
+    // -- Pwd Policy
+    pub PWD_MIN_LEN: usize,
+    pub PWD_MAX_LEN: usize,
+    pub PWD_REQUIRE_LOWERCASE: bool,
+    pub PWD_REQUIRE_UPPERCASE: bool,
+    pub PWD_REQUIRE_DIGIT: bool,
+    pub PWD_REQUIRE_SYMBOL: bool,
+    pub PWD_COMMON_LIST_FILE: String,
 }
 
 impl AuthConfig {
-    fn load_from_env() -> lib_utils::envs::Result<AuthConfig> {
-        Ok(AuthConfig {
+    /// The values without default are the keys, which must be set.
+    pub const DEFAULTS: &'static [(&'static str, &'static str)] = &[
+        ("SERVICE_PWD_KEYS", ""),
+        ("SERVICE_PWD_KEY_ID", ""),
+        ("SERVICE_TOKEN_DURATION", "30m"),
+        ("SERVICE_PWD_RESET_DURATION", "15m"),
+        ("SERVICE_EMAIL_VERIFICATION_DURATION", "24h"),
+        ("SERVICE_MFA_TOKEN_DURATION", "5m"),
+        ("SERVICE_IMPERSONATION_TOKEN_DURATION", "15m"),
+        ("SERVICE_TOTP_ISSUER", "rust-web-app"),
+        ("SERVICE_PWD_ARGON2_M_COST", "19456"),
+        ("SERVICE_PWD_ARGON2_T_COST", "2"),
+        ("SERVICE_PWD_ARGON2_P_COST", "1"),
+        ("SERVICE_PWD_MIN_LEN", "8"),
+        ("SERVICE_PWD_MAX_LEN", "128"),
+        ("SERVICE_PWD_REQUIRE_LOWERCASE", "true"),
+        ("SERVICE_PWD_REQUIRE_UPPERCASE", "true"),
+        ("SERVICE_PWD_REQUIRE_DIGIT", "true"),
+        ("SERVICE_PWD_REQUIRE_SYMBOL", "false"),
+    ];
+
+    /// Read the config, the errors being collected by `reader`.
+    pub fn load(reader: &mut ConfigReader) -> AuthConfig {
+        let config = AuthConfig {
             // -- Crypt
-            PWD_KEY: get_env_b64u_as_u8s("SERVICE_PWD_KEY")?,
-            TOKEN_KEY: get_env_b64u_as_u8s("SERVICE_TOKEN_KEY")?,
-            TOKEN_DURATION_SEC: get_env_parse("SERVICE_TOKEN_DURATION_SEC")?,
-            TOKEN_DURATION_SEC_USIZE: get_env_parse("SERVICE_TOKEN_DURATION_SEC")?,
+            PWD_KEY: Secret::new(reader.b64u("SERVICE_PWD_KEY")),
+            PWD_KEYS: Secret::new(reader.parse_with("SERVICE_PWD_KEYS", parse_pwd_keys)),
+            PWD_KEY_ID: reader.get_opt("SERVICE_PWD_KEY_ID"),
+            TOKEN_KEY: Secret::new(reader.b64u("SERVICE_TOKEN_KEY")),
+            TOKEN_DURATION: reader.duration("SERVICE_TOKEN_DURATION"),
+            PWD_RESET_DURATION: reader.duration("SERVICE_PWD_RESET_DURATION"),
+            EMAIL_VERIFICATION_DURATION: reader.duration("SERVICE_EMAIL_VERIFICATION_DURATION"),
+            MFA_TOKEN_DURATION: reader.duration("SERVICE_MFA_TOKEN_DURATION"),
+            IMPERSONATION_TOKEN_DURATION: reader.duration("SERVICE_IMPERSONATION_TOKEN_DURATION"),
+
+            // -- Totp
+            TOTP_ISSUER: reader.get("SERVICE_TOTP_ISSUER"),
+
+            // -- Argon2
+            PWD_ARGON2_M_COST: reader.parse("SERVICE_PWD_ARGON2_M_COST"),
+            PWD_ARGON2_T_COST: reader.parse("SERVICE_PWD_ARGON2_T_COST"),
+            PWD_ARGON2_P_COST: reader.parse("SERVICE_PWD_ARGON2_P_COST"),
+
+            // -- Pwd Policy
+            PWD_MIN_LEN: reader.parse("SERVICE_PWD_MIN_LEN"),
+            PWD_MAX_LEN: reader.parse("SERVICE_PWD_MAX_LEN"),
+            PWD_REQUIRE_LOWERCASE: reader.parse("SERVICE_PWD_REQUIRE_LOWERCASE"),
+            PWD_REQUIRE_UPPERCASE: reader.parse("SERVICE_PWD_REQUIRE_UPPERCASE"),
+            PWD_REQUIRE_DIGIT: reader.parse("SERVICE_PWD_REQUIRE_DIGIT"),
+            PWD_REQUIRE_SYMBOL: reader.parse("SERVICE_PWD_REQUIRE_SYMBOL"),
+            PWD_COMMON_LIST_FILE: reader.get("SERVICE_PWD_COMMON_LIST_FILE"),
+        };
+
+        // -- Validate
+        reader.check(
+            config
+                .PWD_KEY_ID
+                .as_ref()
+                .is_none_or(|id| config.PWD_KEYS.contains_key(id)),
+            "SERVICE_PWD_KEY_ID",
+            "not in SERVICE_PWD_KEYS",
+        );
+        reader.check(
+            config.PWD_MIN_LEN <= config.PWD_MAX_LEN,
+            "SERVICE_PWD_MIN_LEN",
+            "greater than SERVICE_PWD_MAX_LEN",
+        );
+        reader.check(
+            !config.TOKEN_DURATION.is_zero(),
+            "SERVICE_TOKEN_DURATION",
+            "must not be zero",
+        );
+        reader.check(
+            Path::new(&config.PWD_COMMON_LIST_FILE).is_file(),
+            "SERVICE_PWD_COMMON_LIST_FILE",
+            "file not found",
+        );
+
+        // -- Former names (the durations were in seconds)
+        for (old_name, new_name) in [
+            ("SERVICE_TOKEN_DURATION_SEC", "SERVICE_TOKEN_DURATION"),
+            (
+                "SERVICE_PWD_RESET_DURATION_SEC",
+                "SERVICE_PWD_RESET_DURATION",
+            ),
+            (
+                "SERVICE_EMAIL_VERIFICATION_DURATION_SEC",
+                "SERVICE_EMAIL_VERIFICATION_DURATION",
+            ),
+            (
+                "SERVICE_MFA_TOKEN_DURATION_SEC",
+                "SERVICE_MFA_TOKEN_DURATION",
+            ),
+            (
+                "SERVICE_IMPERSONATION_TOKEN_DURATION_SEC",
+                "SERVICE_IMPERSONATION_TOKEN_DURATION",
+            ),
+        ] {
+            reader.renamed(old_name, new_name);
+        }
+
+        config
+    }
+}
+
+/// Parse the named pepper keys, formatted as `key_id:key_b64u,key_id:key_b64u` (can be empty).
+fn parse_pwd_keys(value: &str) -> Option<HashMap<String, Vec<u8>>> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+        .map(|key| {
+            let (key_id, key_b64u) = key.split_once(':')?;
+            let key = b64u_decode(key_b64u).ok()?;
+            Some((key_id.to_string(), key))
         })
+        .collect()
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_auth_config_load_errors() -> Result<()> {
+        // -- Setup & Fixtures
+        let sources = ConfigSources::new()
+            .defaults(AuthConfig::DEFAULTS)
+            .toml_str(
+                r#"
+                SERVICE_PWD_KEY = "not-b64u!"
+                SERVICE_PWD_KEY_ID = "01"
+                SERVICE_TOKEN_KEY = "AAAA"
+                SERVICE_TOKEN_DURATION = "30 minutes"
+                SERVICE_PWD_COMMON_LIST_FILE = "data/common-passwords.txt"
+                "#,
+            )?;
+
+        // -- Exec
+        let mut reader = sources.reader();
+        let config = AuthConfig::load(&mut reader);
+
+        // -- Check
+        assert_eq!(
+            reader.finish(),
+            Err(Error::Many(vec![
+                Error::WrongFormat("SERVICE_PWD_KEY"),
+                Error::WrongFormat("SERVICE_TOKEN_DURATION"),
+                Error::NotValid {
+                    name: "SERVICE_PWD_KEY_ID",
+                    reason: "not in SERVICE_PWD_KEYS"
+                },
+                Error::NotValid {
+                    name: "SERVICE_TOKEN_DURATION",
+                    reason: "must not be zero"
+                },
+            ]))
+        );
+        assert_eq!(config.PWD_RESET_DURATION, Duration::from_secs(15 * 60));
+        let config_debug = format!("{config:?}");
+        assert!(config_debug.contains("TOKEN_KEY: [REDACTED]"));
+        assert!(!config_debug.contains("[0, 0, 0]"));
+
+        Ok(())
     }
 }
+// endregion: --- Tests
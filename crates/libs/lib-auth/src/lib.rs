@@ -1,6 +1,9 @@
+pub mod api_key;
 mod config;
+pub mod oidc;
 pub mod pwd;
 pub mod token;
+pub mod totp;
 
-pub use config::auth_config;
+pub use config::{auth_config, init_auth_config, AuthConfig};
 pub use jsonwebtoken;
@@ -0,0 +1,30 @@
+use jsonwebtoken::Algorithm;
+use serde::Serialize;
+
+pub type Result<T> = core::result::Result<T, Error>;
+
+#[derive(Debug, Serialize, PartialEq)]
+pub enum Error {
+    IdTokenHeaderInvalid,
+    /// No key of the provider for the token, the provider keys may have rotated.
+    KeyNotFound {
+        kid: Option<String>,
+    },
+    KeyInvalid,
+    /// The token header `alg` is not the one of its key.
+    AlgNotMatching {
+        alg: Algorithm,
+    },
+    IdTokenInvalid(String),
+    NonceNotMatching,
+}
+
+// region:    --- Error Boilerplate
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+impl std::error::Error for Error {}
+// endregion: --- Error Boilerplate
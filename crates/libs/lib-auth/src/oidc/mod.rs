@@ -0,0 +1,263 @@
+//! OpenID Connect helpers for the authorization code flow with PKCE (RFC 7636).
+//!
+//! The provider calls (discovery, token exchange, keys fetching) are done by the service,
+//! this module only generates the flow secrets and validates the returned id token.
+
+mod error;
+
+pub use self::error::{Error, Result};
+
+use std::str::FromStr;
+
+use jsonwebtoken::{
+    decode, decode_header,
+    jwk::{AlgorithmParameters, EllipticCurve, Jwk, JwkSet},
+    Algorithm, DecodingKey, Validation,
+};
+use lib_utils::b64::b64u_encode;
+use rand::RngCore;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+const RANDOM_LEN: usize = 32;
+
+/// The PKCE pair, the verifier stays on our side until the code exchange.
+#[derive(Debug)]
+pub struct Pkce {
+    pub code_verifier: String,
+    pub code_challenge: String,
+}
+
+/// The claims we use from the id token. `iss`, `aud` and `exp` are checked on validation.
+#[derive(Debug, Deserialize)]
+pub struct IdTokenClaims {
+    pub iss: String,
+    pub sub: String,
+    pub email: Option<String>,
+    #[serde(default)]
+    pub email_verified: bool,
+    pub given_name: Option<String>,
+    pub family_name: Option<String>,
+    pub nonce: Option<String>,
+}
+
+/// Generate a new PKCE pair, with the `S256` challenge method.
+pub fn generate_pkce() -> Pkce {
+    let code_verifier = generate_random();
+    let code_challenge = pkce_challenge(&code_verifier);
+
+    Pkce {
+        code_verifier,
+        code_challenge,
+    }
+}
+
+/// Generate a random value for the `nonce` or `state` parameters.
+pub fn generate_random() -> String {
+    let mut random = [0u8; RANDOM_LEN];
+    rand::thread_rng().fill_bytes(&mut random);
+
+    b64u_encode(random)
+}
+
+/// Validate the id token signature with the provider keys, its issuer, audience,
+/// expiration and nonce, and return its claims.
+pub fn validate_id_token(
+    id_token: &str,
+    jwks: &JwkSet,
+    issuer: &str,
+    client_id: &str,
+    nonce: &str,
+) -> Result<IdTokenClaims> {
+    let header = decode_header(id_token).map_err(|_| Error::IdTokenHeaderInvalid)?;
+
+    // -- Find the signing key, a provider with a single key may not set the kid.
+    let jwk = match &header.kid {
+        Some(kid) => jwks.find(kid),
+        None if jwks.keys.len() == 1 => jwks.keys.first(),
+        None => None,
+    }
+    .ok_or(Error::KeyNotFound {
+        kid: header.kid.clone(),
+    })?;
+    let key = DecodingKey::from_jwk(jwk).map_err(|_| Error::KeyInvalid)?;
+
+    // -- The header is not signed yet, so the algorithm comes from the key.
+    let alg = key_algorithm(jwk)?;
+    if header.alg != alg {
+        return Err(Error::AlgNotMatching { alg: header.alg });
+    }
+
+    let mut validation = Validation::new(alg);
+    validation.set_issuer(&[issuer]);
+    validation.set_audience(&[client_id]);
+
+    let claims = decode::<IdTokenClaims>(id_token, &key, &validation)
+        .map_err(|ex| Error::IdTokenInvalid(ex.to_string()))?
+        .claims;
+
+    if claims.nonce.as_deref() != Some(nonce) {
+        return Err(Error::NonceNotMatching);
+    }
+
+    Ok(claims)
+}
+
+/// The signing algorithm of a provider key, its `alg` or else the one of its key type.
+/// A secret key is only accepted with an explicit `alg`.
+fn key_algorithm(jwk: &Jwk) -> Result<Algorithm> {
+    if let Some(key_alg) = jwk.common.key_algorithm {
+        // -- An encryption algorithm (e.g. `RSA-OAEP`) is not a signing one.
+        return Algorithm::from_str(&key_alg.to_string()).map_err(|_| Error::KeyInvalid);
+    }
+
+    match &jwk.algorithm {
+        AlgorithmParameters::RSA(_) => Ok(Algorithm::RS256),
+        AlgorithmParameters::EllipticCurve(params) => match params.curve {
+            EllipticCurve::P256 => Ok(Algorithm::ES256),
+            EllipticCurve::P384 => Ok(Algorithm::ES384),
+            _ => Err(Error::KeyInvalid),
+        },
+        AlgorithmParameters::OctetKeyPair(_) => Ok(Algorithm::EdDSA),
+        AlgorithmParameters::OctetKey(_) => Err(Error::KeyInvalid),
+    }
+}
+
+fn pkce_challenge(code_verifier: &str) -> String {
+    b64u_encode(Sha256::digest(code_verifier.as_bytes()))
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use jsonwebtoken::{encode, get_current_timestamp, EncodingKey, Header};
+    use serde_json::json;
+
+    const FX_ISSUER: &str = "http://localhost:8090/default";
+    const FX_CLIENT_ID: &str = "rust-web-app";
+    const FX_KEY: &[u8] = b"mock-provider-signing-key-for-tests";
+
+    fn fx_jwks() -> Result<JwkSet> {
+        let jwks = serde_json::from_value(json!({
+            "keys": [{
+                "kty": "oct",
+                "kid": "fx-key",
+                "alg": "HS256",
+                "k": b64u_encode(FX_KEY),
+            }]
+        }))?;
+
+        Ok(jwks)
+    }
+
+    fn fx_id_token(aud: &str, nonce: &str) -> Result<String> {
+        fx_id_token_with_alg(aud, nonce, Algorithm::HS256)
+    }
+
+    fn fx_id_token_with_alg(aud: &str, nonce: &str, alg: Algorithm) -> Result<String> {
+        let header = Header {
+            kid: Some("fx-key".to_string()),
+            ..Header::new(alg)
+        };
+        let claims = json!({
+            "iss": FX_ISSUER,
+            "sub": "fx-subject",
+            "aud": aud,
+            "exp": get_current_timestamp() + 300,
+            "email": "demo1@demo.com",
+            "email_verified": true,
+            "nonce": nonce,
+        });
+
+        Ok(encode(&header, &claims, &EncodingKey::from_secret(FX_KEY))?)
+    }
+
+    #[test]
+    fn test_pkce_rfc7636_vector() -> Result<()> {
+        // -- Setup & Fixtures (RFC 7636, Appendix B)
+        let fx_verifier = "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk";
+        let fx_challenge = "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM";
+
+        // -- Exec
+        let challenge = pkce_challenge(fx_verifier);
+
+        // -- Check
+        assert_eq!(challenge, fx_challenge);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_id_token_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let jwks = fx_jwks()?;
+        let id_token = fx_id_token(FX_CLIENT_ID, "fx-nonce")?;
+
+        // -- Exec
+        let claims = validate_id_token(&id_token, &jwks, FX_ISSUER, FX_CLIENT_ID, "fx-nonce")?;
+
+        // -- Check
+        assert_eq!(claims.sub, "fx-subject");
+        assert_eq!(claims.email.as_deref(), Some("demo1@demo.com"));
+        assert!(claims.email_verified);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_id_token_err() -> Result<()> {
+        // -- Setup & Fixtures
+        let jwks = fx_jwks()?;
+        let other_aud_token = fx_id_token("other-client", "fx-nonce")?;
+        let id_token = fx_id_token(FX_CLIENT_ID, "fx-nonce")?;
+
+        // -- Exec & Check
+        assert!(matches!(
+            validate_id_token(&other_aud_token, &jwks, FX_ISSUER, FX_CLIENT_ID, "fx-nonce"),
+            Err(Error::IdTokenInvalid(_))
+        ));
+        assert_eq!(
+            validate_id_token(&id_token, &jwks, FX_ISSUER, FX_CLIENT_ID, "other-nonce")
+                .map(|claims| claims.sub),
+            Err(Error::NonceNotMatching)
+        );
+        assert_eq!(
+            validate_id_token(
+                &id_token,
+                &JwkSet { keys: vec![] },
+                FX_ISSUER,
+                FX_CLIENT_ID,
+                "fx-nonce"
+            )
+            .map(|claims| claims.sub),
+            Err(Error::KeyNotFound {
+                kid: Some("fx-key".to_string())
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_id_token_err_alg_not_matching() -> Result<()> {
+        // -- Setup & Fixtures
+        let jwks = fx_jwks()?;
+        let fx_id_token = fx_id_token_with_alg(FX_CLIENT_ID, "fx-nonce", Algorithm::HS384)?;
+
+        // -- Exec
+        let res = validate_id_token(&fx_id_token, &jwks, FX_ISSUER, FX_CLIENT_ID, "fx-nonce");
+
+        // -- Check
+        assert_eq!(
+            res.map(|claims| claims.sub),
+            Err(Error::AlgNotMatching {
+                alg: Algorithm::HS384
+            })
+        );
+
+        Ok(())
+    }
+}
+// endregion: --- Tests
@@ -1,4 +1,4 @@
-use super::scheme;
+use super::{scheme, PolicyViolation};
 use derive_more::From;
 use serde::Serialize;
 
@@ -8,7 +8,9 @@ pub type Result<T> = std::result::Result<T, Error>;
 pub enum Error {
     FailSpawnBlockForHash,
     PwdWithSchemeFailedParse,
+    PwdKeyNotFound(String),
     FailSpawnBlockForValidate,
+    PwdPolicyViolated(Vec<PolicyViolation>),
 
     // -- Modules
     #[from]
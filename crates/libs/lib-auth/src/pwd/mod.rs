@@ -1,12 +1,18 @@
 mod error;
+mod policy;
 mod scheme;
 
-use std::str::FromStr;
+use std::{str::FromStr, time::Instant};
 
 use lazy_regex::regex_captures;
+use metrics::{gauge, histogram};
+use tracing::{info_span, Span};
 use uuid::Uuid;
 
+use crate::config::auth_config;
+
 pub use self::error::{Error, Result};
+pub use self::policy::{init_pwd_policy, pwd_policy, PolicyViolation, PwdPolicy};
 pub use self::scheme::{get_scheme, Scheme, SchemeStatus, DEFAULT_SCHEME};
 
 // region:    --- Types
@@ -30,20 +36,45 @@ impl ContentToHash {
 
 /// Hash the password with the default scheme.
 pub async fn hash_pwd(to_hash: ContentToHash) -> Result<String> {
-    tokio::task::spawn_blocking(move || hash_for_scheme(DEFAULT_SCHEME, to_hash))
-        .await
-        .map_err(|_| Error::FailSpawnBlockForHash)?
+    // -- The span is created here, to be a child of the caller span (not of the blocking thread).
+    let span = info_span!("pwd.hash", pwd.scheme = DEFAULT_SCHEME);
+
+    spawn_hashing(span, "hash", DEFAULT_SCHEME.to_string(), move || {
+        hash_for_scheme(DEFAULT_SCHEME, to_hash)
+    })
+    .await
+    .ok_or(Error::FailSpawnBlockForHash)?
+}
+
+/// Check the clear password against the configured password policy.
+///
+/// `identities` are the values the password must not be equal to (e.g., username, email).
+pub fn validate_pwd_policy(pwd: &str, identities: &[&str]) -> Result<()> {
+    let violations = pwd_policy().check(pwd, identities);
+
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::PwdPolicyViolated(violations))
+    }
 }
 
 /// Validate if an ContentToHash matches.
+///
+/// The status is `Outdated` when the hash does not use the default scheme,
+/// its current params, or the current pepper key.
 pub async fn validate_pwd(to_hash: ContentToHash, pwd_ref: String) -> Result<SchemeStatus> {
     let PwdParts {
         scheme_name,
+        key_id,
         hashed,
     } = pwd_ref.parse()?;
 
     // Note: We do first, so that we do not have to  clone the scheme_name.
-    let scheme_status = if scheme_name == DEFAULT_SCHEME {
+    let scheme_status = if scheme_name == DEFAULT_SCHEME
+        && key_id.as_deref() == auth_config().PWD_KEY_ID.as_deref()
+        && !get_scheme(DEFAULT_SCHEME)?.is_outdated(&hashed)
+    {
         SchemeStatus::Ok
     } else {
         SchemeStatus::Outdated
@@ -51,28 +82,115 @@ pub async fn validate_pwd(to_hash: ContentToHash, pwd_ref: String) -> Result<Sch
 
     // Note: Since validate might take some time depending on algo
     //       doing a spawn_blocking to avoid
-    tokio::task::spawn_blocking(move || validate_for_scheme(&scheme_name, to_hash, hashed))
-        .await
-        .map_err(|_| Error::FailSpawnBlockForValidate)??;
+    let span = info_span!("pwd.validate", pwd.scheme = %scheme_name);
+    spawn_hashing(span, "validate", scheme_name.clone(), move || {
+        validate_for_scheme(&scheme_name, key_id.as_deref(), to_hash, hashed)
+    })
+    .await
+    .ok_or(Error::FailSpawnBlockForValidate)??;
 
     // validate_for_scheme(&scheme_name, to_hash, hashed)?;
     Ok(scheme_status)
 }
 
-fn validate_for_scheme(scheme_name: &str, to_hash: ContentToHash, pwd_ref: String) -> Result<()> {
-    let _ = get_scheme(scheme_name)?.validate(&to_hash, &pwd_ref)?;
+/// Check that an already hashed password (e.g., imported from another system)
+/// is tagged with a known scheme, like `#legacy-bcrypt#$2b$...`.
+///
+/// The hash itself is stored verbatim, and upgraded on the first successful login.
+pub fn validate_pwd_ref_scheme(pwd_ref: &str) -> Result<()> {
+    let PwdParts {
+        scheme_name,
+        key_id,
+        ..
+    } = pwd_ref.parse()?;
+    get_scheme(&scheme_name)?;
+    get_pwd_key(key_id.as_deref())?;
+
+    Ok(())
+}
+
+/// Run a hashing on the blocking threads, in its span, and record its duration.
+///
+/// The `pwd_hash_queue_depth` gauge counts the hashings waiting for, or running on,
+/// a blocking thread. `None` when the blocking task failed.
+async fn spawn_hashing<T: Send + 'static>(
+    span: Span,
+    operation: &'static str,
+    scheme: String,
+    hashing: impl FnOnce() -> T + Send + 'static,
+) -> Option<T> {
+    let _queued = QueuedHashing::new();
+
+    tokio::task::spawn_blocking(move || {
+        let start = Instant::now();
+        let output = span.in_scope(hashing);
+        histogram!("pwd_hash_duration_seconds", "operation" => operation, "scheme" => scheme)
+            .record(start.elapsed().as_secs_f64());
+
+        output
+    })
+    .await
+    .ok()
+}
+
+/// One hashing in the `pwd_hash_queue_depth` gauge, until dropped (even when cancelled).
+struct QueuedHashing;
+
+impl QueuedHashing {
+    fn new() -> Self {
+        gauge!("pwd_hash_queue_depth").increment(1.0);
+        QueuedHashing
+    }
+}
+
+impl Drop for QueuedHashing {
+    fn drop(&mut self) {
+        gauge!("pwd_hash_queue_depth").decrement(1.0);
+    }
+}
+
+fn validate_for_scheme(
+    scheme_name: &str,
+    key_id: Option<&str>,
+    to_hash: ContentToHash,
+    pwd_ref: String,
+) -> Result<()> {
+    let key = get_pwd_key(key_id)?;
+    let _ = get_scheme(scheme_name)?.validate(&to_hash, key, &pwd_ref)?;
     Ok(())
 }
 
+/// Hash with the current pepper key, and tag the hash with its scheme and key id,
+/// `#_scheme_id_#...` or `#_scheme_id_:_key_id_#...`.
 fn hash_for_scheme(scheme_name: &str, to_hash: ContentToHash) -> Result<String> {
-    let pwd_hashed = get_scheme(scheme_name)?.hash(&to_hash)?;
+    let key_id = auth_config().PWD_KEY_ID.as_deref();
+    let key = get_pwd_key(key_id)?;
+    let pwd_hashed = get_scheme(scheme_name)?.hash(&to_hash, key)?;
 
-    Ok(format!("#{scheme_name}#{pwd_hashed}"))
+    match key_id {
+        Some(key_id) => Ok(format!("#{scheme_name}:{key_id}#{pwd_hashed}")),
+        None => Ok(format!("#{scheme_name}#{pwd_hashed}")),
+    }
+}
+
+/// The pepper key named `key_id`. Hashes without key id use the original `PWD_KEY`.
+fn get_pwd_key(key_id: Option<&str>) -> Result<&'static [u8]> {
+    let config = auth_config();
+    match key_id {
+        None => Ok(config.PWD_KEY.as_slice()),
+        Some(key_id) => config
+            .PWD_KEYS
+            .get(key_id)
+            .map(|key| key.as_slice())
+            .ok_or_else(|| Error::PwdKeyNotFound(key_id.to_string())),
+    }
 }
 
 struct PwdParts {
     /// The scheme only (e.g., "01")
     scheme_name: String,
+    /// The pepper key id, if not the original `PWD_KEY`.
+    key_id: Option<String>,
     /// The hashed password,
     hashed: String,
 }
@@ -82,11 +200,12 @@ impl FromStr for PwdParts {
 
     fn from_str(pwd_with_scheme: &str) -> Result<Self> {
         regex_captures!(
-            r#"^#(\w+)#(.*)"#, // a literal regex
+            r#"^#([\w-]+)(?::([\w-]+))?#(.*)"#, // a literal regex
             pwd_with_scheme
         )
-        .map(|(_, scheme, hashed)| Self {
+        .map(|(_, scheme, key_id, hashed)| Self {
             scheme_name: scheme.to_string(),
+            key_id: (!key_id.is_empty()).then(|| key_id.to_string()),
             hashed: hashed.to_string(),
         })
         .ok_or(Error::PwdWithSchemeFailedParse)
@@ -129,7 +248,7 @@ mod tests {
             content: "hello world".to_string(),
             salt: fx_salt,
         };
-        let fx_pwd_ref = "#02#$argon2id$v=19$m=19456,t=2,p=1$8F6JYdatQIaeeKbeBl5UUw$TaRnmmbDdQ1aTzk2qQ2yQzPQoZfnKqhrfuTH/TRP5V4".to_string();
+        let fx_pwd_ref = "#03#$argon2id$v=19$m=19456,t=2,p=1$8F6JYdatQIaeeKbeBl5UUw$TaRnmmbDdQ1aTzk2qQ2yQzPQoZfnKqhrfuTH/TRP5V4".to_string();
 
         // -- Exec
         let pwd_validate = validate_pwd(fx_to_hash.clone(), fx_pwd_ref).await?;
@@ -140,6 +259,62 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_multi_scheme_legacy_outdated() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_salt = Uuid::parse_str("f05e8961-d6ad-4086-9e78-a6de065e5453")?;
+        let fx_to_hash = ContentToHash {
+            content: "hello world".to_string(),
+            salt: fx_salt,
+        };
+        let fx_pwd_ref = format!("#legacy-bcrypt#{}", bcrypt::hash("hello world", 4)?);
+
+        // -- Exec
+        validate_pwd_ref_scheme(&fx_pwd_ref)?;
+        let pwd_validate = validate_pwd(fx_to_hash, fx_pwd_ref).await?;
+
+        // -- Check
+        assert!(matches!(pwd_validate, SchemeStatus::Outdated));
+        assert!(validate_pwd_ref_scheme("#legacy-md5#5eb63bbbe01eeed093cb22bb8f5acdc3").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_pwd_parts_key_id() -> Result<()> {
+        // -- Exec
+        let with_key_id: PwdParts = "#03:2025-01#$argon2id$v=19$...".parse()?;
+        let without_key_id: PwdParts = "#03#$argon2id$v=19$...".parse()?;
+
+        // -- Check
+        assert_eq!(with_key_id.scheme_name, "03");
+        assert_eq!(with_key_id.key_id.as_deref(), Some("2025-01"));
+        assert_eq!(with_key_id.hashed, "$argon2id$v=19$...");
+        assert_eq!(without_key_id.scheme_name, "03");
+        assert_eq!(without_key_id.key_id, None);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_multi_scheme_unknown_key_id() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_salt = Uuid::parse_str("f05e8961-d6ad-4086-9e78-a6de065e5453")?;
+        let fx_to_hash = ContentToHash {
+            content: "hello world".to_string(),
+            salt: fx_salt,
+        };
+        let fx_pwd_ref = "#03:unknown-key#$argon2id$v=19$m=19456,t=2,p=1$8F6JYdatQIaeeKbeBl5UUw$TaRnmmbDdQ1aTzk2qQ2yQzPQoZfnKqhrfuTH/TRP5V4".to_string();
+
+        // -- Exec
+        let res = validate_pwd(fx_to_hash, fx_pwd_ref).await;
+
+        // -- Check
+        assert!(matches!(res, Err(Error::PwdKeyNotFound(key_id)) if key_id == "unknown-key"));
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_multi_scheme_invalid_content() -> Result<()> {
         // -- Setup & Fixtures
@@ -0,0 +1,208 @@
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+use lib_utils::config::{Error, Result};
+use serde::Serialize;
+
+use crate::config::auth_config;
+
+static INSTANCE: OnceLock<PwdPolicy> = OnceLock::new();
+
+/// Load the policy from the auth config at startup, so a missing common list fails there
+/// rather than on the first password check.
+pub fn init_pwd_policy() -> Result<()> {
+    INSTANCE
+        .set(PwdPolicy::load_from_config()?)
+        .map_err(|_| Error::AlreadyInitialized("pwd_policy"))
+}
+
+/// The policy set by `init_pwd_policy`, or else loaded on first use (e.g., in the tests).
+pub fn pwd_policy() -> &'static PwdPolicy {
+    INSTANCE.get_or_init(|| {
+        PwdPolicy::load_from_config()
+            .unwrap_or_else(|ex| panic!("FATAL - WHILE LOADING PWD POLICY - Cause: {ex}"))
+    })
+}
+
+/// A single rule of the password policy that a password failed.
+#[derive(Clone, Debug, Serialize, PartialEq)]
+#[serde(tag = "rule", content = "detail")]
+#[allow(non_camel_case_types)]
+pub enum PolicyViolation {
+    TOO_SHORT { min: usize },
+    TOO_LONG { max: usize },
+    MISSING_LOWERCASE,
+    MISSING_UPPERCASE,
+    MISSING_DIGIT,
+    MISSING_SYMBOL,
+    SAME_AS_IDENTITY,
+    TOO_COMMON,
+}
+
+pub struct PwdPolicy {
+    pub min_len: usize,
+    /// Bound the password size, so the argon2 cost stays predictable.
+    pub max_len: usize,
+    pub require_lowercase: bool,
+    pub require_uppercase: bool,
+    pub require_digit: bool,
+    pub require_symbol: bool,
+    /// Lowercased common passwords.
+    pub common_pwds: HashSet<String>,
+}
+
+impl PwdPolicy {
+    fn load_from_config() -> Result<Self> {
+        let config = auth_config();
+        let common_list = std::fs::read_to_string(&config.PWD_COMMON_LIST_FILE).map_err(|ex| {
+            Error::FileCannotRead {
+                path: config.PWD_COMMON_LIST_FILE.clone(),
+                cause: ex.to_string(),
+            }
+        })?;
+
+        Ok(PwdPolicy {
+            min_len: config.PWD_MIN_LEN,
+            max_len: config.PWD_MAX_LEN,
+            require_lowercase: config.PWD_REQUIRE_LOWERCASE,
+            require_uppercase: config.PWD_REQUIRE_UPPERCASE,
+            require_digit: config.PWD_REQUIRE_DIGIT,
+            require_symbol: config.PWD_REQUIRE_SYMBOL,
+            common_pwds: parse_common_list(&common_list),
+        })
+    }
+
+    /// Check the password against every rule, and return all the violated ones.
+    ///
+    /// `identities` are the values the password must not be equal to (e.g., username, email).
+    pub fn check(&self, pwd: &str, identities: &[&str]) -> Vec<PolicyViolation> {
+        let mut violations = Vec::new();
+        let len = pwd.chars().count();
+
+        if len < self.min_len {
+            violations.push(PolicyViolation::TOO_SHORT { min: self.min_len });
+        }
+        if len > self.max_len {
+            violations.push(PolicyViolation::TOO_LONG { max: self.max_len });
+        }
+        if self.require_lowercase && !pwd.chars().any(char::is_lowercase) {
+            violations.push(PolicyViolation::MISSING_LOWERCASE);
+        }
+        if self.require_uppercase && !pwd.chars().any(char::is_uppercase) {
+            violations.push(PolicyViolation::MISSING_UPPERCASE);
+        }
+        if self.require_digit && !pwd.chars().any(|c| c.is_ascii_digit()) {
+            violations.push(PolicyViolation::MISSING_DIGIT);
+        }
+        if self.require_symbol && !pwd.chars().any(|c| !c.is_alphanumeric()) {
+            violations.push(PolicyViolation::MISSING_SYMBOL);
+        }
+        if identities
+            .iter()
+            .any(|identity| identity.eq_ignore_ascii_case(pwd))
+        {
+            violations.push(PolicyViolation::SAME_AS_IDENTITY);
+        }
+        if self.common_pwds.contains(&pwd.to_lowercase()) {
+            violations.push(PolicyViolation::TOO_COMMON);
+        }
+
+        violations
+    }
+}
+
+fn parse_common_list(content: &str) -> HashSet<String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_lowercase)
+        .collect()
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+
+    fn fx_policy() -> PwdPolicy {
+        PwdPolicy {
+            min_len: 8,
+            max_len: 16,
+            require_lowercase: true,
+            require_uppercase: true,
+            require_digit: true,
+            require_symbol: true,
+            common_pwds: parse_common_list("# comment\nPassword1!\n\n"),
+        }
+    }
+
+    #[test]
+    fn test_policy_check_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let policy = fx_policy();
+
+        // -- Exec
+        let violations = policy.check("Tr0ub4dor&3", &["demo1@demo.com"]);
+
+        // -- Check
+        assert!(
+            violations.is_empty(),
+            "should be empty but was {violations:?}"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_policy_check_all_violations() -> Result<()> {
+        // -- Setup & Fixtures
+        let policy = fx_policy();
+        let fx_violations = vec![
+            PolicyViolation::TOO_SHORT { min: 8 },
+            PolicyViolation::MISSING_UPPERCASE,
+            PolicyViolation::MISSING_DIGIT,
+            PolicyViolation::MISSING_SYMBOL,
+        ];
+
+        // -- Exec
+        let violations = policy.check("abc", &[]);
+
+        // -- Check
+        assert_eq!(violations, fx_violations);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_policy_check_too_long() -> Result<()> {
+        // -- Setup & Fixtures
+        let policy = fx_policy();
+
+        // -- Exec
+        let violations = policy.check("Abcdefgh1!abcdefgh", &[]);
+
+        // -- Check
+        assert_eq!(violations, vec![PolicyViolation::TOO_LONG { max: 16 }]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_policy_check_same_as_identity_and_common() -> Result<()> {
+        // -- Setup & Fixtures
+        let policy = fx_policy();
+
+        // -- Exec
+        let same_as_identity = policy.check("Demo1@Demo.com", &["demo1@demo.com"]);
+        let too_common = policy.check("pAssword1!", &[]);
+
+        // -- Check
+        assert_eq!(same_as_identity, vec![PolicyViolation::SAME_AS_IDENTITY]);
+        assert_eq!(too_common, vec![PolicyViolation::TOO_COMMON]);
+
+        Ok(())
+    }
+}
+// endregion: --- Tests
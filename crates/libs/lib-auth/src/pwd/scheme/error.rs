@@ -8,6 +8,7 @@ pub enum Error {
     PwdInValidate,
     Salt,
     Hash,
+    HashNotSupported,
     SchemeNotFound(String),
 }
 
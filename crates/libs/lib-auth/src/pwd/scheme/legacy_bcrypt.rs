@@ -0,0 +1,60 @@
+use crate::pwd::{scheme::Scheme, ContentToHash};
+
+use super::{Error, Result};
+
+/// Imported bcrypt hashes (`$2a$`, `$2b$`, `$2y$`), for validation only.
+///
+/// The hash carries its own salt, the `ContentToHash` salt and the pepper key are not used.
+pub struct LegacyBcrypt;
+
+impl Scheme for LegacyBcrypt {
+    fn hash(&self, _to_hash: &ContentToHash, _key: &[u8]) -> Result<String> {
+        Err(Error::HashNotSupported)
+    }
+
+    fn validate(&self, to_hash: &ContentToHash, _key: &[u8], pwd_ref: &str) -> Result<()> {
+        match bcrypt::verify(&to_hash.content, pwd_ref) {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(Error::PwdInValidate),
+            Err(_) => Err(Error::Hash),
+        }
+    }
+
+    fn is_outdated(&self, _pwd_ref: &str) -> bool {
+        true
+    }
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_legacy_bcrypt_validate() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_to_hash = ContentToHash {
+            content: "hello world".to_string(),
+            salt: Uuid::parse_str("f05e8961-d6ad-4086-9e78-a6de065e5453")?,
+        };
+        let fx_wrong_to_hash = ContentToHash {
+            content: "hello world2".to_string(),
+            salt: fx_to_hash.salt,
+        };
+        let fx_pwd_ref = bcrypt::hash(&fx_to_hash.content, 4)?;
+
+        // -- Exec & Check
+        let scheme = LegacyBcrypt;
+        assert!(fx_pwd_ref.starts_with("$2b$"));
+        assert!(scheme.validate(&fx_to_hash, &[], &fx_pwd_ref).is_ok());
+        assert!(scheme
+            .validate(&fx_wrong_to_hash, &[], &fx_pwd_ref)
+            .is_err());
+        assert!(scheme.hash(&fx_to_hash, &[]).is_err());
+
+        Ok(())
+    }
+}
+// endregion: --- Tests
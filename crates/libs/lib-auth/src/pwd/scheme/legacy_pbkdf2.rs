@@ -0,0 +1,84 @@
+use pbkdf2::{
+    password_hash::{PasswordHash, PasswordVerifier},
+    Pbkdf2,
+};
+
+use crate::pwd::{scheme::Scheme, ContentToHash};
+
+use super::{Error, Result};
+
+/// Imported PBKDF2 hashes in the PHC format (`$pbkdf2-sha256$`, `$pbkdf2-sha512$`),
+/// for validation only.
+///
+/// The hash carries its own salt, the `ContentToHash` salt and the pepper key are not used.
+pub struct LegacyPbkdf2;
+
+impl Scheme for LegacyPbkdf2 {
+    fn hash(&self, _to_hash: &ContentToHash, _key: &[u8]) -> Result<String> {
+        Err(Error::HashNotSupported)
+    }
+
+    fn validate(&self, to_hash: &ContentToHash, _key: &[u8], pwd_ref: &str) -> Result<()> {
+        let parsed_hash_ref = PasswordHash::new(pwd_ref).map_err(|_| Error::Hash)?;
+
+        Pbkdf2
+            .verify_password(to_hash.content.as_bytes(), &parsed_hash_ref)
+            .map_err(|_| Error::PwdInValidate)
+    }
+
+    fn is_outdated(&self, _pwd_ref: &str) -> bool {
+        true
+    }
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use pbkdf2::{
+        password_hash::{PasswordHasher, SaltString},
+        Algorithm, Params,
+    };
+    use uuid::Uuid;
+
+    #[test]
+    fn test_legacy_pbkdf2_validate() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_to_hash = ContentToHash {
+            content: "hello world".to_string(),
+            salt: Uuid::parse_str("f05e8961-d6ad-4086-9e78-a6de065e5453")?,
+        };
+        let fx_wrong_to_hash = ContentToHash {
+            content: "hello world2".to_string(),
+            salt: fx_to_hash.salt,
+        };
+        let fx_salt =
+            SaltString::encode_b64(b"legacy-salt").map_err(|ex| anyhow::anyhow!("{ex}"))?;
+        let fx_params = Params {
+            rounds: 1_000,
+            output_length: 32,
+        };
+        let fx_pwd_ref = Pbkdf2
+            .hash_password_customized(
+                fx_to_hash.content.as_bytes(),
+                Some(Algorithm::Pbkdf2Sha256.ident()),
+                None,
+                fx_params,
+                &fx_salt,
+            )
+            .map_err(|ex| anyhow::anyhow!("{ex}"))?
+            .to_string();
+
+        // -- Exec & Check
+        let scheme = LegacyPbkdf2;
+        assert!(fx_pwd_ref.starts_with("$pbkdf2-sha256$"));
+        assert!(scheme.validate(&fx_to_hash, &[], &fx_pwd_ref).is_ok());
+        assert!(scheme
+            .validate(&fx_wrong_to_hash, &[], &fx_pwd_ref)
+            .is_err());
+
+        Ok(())
+    }
+}
+// endregion: --- Tests
@@ -0,0 +1,80 @@
+use scrypt::{
+    password_hash::{PasswordHash, PasswordVerifier},
+    Scrypt,
+};
+
+use crate::pwd::{scheme::Scheme, ContentToHash};
+
+use super::{Error, Result};
+
+/// Imported scrypt hashes in the PHC format (`$scrypt$`), for validation only.
+///
+/// The hash carries its own salt, the `ContentToHash` salt and the pepper key are not used.
+pub struct LegacyScrypt;
+
+impl Scheme for LegacyScrypt {
+    fn hash(&self, _to_hash: &ContentToHash, _key: &[u8]) -> Result<String> {
+        Err(Error::HashNotSupported)
+    }
+
+    fn validate(&self, to_hash: &ContentToHash, _key: &[u8], pwd_ref: &str) -> Result<()> {
+        let parsed_hash_ref = PasswordHash::new(pwd_ref).map_err(|_| Error::Hash)?;
+
+        Scrypt
+            .verify_password(to_hash.content.as_bytes(), &parsed_hash_ref)
+            .map_err(|_| Error::PwdInValidate)
+    }
+
+    fn is_outdated(&self, _pwd_ref: &str) -> bool {
+        true
+    }
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use scrypt::{
+        password_hash::{PasswordHasher, SaltString},
+        Params,
+    };
+    use uuid::Uuid;
+
+    #[test]
+    fn test_legacy_scrypt_validate() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_to_hash = ContentToHash {
+            content: "hello world".to_string(),
+            salt: Uuid::parse_str("f05e8961-d6ad-4086-9e78-a6de065e5453")?,
+        };
+        let fx_wrong_to_hash = ContentToHash {
+            content: "hello world2".to_string(),
+            salt: fx_to_hash.salt,
+        };
+        let fx_salt =
+            SaltString::encode_b64(b"legacy-salt").map_err(|ex| anyhow::anyhow!("{ex}"))?;
+        let fx_params = Params::new(4, 8, 1, 32).map_err(|ex| anyhow::anyhow!("{ex}"))?;
+        let fx_pwd_ref = Scrypt
+            .hash_password_customized(
+                fx_to_hash.content.as_bytes(),
+                None,
+                None,
+                fx_params,
+                &fx_salt,
+            )
+            .map_err(|ex| anyhow::anyhow!("{ex}"))?
+            .to_string();
+
+        // -- Exec & Check
+        let scheme = LegacyScrypt;
+        assert!(fx_pwd_ref.starts_with("$scrypt$"));
+        assert!(scheme.validate(&fx_to_hash, &[], &fx_pwd_ref).is_ok());
+        assert!(scheme
+            .validate(&fx_wrong_to_hash, &[], &fx_pwd_ref)
+            .is_err());
+
+        Ok(())
+    }
+}
+// endregion: --- Tests
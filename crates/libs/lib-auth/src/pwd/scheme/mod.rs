@@ -1,6 +1,10 @@
 mod error;
+mod legacy_bcrypt;
+mod legacy_pbkdf2;
+mod legacy_scrypt;
 mod scheme_01;
 mod scheme_02;
+mod scheme_03;
 
 use enum_dispatch::enum_dispatch;
 
@@ -8,31 +12,46 @@ pub use self::error::{Error, Result};
 
 use super::ContentToHash;
 
-pub const DEFAULT_SCHEME: &str = "02";
+pub const DEFAULT_SCHEME: &str = "03";
 
 #[derive(Debug)]
 pub enum SchemeStatus {
     Ok,       // The pwd uses the latest scheme. All good.
-    Outdated, // The pwd uses and old scheme.
+    Outdated, // The pwd uses and old scheme, or weaker params than configured.
 }
 
 #[enum_dispatch]
 pub trait Scheme {
-    fn hash(&self, to_hash: &ContentToHash) -> Result<String>;
+    /// Hash with `key` as pepper (HMAC key or argon2 secret, depending on the scheme).
+    fn hash(&self, to_hash: &ContentToHash, key: &[u8]) -> Result<String>;
 
-    fn validate(&self, to_hash: &ContentToHash, pwd_ref: &str) -> Result<()>;
+    fn validate(&self, to_hash: &ContentToHash, key: &[u8], pwd_ref: &str) -> Result<()>;
+
+    /// Whether `pwd_ref` was hashed with weaker params than the current ones.
+    fn is_outdated(&self, _pwd_ref: &str) -> bool {
+        false
+    }
 }
 
 #[enum_dispatch(Scheme)]
 pub enum SchemeDispatcher {
     Scheme01(scheme_01::Scheme01),
     Scheme02(scheme_02::Scheme02),
+    Scheme03(scheme_03::Scheme03),
+    LegacyBcrypt(legacy_bcrypt::LegacyBcrypt),
+    LegacyPbkdf2(legacy_pbkdf2::LegacyPbkdf2),
+    LegacyScrypt(legacy_scrypt::LegacyScrypt),
 }
 
 pub fn get_scheme(scheme_name: &str) -> Result<impl Scheme> {
     match scheme_name {
         "01" => Ok(SchemeDispatcher::Scheme01(scheme_01::Scheme01)),
         "02" => Ok(SchemeDispatcher::Scheme02(scheme_02::Scheme02)),
+        "03" => Ok(SchemeDispatcher::Scheme03(scheme_03::Scheme03)),
+        // -- Imported from other systems, validate only.
+        "legacy-bcrypt" => Ok(SchemeDispatcher::LegacyBcrypt(legacy_bcrypt::LegacyBcrypt)),
+        "legacy-pbkdf2" => Ok(SchemeDispatcher::LegacyPbkdf2(legacy_pbkdf2::LegacyPbkdf2)),
+        "legacy-scrypt" => Ok(SchemeDispatcher::LegacyScrypt(legacy_scrypt::LegacyScrypt)),
         _ => Err(Error::SchemeNotFound(scheme_name.to_string())),
     }
 }
@@ -2,20 +2,19 @@ use hmac::{Hmac, Mac};
 use lib_utils::b64::b64u_encode;
 use sha2::Sha512;
 
-use crate::{config::auth_config, pwd::ContentToHash};
+use crate::pwd::ContentToHash;
 
 use super::{Error, Result, Scheme};
 
 pub struct Scheme01;
 
 impl Scheme for Scheme01 {
-    fn hash(&self, to_hash: &ContentToHash) -> Result<String> {
-        let key = &auth_config().PWD_KEY;
+    fn hash(&self, to_hash: &ContentToHash, key: &[u8]) -> Result<String> {
         hash(key, to_hash)
     }
 
-    fn validate(&self, to_hash: &ContentToHash, raw_pwd_ref: &str) -> Result<()> {
-        let raw_pwd_new = self.hash(to_hash)?;
+    fn validate(&self, to_hash: &ContentToHash, key: &[u8], raw_pwd_ref: &str) -> Result<()> {
+        let raw_pwd_new = self.hash(to_hash, key)?;
         if raw_pwd_new == raw_pwd_ref {
             Ok(())
         } else {
@@ -1,7 +1,4 @@
-use std::sync::OnceLock;
-
 use super::{Error, Result};
-use crate::config::auth_config;
 use crate::pwd::{scheme::Scheme, ContentToHash};
 use argon2::password_hash::SaltString;
 use argon2::{
@@ -11,8 +8,8 @@ use argon2::{
 pub struct Scheme02;
 
 impl Scheme for Scheme02 {
-    fn hash(&self, to_hash: &ContentToHash) -> Result<String> {
-        let argon2 = get_argon2();
+    fn hash(&self, to_hash: &ContentToHash, key: &[u8]) -> Result<String> {
+        let argon2 = get_argon2(key)?;
 
         let salt_b64 = SaltString::encode_b64(to_hash.salt.as_bytes()).map_err(|_| Error::Salt)?;
 
@@ -24,8 +21,8 @@ impl Scheme for Scheme02 {
         Ok(pwd)
     }
 
-    fn validate(&self, to_hash: &ContentToHash, pwd_ref: &str) -> Result<()> {
-        let argon2 = get_argon2();
+    fn validate(&self, to_hash: &ContentToHash, key: &[u8], pwd_ref: &str) -> Result<()> {
+        let argon2 = get_argon2(key)?;
 
         let parsed_hash_ref = PasswordHash::new(pwd_ref).map_err(|_| Error::Hash)?;
 
@@ -35,25 +32,21 @@ impl Scheme for Scheme02 {
     }
 }
 
-fn get_argon2() -> &'static Argon2<'static> {
-    static INSTANCE: OnceLock<Argon2<'static>> = OnceLock::new();
-
-    INSTANCE.get_or_init(|| {
-        let key = &auth_config().PWD_KEY;
-        Argon2::new_with_secret(
-            key,
-            Algorithm::default(),
-            Version::default(),
-            Params::default(),
-        )
-        .unwrap() // TODO: needs to fail early
-    })
+fn get_argon2(key: &[u8]) -> Result<Argon2<'_>> {
+    Argon2::new_with_secret(
+        key,
+        Algorithm::default(),
+        Version::default(),
+        Params::default(),
+    )
+    .map_err(|_| Error::Key)
 }
 
 // region:    --- Tests
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::auth_config;
     use crate::pwd::ContentToHash;
     use anyhow::Result;
     use uuid::Uuid;
@@ -69,7 +62,7 @@ mod tests {
 
         // -- Exec
         let scheme = Scheme02;
-        let res = scheme.hash(&fx_to_hash)?;
+        let res = scheme.hash(&fx_to_hash, &auth_config().PWD_KEY)?;
 
         // -- Check
         assert_eq!(res, fx_res);
@@ -90,7 +83,7 @@ mod tests {
         // -- Exec
         let scheme = Scheme02;
         // $argon2id$v=19$m=19456,t=2,p=1$8F6JYdatQIaeeKbeBl5UUw$6bZW7re4W2G4rmxVI+9TttSmnU9zSO35K7UsEnMhe10
-        let res = scheme.hash(&fx_to_hash)?;
+        let res = scheme.hash(&fx_to_hash, &auth_config().PWD_KEY)?;
 
         // -- Check
         assert_ne!(res, fx_res);
@@ -111,7 +104,7 @@ mod tests {
         // -- Exec
         let scheme = Scheme02;
         // $argon2id$v=19$m=19456,t=2,p=1$8F6JYdatQIaeeKbeBl5UUQ$PAJGAasj0uAw82Kl9PvuIAGC6CS0NAZrBNYMxF0eLQo
-        let res = scheme.hash(&fx_to_hash)?;
+        let res = scheme.hash(&fx_to_hash, &auth_config().PWD_KEY)?;
 
         // -- Check
         assert_ne!(res, fx_res);
@@ -130,7 +123,7 @@ mod tests {
 
         // -- Exec
         let scheme = Scheme02;
-        let res = scheme.validate(&fx_to_hash, &fx_pass_ref);
+        let res = scheme.validate(&fx_to_hash, &auth_config().PWD_KEY, &fx_pass_ref);
 
         // -- Check
         assert!(res.is_ok());
@@ -149,7 +142,7 @@ mod tests {
 
         // -- Exec
         let scheme = Scheme02;
-        let res = scheme.validate(&fx_to_hash, &fx_pass_ref);
+        let res = scheme.validate(&fx_to_hash, &auth_config().PWD_KEY, &fx_pass_ref);
 
         // -- Check
         assert!(res.is_ok());
@@ -169,7 +162,7 @@ mod tests {
 
         // -- Exec
         let scheme = Scheme02;
-        let res = scheme.validate(&fx_to_hash, &fx_pass_ref);
+        let res = scheme.validate(&fx_to_hash, &auth_config().PWD_KEY, &fx_pass_ref);
 
         // -- Check
         assert!(res.is_err());
@@ -0,0 +1,145 @@
+use std::sync::OnceLock;
+
+use super::{Error, Result};
+use crate::config::auth_config;
+use crate::pwd::{scheme::Scheme, ContentToHash};
+use argon2::password_hash::SaltString;
+use argon2::{
+    Algorithm, Argon2, Params, PasswordHash, PasswordHasher as _, PasswordVerifier, Version,
+};
+
+/// Argon2id, with the memory, iteration and parallelism costs from the config.
+pub struct Scheme03;
+
+impl Scheme for Scheme03 {
+    fn hash(&self, to_hash: &ContentToHash, key: &[u8]) -> Result<String> {
+        let argon2 = get_argon2(key)?;
+
+        let salt_b64 = SaltString::encode_b64(to_hash.salt.as_bytes()).map_err(|_| Error::Salt)?;
+
+        let pwd = argon2
+            .hash_password(to_hash.content.as_bytes(), &salt_b64)
+            .map_err(|_| Error::Hash)?
+            .to_string();
+
+        Ok(pwd)
+    }
+
+    fn validate(&self, to_hash: &ContentToHash, key: &[u8], pwd_ref: &str) -> Result<()> {
+        let argon2 = get_argon2(key)?;
+
+        // Note: The verification uses the params stored in `pwd_ref`,
+        //       so hashes made with weaker params still validate.
+        let parsed_hash_ref = PasswordHash::new(pwd_ref).map_err(|_| Error::Hash)?;
+
+        argon2
+            .verify_password(to_hash.content.as_bytes(), &parsed_hash_ref)
+            .map_err(|_| Error::PwdInValidate)
+    }
+
+    fn is_outdated(&self, pwd_ref: &str) -> bool {
+        let Ok(parsed_hash_ref) = PasswordHash::new(pwd_ref) else {
+            return true;
+        };
+        let Ok(params_ref) = Params::try_from(&parsed_hash_ref) else {
+            return true;
+        };
+        let params = get_params();
+
+        parsed_hash_ref.algorithm != Algorithm::Argon2id.ident()
+            || params_ref.m_cost() < params.m_cost()
+            || params_ref.t_cost() < params.t_cost()
+            || params_ref.p_cost() < params.p_cost()
+    }
+}
+
+fn get_argon2(key: &[u8]) -> Result<Argon2<'_>> {
+    Argon2::new_with_secret(
+        key,
+        Algorithm::Argon2id,
+        Version::default(),
+        get_params().clone(),
+    )
+    .map_err(|_| Error::Key)
+}
+
+fn get_params() -> &'static Params {
+    static INSTANCE: OnceLock<Params> = OnceLock::new();
+
+    INSTANCE.get_or_init(|| {
+        let config = auth_config();
+        Params::new(
+            config.PWD_ARGON2_M_COST,
+            config.PWD_ARGON2_T_COST,
+            config.PWD_ARGON2_P_COST,
+            None,
+        )
+        .unwrap_or_else(|ex| panic!("FATAL - WHILE LOADING ARGON2 PARAMS - Cause: {ex:?}"))
+    })
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pwd::ContentToHash;
+    use anyhow::Result;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_scheme_03_hash_and_validate_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_to_hash = ContentToHash {
+            content: "hello world".to_string(),
+            salt: Uuid::parse_str("f05e8961-d6ad-4086-9e78-a6de065e5453")?,
+        };
+
+        // -- Exec
+        let scheme = Scheme03;
+        let pwd_ref = scheme.hash(&fx_to_hash, &auth_config().PWD_KEY)?;
+
+        // -- Check
+        assert!(scheme
+            .validate(&fx_to_hash, &auth_config().PWD_KEY, &pwd_ref)
+            .is_ok());
+        assert!(!scheme.is_outdated(&pwd_ref));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_scheme_03_is_outdated_weaker_params() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_to_hash = ContentToHash {
+            content: "hello world".to_string(),
+            salt: Uuid::parse_str("f05e8961-d6ad-4086-9e78-a6de065e5453")?,
+        };
+        let config = auth_config();
+        let fx_weak_params = Params::new(8, 1, 1, None).map_err(|ex| anyhow::anyhow!("{ex}"))?;
+        let fx_weak_argon2 = Argon2::new_with_secret(
+            &config.PWD_KEY,
+            Algorithm::Argon2id,
+            Version::default(),
+            fx_weak_params,
+        )
+        .map_err(|ex| anyhow::anyhow!("{ex}"))?;
+        let fx_salt_b64 = SaltString::encode_b64(fx_to_hash.salt.as_bytes())
+            .map_err(|ex| anyhow::anyhow!("{ex}"))?;
+        let fx_pwd_ref = fx_weak_argon2
+            .hash_password(fx_to_hash.content.as_bytes(), &fx_salt_b64)
+            .map_err(|ex| anyhow::anyhow!("{ex}"))?
+            .to_string();
+
+        // -- Exec
+        let scheme = Scheme03;
+
+        // -- Check
+        assert!(scheme
+            .validate(&fx_to_hash, &auth_config().PWD_KEY, &fx_pwd_ref)
+            .is_ok());
+        assert!(scheme.is_outdated(&fx_pwd_ref));
+
+        Ok(())
+    }
+}
+// endregion: --- Tests
@@ -11,15 +11,68 @@ use crate::auth_config;
 struct Claims {
     sub: String,
     exp: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    jti: Option<String>,
+    /// The acting party (RFC 8693), the impersonator.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    act: Option<Actor>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Actor {
+    sub: String,
+}
+
+/// The claims of a valid jwt.
+#[derive(Debug, PartialEq)]
+pub struct JwtSession {
+    pub sub: String,
+    /// The session the jwt was issued for (`jti` claim),
+    /// `None` for a jwt issued by `encode_jwt`.
+    pub session_id: Option<String>,
+    /// The user acting as `sub` (`act` claim), see `encode_impersonation_jwt`.
+    pub impersonator_id: Option<String>,
 }
 
 pub fn encode_jwt(sub: &str, secret_key: &[u8]) -> Result<String> {
-    let jwt_duration_sec = auth_config().TOKEN_DURATION_SEC_USIZE;
+    let duration_sec = auth_config().TOKEN_DURATION.as_secs() as usize;
+    inner_encode_jwt(sub, None, None, duration_sec, secret_key)
+}
+
+/// Encode a jwt bound to `session_id`, so it can be revoked with its session.
+pub fn encode_session_jwt(sub: &str, session_id: &str, secret_key: &[u8]) -> Result<String> {
+    let duration_sec = auth_config().TOKEN_DURATION.as_secs() as usize;
+    inner_encode_jwt(sub, Some(session_id), None, duration_sec, secret_key)
+}
+
+/// Encode a short-lived jwt of `sub`, used by `impersonator_id` to act as `sub`.
+///
+/// The `secret_key` is the token salt of `sub`, as for its own jwt.
+pub fn encode_impersonation_jwt(
+    sub: &str,
+    impersonator_id: &str,
+    secret_key: &[u8],
+) -> Result<String> {
+    let duration_sec = auth_config().IMPERSONATION_TOKEN_DURATION.as_secs() as usize;
+    inner_encode_jwt(sub, None, Some(impersonator_id), duration_sec, secret_key)
+}
+
+fn inner_encode_jwt(
+    sub: &str,
+    jti: Option<&str>,
+    act: Option<&str>,
+    duration_sec: usize,
+    secret_key: &[u8],
+) -> Result<String> {
     let current_time = get_current_timestamp() as usize;
-    let exp = current_time + jwt_duration_sec;
+    let exp = current_time + duration_sec;
     let claim = Claims {
         sub: sub.to_string(),
         exp,
+        jti: jti.map(str::to_string),
+        act: act.map(|sub| Actor {
+            sub: sub.to_string(),
+        }),
     };
     let mut headers = Header::default();
     headers.kid = Some(sub.to_string());
@@ -34,15 +87,24 @@ pub fn decode_kid_from_jwt_headers(jwt: &str) -> Result<String> {
 }
 
 pub fn decode_sub_from_jwt(ori_jwt: &str, secret_key: &[u8]) -> Result<String> {
-    let sub = decode::<Claims>(
+    let sub = decode_session_from_jwt(ori_jwt, secret_key)?.sub;
+
+    Ok(sub)
+}
+
+pub fn decode_session_from_jwt(ori_jwt: &str, secret_key: &[u8]) -> Result<JwtSession> {
+    let claims = decode::<Claims>(
         ori_jwt,
         &DecodingKey::from_secret(secret_key),
         &Validation::default(),
     )?
-    .claims
-    .sub;
+    .claims;
 
-    Ok(sub)
+    Ok(JwtSession {
+        sub: claims.sub,
+        session_id: claims.jti,
+        impersonator_id: claims.act.map(|act| act.sub),
+    })
 }
 
 // region:    --- Tests
@@ -93,6 +155,47 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_validate_and_get_session_jwt() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_sub = "iR1f8i7Wg7jipR3uhDhJ";
+        let fx_session_id = "u8dq3k0zm1w5n7xj2c4r";
+        let token_salt = "36ee060e-20a7-4a42-8bd1-0cbd704a29a2";
+
+        // -- Exec
+        let session_jwt = encode_session_jwt(fx_sub, fx_session_id, token_salt.as_ref())?;
+        let jwt = encode_jwt(fx_sub, token_salt.as_ref())?;
+
+        // -- Check
+        let session = decode_session_from_jwt(&session_jwt, token_salt.as_ref())?;
+        assert_eq!(session.sub, fx_sub);
+        assert_eq!(session.session_id.as_deref(), Some(fx_session_id));
+
+        let session = decode_session_from_jwt(&jwt, token_salt.as_ref())?;
+        assert_eq!(session.session_id, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_and_get_impersonation_jwt() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_sub = "iR1f8i7Wg7jipR3uhDhJ";
+        let fx_impersonator_id = "qhoj2wjjc7w6lhou8vu4";
+        let token_salt = "36ee060e-20a7-4a42-8bd1-0cbd704a29a2";
+
+        // -- Exec
+        let jwt = encode_impersonation_jwt(fx_sub, fx_impersonator_id, token_salt.as_ref())?;
+
+        // -- Check
+        let session = decode_session_from_jwt(&jwt, token_salt.as_ref())?;
+        assert_eq!(session.sub, fx_sub);
+        assert_eq!(session.session_id, None);
+        assert_eq!(session.impersonator_id.as_deref(), Some(fx_impersonator_id));
+
+        Ok(())
+    }
+
     #[test]
     fn test_expired_get_sub_jwt_error() -> Result<()> {
         // -- Setup & Fixtures
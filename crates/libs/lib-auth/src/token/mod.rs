@@ -56,7 +56,12 @@ impl Display for Token {
 
 pub fn generate_web_token(user: &str, salt: Uuid) -> Result<Token> {
     let config = &auth_config();
-    inner_generate_token(user, config.TOKEN_DURATION_SEC, salt, &config.TOKEN_KEY)
+    inner_generate_token(
+        user,
+        config.TOKEN_DURATION.as_secs_f64(),
+        salt,
+        &config.TOKEN_KEY,
+    )
 }
 
 pub fn validate_web_token(origin_token: &Token, salt: Uuid) -> Result<()> {
@@ -66,6 +71,67 @@ pub fn validate_web_token(origin_token: &Token, salt: Uuid) -> Result<()> {
     Ok(())
 }
 
+/// Generate a short-lived token for the password reset flow.
+///
+/// The `salt` must be unique to the reset request, so the token is single-use
+/// once the reset request is consumed.
+pub fn generate_pwd_reset_token(ident: &str, salt: Uuid) -> Result<Token> {
+    let config = &auth_config();
+    inner_generate_token(
+        ident,
+        config.PWD_RESET_DURATION.as_secs_f64(),
+        salt,
+        &config.TOKEN_KEY,
+    )
+}
+
+pub fn validate_pwd_reset_token(origin_token: &Token, salt: Uuid) -> Result<()> {
+    let config = &auth_config();
+    inner_validate_token_sign_and_exp(origin_token, salt, &config.TOKEN_KEY)?;
+
+    Ok(())
+}
+
+/// Generate a token for the email verification flow.
+///
+/// Like the password reset token, the `salt` must be unique to the verification request.
+pub fn generate_email_verification_token(ident: &str, salt: Uuid) -> Result<Token> {
+    let config = &auth_config();
+    inner_generate_token(
+        ident,
+        config.EMAIL_VERIFICATION_DURATION.as_secs_f64(),
+        salt,
+        &config.TOKEN_KEY,
+    )
+}
+
+pub fn validate_email_verification_token(origin_token: &Token, salt: Uuid) -> Result<()> {
+    let config = &auth_config();
+    inner_validate_token_sign_and_exp(origin_token, salt, &config.TOKEN_KEY)?;
+
+    Ok(())
+}
+
+/// Generate the short-lived token returned by the login when a second factor is needed.
+///
+/// It only proves the password step, the `salt` is the user token salt.
+pub fn generate_mfa_token(ident: &str, salt: Uuid) -> Result<Token> {
+    let config = &auth_config();
+    inner_generate_token(
+        ident,
+        config.MFA_TOKEN_DURATION.as_secs_f64(),
+        salt,
+        &config.TOKEN_KEY,
+    )
+}
+
+pub fn validate_mfa_token(origin_token: &Token, salt: Uuid) -> Result<()> {
+    let config = &auth_config();
+    inner_validate_token_sign_and_exp(origin_token, salt, &config.TOKEN_KEY)?;
+
+    Ok(())
+}
+
 fn inner_generate_token(ident: &str, duration_sec: f64, salt: Uuid, key: &[u8]) -> Result<Token> {
     // -- Compute the two first components.
     let ident = ident.to_string();
@@ -164,6 +230,26 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_validate_pwd_reset_token_err_wrong_salt() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_ident = "pwd_reset_one";
+        let fx_salt = Uuid::parse_str("f05e8961-d6ad-4086-9e78-a6de065e5453").unwrap();
+        let fx_other_salt = Uuid::parse_str("f05e8961-d6ad-4086-9e78-a6de065e5451").unwrap();
+        let fx_token = generate_pwd_reset_token(fx_ident, fx_salt)?;
+
+        // -- Exec
+        let res = validate_pwd_reset_token(&fx_token, fx_other_salt);
+
+        // -- Check
+        assert!(
+            matches!(res, Err(Error::SignatureNotMatching)),
+            "Should have matched `Err(Error::SignatureNotMatching)` but was `{res:?}`"
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn test_validate_web_token_err_expired() -> Result<()> {
         // -- Setup & Fixtures
@@ -0,0 +1,235 @@
+//! Time-based one-time passwords (RFC 6238) and their recovery codes.
+//!
+//! Parameters are the ones every authenticator app supports:
+//! HMAC-SHA1, 6 digits, 30 seconds steps.
+
+mod error;
+
+pub use self::error::{Error, Result};
+
+use hmac::{Hmac, Mac};
+use lib_utils::{
+    b32::{b32_decode, b32_encode},
+    b64::b64u_encode,
+    time::now_utc,
+};
+use rand::RngCore;
+use sha1::Sha1;
+use sha2::Sha512;
+
+use crate::config::auth_config;
+
+const SECRET_LEN: usize = 20;
+const DIGITS: u32 = 6;
+const STEP_SEC: u64 = 30;
+/// Number of steps accepted before and after the current one, for clock drift.
+const SKEW_STEPS: u64 = 1;
+const RECOVERY_CODE_LEN: usize = 5;
+
+/// Generate a new random secret, base32 encoded (as shown to the user).
+pub fn generate_secret() -> String {
+    let mut secret = [0u8; SECRET_LEN];
+    rand::thread_rng().fill_bytes(&mut secret);
+
+    b32_encode(secret)
+}
+
+/// Build the `otpauth://` URI to be rendered as a QR code by the client.
+pub fn otpauth_uri(secret_b32: &str, account: &str) -> String {
+    let issuer = url_encode(&auth_config().TOTP_ISSUER);
+    let account = url_encode(account);
+
+    format!(
+        "otpauth://totp/{issuer}:{account}?secret={secret_b32}&issuer={issuer}\
+		&algorithm=SHA1&digits={DIGITS}&period={STEP_SEC}"
+    )
+}
+
+/// Validate a code against the secret, for the current time.
+///
+/// Returns the matched time step, which the caller must persist and pass as
+/// `last_used_step` on the next validation, so a code cannot be replayed.
+pub fn validate_totp(secret_b32: &str, code: &str, last_used_step: Option<u64>) -> Result<u64> {
+    let now = now_utc().unix_timestamp().max(0) as u64;
+    inner_validate_totp(secret_b32, code, now, last_used_step)
+}
+
+/// Generate `count` one-time recovery codes, in the `xxxx-xxxx` format.
+pub fn generate_recovery_codes(count: usize) -> Vec<String> {
+    let mut rng = rand::thread_rng();
+
+    (0..count)
+        .map(|_| {
+            let mut bytes = [0u8; RECOVERY_CODE_LEN];
+            rng.fill_bytes(&mut bytes);
+            let code = b32_encode(bytes).to_lowercase();
+            format!("{}-{}", &code[..4], &code[4..])
+        })
+        .collect()
+}
+
+/// Hash a recovery code for storage and lookup.
+///
+/// Recovery codes are random and single-use, so a keyed hash is enough
+/// (no per-code salt), which allows to find the code by equality.
+pub fn hash_recovery_code(code: &str) -> Result<String> {
+    let code: String = code
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+
+    let mut hmac_sha512 = Hmac::<Sha512>::new_from_slice(&auth_config().PWD_KEY)
+        .map_err(|_| Error::HmacFailNewFromSlice)?;
+    hmac_sha512.update(code.as_bytes());
+
+    Ok(b64u_encode(hmac_sha512.finalize().into_bytes()))
+}
+
+fn inner_validate_totp(
+    secret_b32: &str,
+    code: &str,
+    unix_time: u64,
+    last_used_step: Option<u64>,
+) -> Result<u64> {
+    if code.len() != DIGITS as usize || !code.chars().all(|c| c.is_ascii_digit()) {
+        return Err(Error::CodeInvalidFormat);
+    }
+    let secret = b32_decode(secret_b32).map_err(|_| Error::SecretInvalid)?;
+
+    let current_step = unix_time / STEP_SEC;
+    let first_step = current_step.saturating_sub(SKEW_STEPS);
+    for step in first_step..=current_step + SKEW_STEPS {
+        if inner_hotp(&secret, step)? == code {
+            if last_used_step.is_some_and(|last| step <= last) {
+                return Err(Error::CodeAlreadyUsed);
+            }
+            return Ok(step);
+        }
+    }
+
+    Err(Error::CodeNotMatching)
+}
+
+/// HOTP (RFC 4226) for a given counter, zero padded to `DIGITS`.
+fn inner_hotp(secret: &[u8], counter: u64) -> Result<String> {
+    let mut hmac_sha1 =
+        Hmac::<Sha1>::new_from_slice(secret).map_err(|_| Error::HmacFailNewFromSlice)?;
+    hmac_sha1.update(&counter.to_be_bytes());
+    let hash = hmac_sha1.finalize().into_bytes();
+
+    // -- Dynamic truncation.
+    let offset = usize::from(hash[hash.len() - 1] & 0x0f);
+    let bin_code = u32::from_be_bytes([
+        hash[offset] & 0x7f,
+        hash[offset + 1],
+        hash[offset + 2],
+        hash[offset + 3],
+    ]);
+    let code = bin_code % 10u32.pow(DIGITS);
+
+    Ok(format!("{code:0width$}", width = DIGITS as usize))
+}
+
+fn url_encode(content: &str) -> String {
+    content
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+
+    /// RFC 6238 SHA1 seed, "12345678901234567890".
+    const FX_SECRET_B32: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+    #[test]
+    fn test_hotp_rfc6238_vectors() -> Result<()> {
+        // -- Setup & Fixtures
+        let secret = b32_decode(FX_SECRET_B32)?;
+        // (unix_time, 8 digits code from the RFC), we keep the last 6 digits.
+        let fx_vectors = [
+            (59, "94287082"),
+            (1111111109, "07081804"),
+            (1111111111, "14050471"),
+            (1234567890, "89005924"),
+            (2000000000, "69279037"),
+        ];
+
+        for (unix_time, fx_code) in fx_vectors {
+            // -- Exec
+            let code = inner_hotp(&secret, unix_time / STEP_SEC)?;
+
+            // -- Check
+            assert_eq!(code, fx_code[2..]);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_totp_skew_and_replay() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_time = 1111111109;
+        let fx_code = "081804";
+
+        // -- Exec & Check
+        let step = inner_validate_totp(FX_SECRET_B32, fx_code, fx_time, None)?;
+        assert_eq!(step, fx_time / STEP_SEC);
+        // Still accepted one step later (clock drift).
+        let res = inner_validate_totp(FX_SECRET_B32, fx_code, fx_time + STEP_SEC, None);
+        assert!(res.is_ok());
+        // Rejected two steps later.
+        let res = inner_validate_totp(FX_SECRET_B32, fx_code, fx_time + 2 * STEP_SEC, None);
+        assert_eq!(res, Err(Error::CodeNotMatching));
+        // Rejected once used.
+        let res = inner_validate_totp(FX_SECRET_B32, fx_code, fx_time, Some(step));
+        assert_eq!(res, Err(Error::CodeAlreadyUsed));
+        // Rejected when not 6 digits.
+        let res = inner_validate_totp(FX_SECRET_B32, "08180", fx_time, None);
+        assert_eq!(res, Err(Error::CodeInvalidFormat));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_recovery_codes_hash() -> Result<()> {
+        // -- Setup & Fixtures
+        let codes = generate_recovery_codes(10);
+
+        // -- Exec & Check
+        assert_eq!(codes.len(), 10);
+        assert_eq!(codes[0].len(), 9);
+        // Hash ignores the case and the separator, as users retype the codes.
+        assert_eq!(
+            hash_recovery_code(&codes[0])?,
+            hash_recovery_code(&codes[0].to_uppercase().replace('-', ""))?
+        );
+        assert_ne!(
+            hash_recovery_code(&codes[0])?,
+            hash_recovery_code(&codes[1])?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_otpauth_uri() {
+        // -- Exec
+        let uri = otpauth_uri(FX_SECRET_B32, "demo1@example.com");
+
+        // -- Check
+        assert!(uri.starts_with("otpauth://totp/"));
+        assert!(uri.contains(":demo1%40example.com?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&"));
+    }
+}
+// endregion: --- Tests
@@ -1,16 +1,30 @@
-use lib_utils::envs::get_env;
+use lib_utils::config::{ConfigReader, ConfigSources, Error, Result, Secret};
 use std::sync::OnceLock;
 
-pub fn core_config() -> &'static CoreConfig {
-    static INSTANCE: OnceLock<CoreConfig> = OnceLock::new();
+static INSTANCE: OnceLock<CoreConfig> = OnceLock::new();
+
+/// Set the config loaded at startup, before any call to `core_config`.
+pub fn init_core_config(config: CoreConfig) -> Result<()> {
+    INSTANCE
+        .set(config)
+        .map_err(|_| Error::AlreadyInitialized("core"))
+}
 
+/// The config set by `init_core_config`, or else loaded from the env (e.g., in the tests).
+pub fn core_config() -> &'static CoreConfig {
     INSTANCE.get_or_init(|| {
-        CoreConfig::load_from_env()
-            .unwrap_or_else(|ex| panic!("FATEL - WHILE LOADING CONF - Cause: {ex:?}"))
+        let sources = ConfigSources::new().defaults(CoreConfig::DEFAULTS).env();
+        let mut reader = sources.reader();
+        let config = CoreConfig::load(&mut reader);
+        reader
+            .finish()
+            .unwrap_or_else(|ex| panic!("FATAL - WHILE LOADING CONF - Cause: {ex}"));
+        config
     })
 }
 
 #[allow(non_snake_case)]
+#[derive(Debug)]
 pub struct CoreConfig {
     // -- Db
     // pub DB_HOST: String,
@@ -19,24 +33,27 @@ pub struct CoreConfig {
     pub DB_NAMESPACE: String,
     pub DB_DBNAME: String,
     pub DB_USERNAME: String,
-    pub DB_PASSWORD: String,
+    pub DB_PASSWORD: Secret<String>,
     // pub AUTHGODTOKEN: String,
 }
 
 impl CoreConfig {
-    fn load_from_env() -> lib_utils::envs::Result<CoreConfig> {
-        let db_host = get_env("SURREALDB_HOST")?;
-        let db_port = get_env("SURREALDB_PORT")?;
+    pub const DEFAULTS: &'static [(&'static str, &'static str)] = &[("SURREALDB_PORT", "8000")];
+
+    /// Read the config, the errors being collected by `reader`.
+    pub fn load(reader: &mut ConfigReader) -> CoreConfig {
+        let db_host = reader.get("SURREALDB_HOST");
+        let db_port: u16 = reader.parse("SURREALDB_PORT");
         let db_url = format!("{}:{}", db_host, db_port);
-        Ok(CoreConfig {
+        CoreConfig {
             // -- Db
             DB_URL: db_url,
-            DB_NAMESPACE: get_env("SURREALDB_NAMESPACE")?,
-            DB_DBNAME: get_env("SURREALDB_DBNAME")?,
-            DB_USERNAME: get_env("SURREALDB_USERNAME")?,
-            DB_PASSWORD: get_env("SURREALDB_PASSWORD")?,
+            DB_NAMESPACE: reader.get("SURREALDB_NAMESPACE"),
+            DB_DBNAME: reader.get("SURREALDB_DBNAME"),
+            DB_USERNAME: reader.get("SURREALDB_USERNAME"),
+            DB_PASSWORD: Secret::new(reader.get("SURREALDB_PASSWORD")),
             // // -- AuthGodToken
-            // AUTHGODTOKEN: get_env("AUTHGODTOKEN")?,
-        })
+            // AUTHGODTOKEN: reader.get("AUTHGODTOKEN"),
+        }
     }
 }
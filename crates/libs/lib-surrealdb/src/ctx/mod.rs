@@ -1,28 +1,89 @@
 mod error;
 
+use std::net::IpAddr;
+
 use surrealdb::sql::Thing;
+use uuid::Uuid;
+
+use crate::model::role_policies::{Permission, Role};
 
 use self::error::{Error, Result};
 
 #[derive(Debug, Clone)]
 pub struct Ctx {
     user_id: Option<String>,
+    roles: Vec<Role>,
+
+    /// The login session, see `CtxBuilder::session_id`.
+    session_id: Option<String>,
+
+    // -- api key info
+    api_key_id: Option<String>,
+    scopes: Option<Vec<Permission>>,
+
+    // -- request info
+    req_id: Option<Uuid>,
+    client_ip: Option<IpAddr>,
+
+    impersonator_id: Option<String>,
+    tenant_id: Option<String>,
 }
 
 impl Ctx {
     pub fn root_ctx() -> Self {
-        let root_id = None;
-        Ctx { user_id: root_id }
+        Ctx {
+            user_id: None,
+            roles: Vec::new(),
+            session_id: None,
+            api_key_id: None,
+            scopes: None,
+            req_id: None,
+            client_ip: None,
+            impersonator_id: None,
+            tenant_id: None,
+        }
     }
 
-    pub fn new(user_id: Option<String>) -> Result<Self> {
-        if user_id.is_none() {
-            Err(Error::CtxCannotNewRootCtx)
-        } else {
-            Ok(Self { user_id })
+    /// The root ctx of the request `req_id`, e.g., for the public routes.
+    pub fn root_ctx_for_req(req_id: Uuid) -> Self {
+        Ctx {
+            req_id: Some(req_id),
+            ..Self::root_ctx()
         }
     }
 
+    /// Build the ctx of a user, e.g.,
+    /// `Ctx::new(CtxBuilder::new(user_id).roles(roles).req_id(req_id))`.
+    pub fn new(builder: CtxBuilder) -> Result<Self> {
+        let CtxBuilder {
+            user_id,
+            roles,
+            session_id,
+            api_key_id,
+            scopes,
+            req_id,
+            client_ip,
+            impersonator_id,
+            tenant_id,
+        } = builder;
+
+        if user_id.is_empty() {
+            return Err(Error::CtxCannotNewRootCtx);
+        }
+
+        Ok(Self {
+            user_id: Some(user_id),
+            roles,
+            session_id,
+            api_key_id,
+            scopes,
+            req_id,
+            client_ip,
+            impersonator_id,
+            tenant_id,
+        })
+    }
+
     pub fn user_id(&self) -> Option<&str> {
         self.user_id.as_deref()
     }
@@ -31,4 +92,113 @@ impl Ctx {
         self.user_id()
             .and_then(|id_str| Some(Thing::from(("users", id_str))))
     }
+
+    pub fn roles(&self) -> &[Role] {
+        &self.roles
+    }
+
+    pub fn has_role(&self, role: Role) -> bool {
+        self.roles.contains(&role)
+    }
+
+    /// The login session the jwt was issued for.
+    pub fn session_id(&self) -> Option<&str> {
+        self.session_id.as_deref()
+    }
+
+    /// The api key used to authenticate, if not a login session.
+    pub fn api_key_id(&self) -> Option<&str> {
+        self.api_key_id.as_deref()
+    }
+
+    /// The permissions an api key is restricted to, on top of the roles.
+    /// `None` when not authenticated by an api key.
+    pub fn scopes(&self) -> Option<&[Permission]> {
+        self.scopes.as_deref()
+    }
+
+    pub fn req_id(&self) -> Option<Uuid> {
+        self.req_id
+    }
+
+    pub fn client_ip(&self) -> Option<IpAddr> {
+        self.client_ip
+    }
+
+    /// The user acting on behalf of `user_id`, if any.
+    pub fn impersonator_id(&self) -> Option<&str> {
+        self.impersonator_id.as_deref()
+    }
+
+    pub fn tenant_id(&self) -> Option<&str> {
+        self.tenant_id.as_deref()
+    }
+}
+
+// region:    --- Ctx Builder
+#[derive(Debug, Clone)]
+pub struct CtxBuilder {
+    user_id: String,
+    roles: Vec<Role>,
+    session_id: Option<String>,
+    api_key_id: Option<String>,
+    scopes: Option<Vec<Permission>>,
+    req_id: Option<Uuid>,
+    client_ip: Option<IpAddr>,
+    impersonator_id: Option<String>,
+    tenant_id: Option<String>,
+}
+
+impl CtxBuilder {
+    pub fn new(user_id: impl Into<String>) -> Self {
+        Self {
+            user_id: user_id.into(),
+            roles: Vec::new(),
+            session_id: None,
+            api_key_id: None,
+            scopes: None,
+            req_id: None,
+            client_ip: None,
+            impersonator_id: None,
+            tenant_id: None,
+        }
+    }
+
+    pub fn roles(mut self, roles: Vec<Role>) -> Self {
+        self.roles = roles;
+        self
+    }
+
+    pub fn session_id(mut self, session_id: impl Into<String>) -> Self {
+        self.session_id = Some(session_id.into());
+        self
+    }
+
+    /// Authenticated by the api key `api_key_id`, restricted to `scopes`.
+    pub fn api_key(mut self, api_key_id: impl Into<String>, scopes: Vec<Permission>) -> Self {
+        self.api_key_id = Some(api_key_id.into());
+        self.scopes = Some(scopes);
+        self
+    }
+
+    pub fn req_id(mut self, req_id: Uuid) -> Self {
+        self.req_id = Some(req_id);
+        self
+    }
+
+    pub fn client_ip(mut self, client_ip: IpAddr) -> Self {
+        self.client_ip = Some(client_ip);
+        self
+    }
+
+    pub fn impersonator_id(mut self, impersonator_id: impl Into<String>) -> Self {
+        self.impersonator_id = Some(impersonator_id.into());
+        self
+    }
+
+    pub fn tenant_id(mut self, tenant_id: impl Into<String>) -> Self {
+        self.tenant_id = Some(tenant_id.into());
+        self
+    }
 }
+// endregion: --- Ctx Builder
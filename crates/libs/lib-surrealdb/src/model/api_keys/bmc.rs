@@ -0,0 +1,218 @@
+use serde::de::DeserializeOwned;
+use surrealdb::sql::Datetime;
+
+use crate::{
+    ctx::Ctx,
+    model::{
+        store::{BindReqId, Traced},
+        Error, ModelManager, Result,
+    },
+};
+
+use super::{ApiKeys, ApiKeysForCreate, ApiKeysGet, ApiKeysRecord};
+
+pub struct ApiKeysBmc;
+
+impl ApiKeysBmc {
+    pub async fn get<'de, E>(ctx: &Ctx, mm: &ModelManager, id: &str) -> Result<Option<E>>
+    where
+        E: DeserializeOwned,
+    {
+        let db = mm.db();
+        let sql = "SELECT * FROM ONLY type::thing('api_keys', $id);";
+        let mut result = db
+            .query(sql)
+            .bind_req_id(ctx)
+            .bind(("id", id))
+            .traced("ApiKeysBmc::get", sql)
+            .await?;
+        let api_key = result.take(0)?;
+
+        Ok(api_key)
+    }
+
+    /// Get the key only if it belongs to the user.
+    pub async fn get_by_user<'de, E>(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        user_id: &str,
+        id: &str,
+    ) -> Result<Option<E>>
+    where
+        E: DeserializeOwned,
+    {
+        let db = mm.db();
+        let sql = "SELECT * FROM type::thing('api_keys', $id) WHERE user = type::thing('users', $user_id);";
+        let mut result = db
+            .query(sql)
+            .bind_req_id(ctx)
+            .bind(("id", id))
+            .bind(("user_id", user_id))
+            .traced("ApiKeysBmc::get_by_user", sql)
+            .await?;
+        let api_key = result.take(0)?;
+
+        Ok(api_key)
+    }
+
+    /// Get the key if it is neither revoked nor expired.
+    pub async fn get_active(ctx: &Ctx, mm: &ModelManager, id: &str) -> Result<Option<ApiKeys>> {
+        let api_key = ApiKeysBmc::get::<ApiKeys>(ctx, mm, id).await?;
+
+        let now = Datetime::default();
+        let api_key = api_key
+            .filter(|api_key| api_key.revoked_on.is_none())
+            .filter(|api_key| {
+                api_key
+                    .expire_on
+                    .as_ref()
+                    .map_or(true, |expire_on| *expire_on > now)
+            });
+
+        Ok(api_key)
+    }
+
+    /// Create a key for the ctx user.
+    pub async fn create(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        api_keys_for_create: ApiKeysForCreate,
+    ) -> Result<ApiKeysGet> {
+        let db = mm.db();
+        let user_id = ctx.user_id().ok_or(Error::CannotGetUserIdFromCtx)?;
+
+        let ApiKeysForCreate {
+            name,
+            secret_hash,
+            scopes,
+            expire_days,
+        } = api_keys_for_create;
+
+        let sql = "CREATE ONLY api_keys CONTENT {
+            user: type::thing('users', $user_id),
+            name: $name,
+            secret_hash: $secret_hash,
+            scopes: $scopes,
+            expire_on: IF $expire_days != NONE THEN time::now() + duration::from::days($expire_days) ELSE NONE END
+        };";
+        let mut result = db
+            .query(sql)
+            .bind_req_id(ctx)
+            .bind(("user_id", user_id))
+            .bind(("name", name))
+            .bind(("secret_hash", secret_hash))
+            .bind(("scopes", scopes))
+            .bind(("expire_days", expire_days))
+            .traced("ApiKeysBmc::create", sql)
+            .await?;
+
+        result
+            .take::<Option<ApiKeysGet>>(0)?
+            .ok_or(Error::DataNotFoundForCreated)
+    }
+
+    pub async fn list_by_user(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        user_id: &str,
+    ) -> Result<Vec<ApiKeysGet>> {
+        let db = mm.db();
+        let sql = "SELECT * FROM api_keys WHERE user = type::thing('users', $user_id) ORDER BY create_on DESC;";
+        let mut result = db
+            .query(sql)
+            .bind_req_id(ctx)
+            .bind(("user_id", user_id))
+            .traced("ApiKeysBmc::list_by_user", sql)
+            .await?;
+        let api_keys: Vec<ApiKeysGet> = result.take(0)?;
+
+        Ok(api_keys)
+    }
+
+    /// Track the last time the key was used to authenticate.
+    pub async fn touch(ctx: &Ctx, mm: &ModelManager, id: &str) -> Result<()> {
+        let db = mm.db();
+        let sql = "UPDATE type::thing('api_keys', $id) SET last_used_on = time::now();";
+        let mut result = db
+            .query(sql)
+            .bind_req_id(ctx)
+            .bind(("id", id))
+            .traced("ApiKeysBmc::touch", sql)
+            .await?;
+
+        let _api_keys_record = result
+            .take::<Option<ApiKeysRecord>>(0)?
+            .ok_or(Error::DataNotFoundForUpdate)?;
+
+        Ok(())
+    }
+
+    /// Revoke the key, it cannot be used anymore.
+    pub async fn revoke(ctx: &Ctx, mm: &ModelManager, id: &str) -> Result<()> {
+        let db = mm.db();
+        let sql = "UPDATE type::thing('api_keys', $id) SET revoked_on = time::now() WHERE revoked_on IS NONE;";
+        let mut result = db
+            .query(sql)
+            .bind_req_id(ctx)
+            .bind(("id", id))
+            .traced("ApiKeysBmc::revoke", sql)
+            .await?;
+
+        let _api_keys_record = result
+            .take::<Option<ApiKeysRecord>>(0)?
+            .ok_or(Error::DataNotFoundForUpdate)?;
+
+        Ok(())
+    }
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    pub type Result<T> = core::result::Result<T, Error>;
+    pub type Error = Box<dyn std::error::Error>; // For tests.
+    use crate::{ctx::CtxBuilder, model};
+
+    use super::*;
+    use serial_test::serial;
+
+    #[serial]
+    #[tokio::test]
+    async fn test_get_by_user_err_other_user() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = model::ModelManager::new().await?;
+        let fx_user_id = "iR1f8i7Wg7jipR3uhDhJ";
+        let fx_ctx = Ctx::new(CtxBuilder::new(fx_user_id))?;
+        let fx_api_key = ApiKeysBmc::create(
+            &fx_ctx,
+            &mm,
+            ApiKeysForCreate {
+                name: "fx-api-key".to_string(),
+                secret_hash: "fx-secret-hash".to_string(),
+                scopes: vec![],
+                expire_days: None,
+            },
+        )
+        .await?;
+        let fx_key_id = fx_api_key.id.id.to_raw();
+
+        // -- Exec
+        let own = ApiKeysBmc::get_by_user::<ApiKeys>(&fx_ctx, &mm, fx_user_id, &fx_key_id).await?;
+        let other =
+            ApiKeysBmc::get_by_user::<ApiKeys>(&fx_ctx, &mm, "fx-other-user-id", &fx_key_id)
+                .await?;
+        let unknown =
+            ApiKeysBmc::get_by_user::<ApiKeys>(&fx_ctx, &mm, fx_user_id, "fx-unknown-id").await?;
+
+        // -- Check
+        assert_eq!(
+            own.map(|api_key| api_key.name).as_deref(),
+            Some("fx-api-key")
+        );
+        assert!(other.is_none());
+        assert!(unknown.is_none());
+
+        Ok(())
+    }
+}
+// endregion: --- Tests
@@ -0,0 +1,48 @@
+pub mod bmc;
+
+use serde::{Deserialize, Serialize};
+use surrealdb::sql;
+
+use super::role_policies::Permission;
+
+#[derive(Debug, Deserialize)]
+pub struct ApiKeys {
+    pub id: sql::Thing,
+    pub user: sql::Thing, // Users ID Table
+    pub name: String,
+
+    // -- key info
+    pub secret_hash: String,
+    pub scopes: Vec<Permission>,
+
+    pub expire_on: Option<sql::Datetime>,
+    pub last_used_on: Option<sql::Datetime>,
+    pub revoked_on: Option<sql::Datetime>,
+    pub create_on: sql::Datetime,
+}
+
+/// Api keys as shown to the user, without the secret hash.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ApiKeysGet {
+    pub id: sql::Thing,
+    pub name: String,
+    pub scopes: Vec<Permission>,
+    pub expire_on: Option<sql::Datetime>,
+    pub last_used_on: Option<sql::Datetime>,
+    pub revoked_on: Option<sql::Datetime>,
+    pub create_on: sql::Datetime,
+}
+
+#[derive(Debug)]
+pub struct ApiKeysForCreate {
+    pub name: String,
+    pub secret_hash: String,
+    pub scopes: Vec<Permission>,
+    /// `None` for a key that does not expire.
+    pub expire_days: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ApiKeysRecord {
+    pub id: sql::Thing,
+}
@@ -0,0 +1,77 @@
+use crate::{
+    ctx::Ctx,
+    model::{
+        store::{BindReqId, Traced},
+        Error, ModelManager, Result,
+    },
+};
+
+use super::{AuditLogs, AuditLogsForCreate, AuditLogsRecord};
+
+pub struct AuditLogsBmc;
+
+impl AuditLogsBmc {
+    /// Record an action of the ctx, the actor being its impersonator if any.
+    pub async fn create(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        audit_logs_for_create: AuditLogsForCreate,
+    ) -> Result<AuditLogsRecord> {
+        let db = mm.db();
+        let actor_id = ctx
+            .impersonator_id()
+            .or(ctx.user_id())
+            .ok_or(Error::CannotGetUserIdFromCtx)?;
+
+        let AuditLogsForCreate {
+            action,
+            user_id,
+            detail,
+        } = audit_logs_for_create;
+
+        let sql = "CREATE ONLY audit_logs CONTENT {
+            actor: type::thing('users', $actor_id),
+            user: IF $user_id != NONE THEN type::thing('users', $user_id) ELSE NONE END,
+            action: $action,
+            detail: $detail,
+            req_id: $req_id,
+            client_ip: $client_ip
+        } RETURN id;";
+        let mut result = db
+            .query(sql)
+            .bind_req_id(ctx)
+            .bind(("actor_id", actor_id))
+            .bind(("user_id", user_id))
+            .bind(("action", action))
+            .bind(("detail", detail))
+            .bind((
+                "client_ip",
+                ctx.client_ip().map(|client_ip| client_ip.to_string()),
+            ))
+            .traced("AuditLogsBmc::create", sql)
+            .await?;
+
+        result
+            .take::<Option<AuditLogsRecord>>(0)?
+            .ok_or(Error::DataNotFoundForCreated)
+    }
+
+    /// List the actions done by or on the user, the last first.
+    pub async fn list_by_user(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        user_id: &str,
+    ) -> Result<Vec<AuditLogs>> {
+        let db = mm.db();
+        let sql = "SELECT * FROM audit_logs WHERE actor = type::thing('users', $user_id) OR user = type::thing('users', $user_id) ORDER BY create_on DESC;";
+        let mut result = db
+            .query(sql)
+            .bind_req_id(ctx)
+            .bind(("user_id", user_id))
+            .traced("AuditLogsBmc::list_by_user", sql)
+            .await?;
+        let audit_logs: Vec<AuditLogs> = result.take(0)?;
+
+        Ok(audit_logs)
+    }
+}
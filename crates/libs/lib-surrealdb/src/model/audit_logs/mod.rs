@@ -0,0 +1,41 @@
+pub mod bmc;
+
+use serde::{Deserialize, Serialize};
+use surrealdb::sql;
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct AuditLogs {
+    pub id: sql::Thing,
+    pub actor: sql::Thing,        // Users ID Table
+    pub user: Option<sql::Thing>, // Users ID Table
+    pub action: AuditAction,
+    pub detail: Option<String>,
+
+    // -- request info
+    pub req_id: Option<String>,
+    pub client_ip: Option<String>,
+
+    pub create_on: sql::Datetime,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    /// An impersonation token was issued.
+    ImpersonationStart,
+    /// A request, other than a read, was done with an impersonation token.
+    ImpersonatedRequest,
+}
+
+#[derive(Debug)]
+pub struct AuditLogsForCreate {
+    pub action: AuditAction,
+    /// The user acted on, or acted as.
+    pub user_id: Option<String>,
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct AuditLogsRecord {
+    pub id: sql::Thing,
+}
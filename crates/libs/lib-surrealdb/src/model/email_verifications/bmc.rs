@@ -0,0 +1,75 @@
+use serde::de::DeserializeOwned;
+use surrealdb::sql::Thing;
+
+use crate::{
+    ctx::Ctx,
+    model::{
+        store::{BindReqId, Traced},
+        Error, ModelManager, Result,
+    },
+};
+
+use super::{EmailVerifications, EmailVerificationsCreated, EmailVerificationsRecord};
+
+pub struct EmailVerificationsBmc;
+
+impl EmailVerificationsBmc {
+    pub async fn get<'de, E>(ctx: &Ctx, mm: &ModelManager, id: &str) -> Result<Option<E>>
+    where
+        E: DeserializeOwned,
+    {
+        let db = mm.db();
+        let sql = "SELECT * FROM ONLY type::thing('email_verifications', $id);";
+        let mut result = db
+            .query(sql)
+            .bind_req_id(ctx)
+            .bind(("id", id))
+            .traced("EmailVerificationsBmc::get", sql)
+            .await?;
+        let email_verification = result.take(0)?;
+
+        Ok(email_verification)
+    }
+
+    pub async fn create(
+        _ctx: &Ctx,
+        mm: &ModelManager,
+        user_id: &str,
+        email: &str,
+    ) -> Result<EmailVerifications> {
+        let db = mm.db();
+
+        let email_verifications_created = EmailVerificationsCreated {
+            user: Thing::from(("users", user_id)),
+            email,
+        };
+
+        let mut created: Vec<EmailVerifications> = db
+            .create("email_verifications")
+            .content(email_verifications_created)
+            .await?;
+
+        let email_verification = created.pop().ok_or(Error::DataNotFoundForCreated)?;
+
+        Ok(email_verification)
+    }
+
+    /// Mark the verification request as used.
+    /// Fails with `EmailVerificationAlreadyUsed` if it was consumed before.
+    pub async fn consume(ctx: &Ctx, mm: &ModelManager, id: &str) -> Result<()> {
+        let db = mm.db();
+        let sql = "UPDATE type::thing('email_verifications', $id) SET used_on = time::now() WHERE used_on IS NONE;";
+        let mut result = db
+            .query(sql)
+            .bind_req_id(ctx)
+            .bind(("id", id))
+            .traced("EmailVerificationsBmc::consume", sql)
+            .await?;
+
+        let _email_verifications_record = result
+            .take::<Option<EmailVerificationsRecord>>(0)?
+            .ok_or(Error::EmailVerificationAlreadyUsed)?;
+
+        Ok(())
+    }
+}
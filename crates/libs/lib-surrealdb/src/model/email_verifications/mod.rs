@@ -0,0 +1,28 @@
+pub mod bmc;
+
+use serde::{Deserialize, Serialize};
+use surrealdb::sql;
+
+#[derive(Debug, Deserialize)]
+pub struct EmailVerifications {
+    pub id: sql::Thing,
+    pub user: sql::Thing, // Users ID Table
+    pub email: String,
+
+    // -- token info
+    pub token_salt: sql::Uuid,
+
+    pub used_on: Option<sql::Datetime>,
+    pub create_on: sql::Datetime,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EmailVerificationsCreated<'a> {
+    pub user: sql::Thing,
+    pub email: &'a str,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct EmailVerificationsRecord {
+    pub id: sql::Thing,
+}
@@ -26,6 +26,15 @@ pub enum Error {
     DataNotFoundForCreated,
     DataNotFoundForDelete,
     DataNotFoundForUpdate,
+    EmailVerificationAlreadyUsed,
+    EmailVerificationEmailChanged,
+    OidcLoginInvalid,
+    PwdResetAlreadyUsed,
+    RoleAdminLockedOut,
+    RoleNotValid(String),
+    TotpCodeAlreadyUsed,
+    TotpNotPending,
+    TotpRecoveryCodeInvalid,
     UserIdNotFound,
     UsernameAlreadyExists,
     UsernameNotValidFormat,
@@ -0,0 +1,103 @@
+use surrealdb::sql::Datetime;
+
+use crate::{
+    ctx::Ctx,
+    model::{
+        store::{BindReqId, Traced},
+        Error, ModelManager, Result,
+    },
+};
+
+use super::LoginAttempts;
+
+pub struct LoginAttemptsBmc;
+
+impl LoginAttemptsBmc {
+    /// The attempt key for counting failures per username.
+    pub fn username_key(username: &str) -> String {
+        format!("username:{username}")
+    }
+
+    /// The attempt key for counting failures per client ip.
+    pub fn ip_key(ip: &str) -> String {
+        format!("ip:{ip}")
+    }
+
+    /// Return the remaining lock time in seconds, if `key` is currently locked.
+    pub async fn locked_for_sec(ctx: &Ctx, mm: &ModelManager, key: &str) -> Result<Option<i64>> {
+        let db = mm.db();
+        let sql = "SELECT * FROM ONLY type::thing('login_attempts', $key);";
+        let mut result = db
+            .query(sql)
+            .bind_req_id(ctx)
+            .bind(("key", key))
+            .traced("LoginAttemptsBmc::locked_for_sec", sql)
+            .await?;
+        let login_attempts: Option<LoginAttempts> = result.take(0)?;
+
+        let now = Datetime::default();
+        let locked_for_sec = login_attempts
+            .and_then(|login_attempts| login_attempts.locked_until)
+            .filter(|locked_until| *locked_until > now)
+            .map(|locked_until| (locked_until.0.timestamp() - now.0.timestamp()).max(1));
+
+        Ok(locked_for_sec)
+    }
+
+    /// Count one more failure for `key`, and return the failures in the current window.
+    /// The count restarts at 1 when the last failure is older than `window_sec`.
+    pub async fn record_failure(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        key: &str,
+        window_sec: i64,
+    ) -> Result<i64> {
+        let db = mm.db();
+        let sql = "UPDATE type::thing('login_attempts', $key) SET failed_count = IF last_failed_on > time::now() - duration::from::secs($window_sec) THEN failed_count + 1 ELSE 1 END, last_failed_on = time::now();";
+        let mut result = db
+            .query(sql)
+            .bind_req_id(ctx)
+            .bind(("key", key))
+            .bind(("window_sec", window_sec))
+            .traced("LoginAttemptsBmc::record_failure", sql)
+            .await?;
+
+        let login_attempts = result
+            .take::<Option<LoginAttempts>>(0)?
+            .ok_or(Error::DataNotFoundForUpdate)?;
+
+        Ok(login_attempts.failed_count)
+    }
+
+    pub async fn lock(ctx: &Ctx, mm: &ModelManager, key: &str, lock_sec: i64) -> Result<()> {
+        let db = mm.db();
+        let sql = "UPDATE type::thing('login_attempts', $key) SET locked_until = time::now() + duration::from::secs($lock_sec);";
+        let mut result = db
+            .query(sql)
+            .bind_req_id(ctx)
+            .bind(("key", key))
+            .bind(("lock_sec", lock_sec))
+            .traced("LoginAttemptsBmc::lock", sql)
+            .await?;
+
+        let _login_attempts = result
+            .take::<Option<LoginAttempts>>(0)?
+            .ok_or(Error::DataNotFoundForUpdate)?;
+
+        Ok(())
+    }
+
+    /// Forget the failures and the lock of `key` (e.g., on login success or admin unlock).
+    pub async fn reset(ctx: &Ctx, mm: &ModelManager, key: &str) -> Result<()> {
+        let db = mm.db();
+        let sql = "DELETE type::thing('login_attempts', $key);";
+        db.query(sql)
+            .bind_req_id(ctx)
+            .bind(("key", key))
+            .traced("LoginAttemptsBmc::reset", sql)
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,12 @@
+pub mod bmc;
+
+use serde::Deserialize;
+use surrealdb::sql;
+
+#[derive(Debug, Deserialize)]
+pub struct LoginAttempts {
+    pub id: sql::Thing,
+    pub failed_count: i64,
+    pub last_failed_on: Option<sql::Datetime>,
+    pub locked_until: Option<sql::Datetime>,
+}
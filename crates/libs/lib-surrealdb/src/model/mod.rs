@@ -1,5 +1,13 @@
+pub mod api_keys;
+pub mod audit_logs;
 mod conditions;
+pub mod email_verifications;
 mod error;
+pub mod login_attempts;
+pub mod oidc_logins;
+pub mod pwd_resets;
+pub mod role_policies;
+pub mod sessions;
 mod store;
 pub mod tasks;
 pub mod users;
@@ -30,6 +38,15 @@ impl ModelManager {
         &self.db
     }
 
+    /// Sign out of SurrealDB, on shutdown.
+    ///
+    /// The connection itself is closed when the last clone of the ModelManager is dropped.
+    pub async fn close(self) -> Result<()> {
+        self.db.invalidate().await?;
+
+        Ok(())
+    }
+
     pub async fn test_connection(&self) {
         info!(
             "{:<12} - test_connection - {}",
@@ -0,0 +1,60 @@
+use crate::{
+    ctx::Ctx,
+    model::{
+        store::{BindReqId, Traced},
+        Error, ModelManager, Result,
+    },
+};
+
+use super::{OidcLogins, OidcLoginsCreated};
+
+pub struct OidcLoginsBmc;
+
+impl OidcLoginsBmc {
+    pub async fn create(
+        _ctx: &Ctx,
+        mm: &ModelManager,
+        code_verifier: &str,
+        nonce: &str,
+    ) -> Result<OidcLogins> {
+        let db = mm.db();
+
+        let oidc_logins_created = OidcLoginsCreated {
+            code_verifier,
+            nonce,
+        };
+
+        let mut created: Vec<OidcLogins> = db
+            .create("oidc_logins")
+            .content(oidc_logins_created)
+            .await?;
+
+        let oidc_login = created.pop().ok_or(Error::DataNotFoundForCreated)?;
+
+        Ok(oidc_login)
+    }
+
+    /// Mark the login as used and return it.
+    /// Fails with `OidcLoginInvalid` if it was used before or is older than `max_age_sec`,
+    /// so a `state` can only be used once.
+    pub async fn consume(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        id: &str,
+        max_age_sec: i64,
+    ) -> Result<OidcLogins> {
+        let db = mm.db();
+        let sql = "UPDATE oidc_logins SET used_on = time::now() WHERE id = type::thing('oidc_logins', $id) AND used_on IS NONE AND create_on > time::now() - duration::from::secs($max_age_sec);";
+        let mut result = db
+            .query(sql)
+            .bind_req_id(ctx)
+            .bind(("id", id))
+            .bind(("max_age_sec", max_age_sec))
+            .traced("OidcLoginsBmc::consume", sql)
+            .await?;
+
+        result
+            .take::<Option<OidcLogins>>(0)?
+            .ok_or(Error::OidcLoginInvalid)
+    }
+}
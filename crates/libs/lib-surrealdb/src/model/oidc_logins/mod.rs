@@ -0,0 +1,22 @@
+pub mod bmc;
+
+use serde::{Deserialize, Serialize};
+use surrealdb::sql;
+
+#[derive(Debug, Deserialize)]
+pub struct OidcLogins {
+    pub id: sql::Thing, // The `state` parameter
+
+    // -- flow info
+    pub code_verifier: String,
+    pub nonce: String,
+
+    pub used_on: Option<sql::Datetime>,
+    pub create_on: sql::Datetime,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OidcLoginsCreated<'a> {
+    pub code_verifier: &'a str,
+    pub nonce: &'a str,
+}
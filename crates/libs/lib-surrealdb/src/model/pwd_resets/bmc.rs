@@ -0,0 +1,115 @@
+use serde::de::DeserializeOwned;
+use surrealdb::sql::Thing;
+
+use crate::{
+    ctx::Ctx,
+    model::{
+        store::{BindReqId, Traced},
+        Error, ModelManager, Result,
+    },
+};
+
+use super::{PwdResets, PwdResetsCreated, PwdResetsRecord};
+
+pub struct PwdResetsBmc;
+
+impl PwdResetsBmc {
+    pub async fn get<'de, E>(ctx: &Ctx, mm: &ModelManager, id: &str) -> Result<Option<E>>
+    where
+        E: DeserializeOwned,
+    {
+        let db = mm.db();
+        let sql = "SELECT * FROM ONLY type::thing('pwd_resets', $id);";
+        let mut result = db
+            .query(sql)
+            .bind_req_id(ctx)
+            .bind(("id", id))
+            .traced("PwdResetsBmc::get", sql)
+            .await?;
+        let pwd_reset = result.take(0)?;
+
+        Ok(pwd_reset)
+    }
+
+    pub async fn create(_ctx: &Ctx, mm: &ModelManager, user_id: &str) -> Result<PwdResets> {
+        let db = mm.db();
+
+        let pwd_resets_created = PwdResetsCreated {
+            user: Thing::from(("users", user_id)),
+        };
+
+        let mut created: Vec<PwdResets> = db
+            .create("pwd_resets")
+            .content(pwd_resets_created)
+            .traced("PwdResetsBmc::create", "CREATE pwd_resets")
+            .await?;
+
+        let pwd_reset = created.pop().ok_or(Error::DataNotFoundForCreated)?;
+
+        Ok(pwd_reset)
+    }
+
+    /// Mark the reset request as used, and every other unused request of its user,
+    /// so the older reset links stop working once the password is reset.
+    /// Fails with `PwdResetAlreadyUsed` if it was consumed before, so a token can only be used once.
+    pub async fn consume(ctx: &Ctx, mm: &ModelManager, id: &str) -> Result<()> {
+        let db = mm.db();
+        // -- One statement, so the requests of the user are consumed at once.
+        let sql = "UPDATE pwd_resets SET used_on = time::now() WHERE used_on IS NONE AND user = (SELECT VALUE user FROM type::thing('pwd_resets', $id) WHERE used_on IS NONE)[0];";
+        let mut result = db
+            .query(sql)
+            .bind_req_id(ctx)
+            .bind(("id", id))
+            .traced("PwdResetsBmc::consume", sql)
+            .await?;
+
+        let pwd_resets_records: Vec<PwdResetsRecord> = result.take(0)?;
+        if !pwd_resets_records
+            .iter()
+            .any(|pwd_resets_record| pwd_resets_record.id.id.to_raw() == id)
+        {
+            return Err(Error::PwdResetAlreadyUsed);
+        }
+
+        Ok(())
+    }
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    pub type Result<T> = core::result::Result<T, Error>;
+    pub type Error = Box<dyn std::error::Error>; // For tests.
+    use crate::model;
+
+    use super::*;
+    use serial_test::serial;
+
+    #[serial]
+    #[tokio::test]
+    async fn test_consume_invalidates_other_requests() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = model::ModelManager::new().await?;
+        let ctx = Ctx::root_ctx();
+        let fx_user_id = "iR1f8i7Wg7jipR3uhDhJ";
+        let fx_older = PwdResetsBmc::create(&ctx, &mm, fx_user_id).await?;
+        let fx_newer = PwdResetsBmc::create(&ctx, &mm, fx_user_id).await?;
+        let fx_older_id = fx_older.id.id.to_raw();
+
+        // -- Exec
+        PwdResetsBmc::consume(&ctx, &mm, &fx_newer.id.id.to_raw()).await?;
+
+        // -- Check
+        let older: PwdResets = PwdResetsBmc::get(&ctx, &mm, &fx_older_id)
+            .await?
+            .ok_or("Should have the older request")?;
+        assert!(older.used_on.is_some());
+        assert!(matches!(
+            PwdResetsBmc::consume(&ctx, &mm, &fx_older_id).await,
+            Err(model::Error::PwdResetAlreadyUsed)
+        ));
+
+        Ok(())
+    }
+}
+// endregion: --- Tests
@@ -0,0 +1,26 @@
+pub mod bmc;
+
+use serde::{Deserialize, Serialize};
+use surrealdb::sql;
+
+#[derive(Debug, Deserialize)]
+pub struct PwdResets {
+    pub id: sql::Thing,
+    pub user: sql::Thing, // Users ID Table
+
+    // -- token info
+    pub token_salt: sql::Uuid,
+
+    pub used_on: Option<sql::Datetime>,
+    pub create_on: sql::Datetime,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PwdResetsCreated {
+    pub user: sql::Thing,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PwdResetsRecord {
+    pub id: sql::Thing,
+}
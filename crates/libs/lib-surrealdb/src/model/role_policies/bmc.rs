@@ -0,0 +1,137 @@
+use crate::{
+    ctx::Ctx,
+    model::{
+        store::{BindReqId, Traced},
+        Error, ModelManager, Result,
+    },
+};
+
+use super::{Permission, Role, RolePolicies};
+
+pub struct RolePoliciesBmc;
+
+impl RolePoliciesBmc {
+    pub async fn list(ctx: &Ctx, mm: &ModelManager) -> Result<Vec<RolePolicies>> {
+        let db = mm.db();
+        let sql = "SELECT * FROM role_policies;";
+        let mut result = db
+            .query(sql)
+            .bind_req_id(ctx)
+            .traced("RolePoliciesBmc::list", sql)
+            .await?;
+        let role_policies: Vec<RolePolicies> = result.take(0)?;
+
+        Ok(role_policies)
+    }
+
+    /// Whether users of `role` must have a second factor to log in.
+    /// A role without policy does not require it.
+    pub async fn require_2fa(ctx: &Ctx, mm: &ModelManager, role: &str) -> Result<bool> {
+        let db = mm.db();
+        let sql = "SELECT * FROM ONLY type::thing('role_policies', $role);";
+        let mut result = db
+            .query(sql)
+            .bind_req_id(ctx)
+            .bind(("role", role))
+            .traced("RolePoliciesBmc::require_2fa", sql)
+            .await?;
+        let role_policies: Option<RolePolicies> = result.take(0)?;
+
+        Ok(role_policies.is_some_and(|role_policies| role_policies.require_2fa))
+    }
+
+    /// The permissions granted to `role`.
+    /// A role without policy has no permission.
+    pub async fn permissions(ctx: &Ctx, mm: &ModelManager, role: Role) -> Result<Vec<Permission>> {
+        let db = mm.db();
+        let sql = "SELECT * FROM ONLY type::thing('role_policies', $role);";
+        let mut result = db
+            .query(sql)
+            .bind_req_id(ctx)
+            .bind(("role", role.as_str()))
+            .traced("RolePoliciesBmc::permissions", sql)
+            .await?;
+        let role_policies: Option<RolePolicies> = result.take(0)?;
+
+        Ok(role_policies
+            .map(|role_policies| role_policies.permissions)
+            .unwrap_or_default())
+    }
+
+    /// Whether one of the ctx roles is granted `permission`,
+    /// and the ctx api key (if any) has it in its scopes.
+    pub async fn has_permission(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        permission: Permission,
+    ) -> Result<bool> {
+        if ctx
+            .scopes()
+            .is_some_and(|scopes| !scopes.contains(&permission))
+        {
+            return Ok(false);
+        }
+
+        for role in ctx.roles() {
+            let permissions = RolePoliciesBmc::permissions(ctx, mm, *role).await?;
+            if permissions.contains(&permission) {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    pub async fn set_require_2fa(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        role: Role,
+        require_2fa: bool,
+    ) -> Result<RolePolicies> {
+        let db = mm.db();
+        let user_id = ctx.user_id().ok_or(Error::CannotGetUserIdFromCtx)?;
+
+        let sql = "UPDATE type::thing('role_policies', $role) SET require_2fa = $require_2fa, update_by = type::thing('users', $update_by), update_on = time::now();";
+        let mut result = db
+            .query(sql)
+            .bind_req_id(ctx)
+            .bind(("role", role.as_str()))
+            .bind(("require_2fa", require_2fa))
+            .bind(("update_by", user_id))
+            .traced("RolePoliciesBmc::set_require_2fa", sql)
+            .await?;
+
+        result
+            .take::<Option<RolePolicies>>(0)?
+            .ok_or(Error::DataNotFoundForUpdate)
+    }
+
+    pub async fn set_permissions(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        role: Role,
+        permissions: Vec<Permission>,
+    ) -> Result<RolePolicies> {
+        let db = mm.db();
+        let user_id = ctx.user_id().ok_or(Error::CannotGetUserIdFromCtx)?;
+
+        // -- The admin role must keep the right to edit the roles.
+        if role == Role::Admin && !permissions.contains(&Permission::RolesWrite) {
+            return Err(Error::RoleAdminLockedOut);
+        }
+
+        let sql = "UPDATE type::thing('role_policies', $role) SET permissions = $permissions, update_by = type::thing('users', $update_by), update_on = time::now();";
+        let mut result = db
+            .query(sql)
+            .bind_req_id(ctx)
+            .bind(("role", role.as_str()))
+            .bind(("permissions", permissions))
+            .bind(("update_by", user_id))
+            .traced("RolePoliciesBmc::set_permissions", sql)
+            .await?;
+
+        result
+            .take::<Option<RolePolicies>>(0)?
+            .ok_or(Error::DataNotFoundForUpdate)
+    }
+}
@@ -0,0 +1,103 @@
+pub mod bmc;
+
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use surrealdb::sql;
+
+use super::Error;
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct RolePolicies {
+    pub id: sql::Thing,
+    pub require_2fa: bool,
+    #[serde(default)]
+    pub permissions: Vec<Permission>,
+    pub update_by: Option<sql::Thing>,
+    pub update_on: sql::Datetime,
+}
+
+// region:    --- Role
+/// The roles accepted by the `users.role` field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Role {
+    User,
+    Admin,
+}
+
+impl Role {
+    pub const ALL: [Role; 2] = [Role::User, Role::Admin];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::User => "USER",
+            Role::Admin => "ADMIN",
+        }
+    }
+}
+
+impl FromStr for Role {
+    type Err = Error;
+
+    fn from_str(role: &str) -> Result<Self, Self::Err> {
+        Role::ALL
+            .into_iter()
+            .find(|r| r.as_str() == role)
+            .ok_or_else(|| Error::RoleNotValid(role.to_string()))
+    }
+}
+// endregion: --- Role
+
+// region:    --- Permission
+/// The permissions a role can be granted, as `<resource>:<action>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Permission {
+    #[serde(rename = "users:read")]
+    UsersRead,
+    #[serde(rename = "users:write")]
+    UsersWrite,
+    #[serde(rename = "users:delete")]
+    UsersDelete,
+    #[serde(rename = "users:impersonate")]
+    UsersImpersonate,
+    #[serde(rename = "tasks:read")]
+    TasksRead,
+    #[serde(rename = "tasks:write")]
+    TasksWrite,
+    #[serde(rename = "tasks:delete")]
+    TasksDelete,
+    #[serde(rename = "roles:read")]
+    RolesRead,
+    #[serde(rename = "roles:write")]
+    RolesWrite,
+}
+
+impl Permission {
+    pub const ALL: [Permission; 9] = [
+        Permission::UsersRead,
+        Permission::UsersWrite,
+        Permission::UsersDelete,
+        Permission::UsersImpersonate,
+        Permission::TasksRead,
+        Permission::TasksWrite,
+        Permission::TasksDelete,
+        Permission::RolesRead,
+        Permission::RolesWrite,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Permission::UsersRead => "users:read",
+            Permission::UsersWrite => "users:write",
+            Permission::UsersDelete => "users:delete",
+            Permission::UsersImpersonate => "users:impersonate",
+            Permission::TasksRead => "tasks:read",
+            Permission::TasksWrite => "tasks:write",
+            Permission::TasksDelete => "tasks:delete",
+            Permission::RolesRead => "roles:read",
+            Permission::RolesWrite => "roles:write",
+        }
+    }
+}
+// endregion: --- Permission
@@ -0,0 +1,148 @@
+use surrealdb::sql::Datetime;
+
+use crate::{
+    ctx::Ctx,
+    model::{
+        store::{BindReqId, Traced},
+        Error, ModelManager, Result,
+    },
+};
+
+use super::{Sessions, SessionsForCreate, SessionsRecord};
+
+pub struct SessionsBmc;
+
+impl SessionsBmc {
+    pub async fn get(ctx: &Ctx, mm: &ModelManager, id: &str) -> Result<Option<Sessions>> {
+        let db = mm.db();
+        let sql = "SELECT * FROM ONLY type::thing('sessions', $id);";
+        let mut result = db
+            .query(sql)
+            .bind_req_id(ctx)
+            .bind(("id", id))
+            .traced("SessionsBmc::get", sql)
+            .await?;
+        let session = result.take(0)?;
+
+        Ok(session)
+    }
+
+    /// Get the session if it is neither revoked nor expired.
+    pub async fn get_active(ctx: &Ctx, mm: &ModelManager, id: &str) -> Result<Option<Sessions>> {
+        let session = SessionsBmc::get(ctx, mm, id).await?;
+
+        let now = Datetime::default();
+        let session = session
+            .filter(|session| session.revoked_on.is_none())
+            .filter(|session| session.expire_on > now);
+
+        Ok(session)
+    }
+
+    /// Create a session of the user `user_id`, on its login.
+    pub async fn create(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        user_id: &str,
+        sessions_for_create: SessionsForCreate,
+    ) -> Result<Sessions> {
+        let db = mm.db();
+
+        let SessionsForCreate {
+            user_agent,
+            client_ip,
+            expire_sec,
+        } = sessions_for_create;
+
+        let sql = "CREATE ONLY sessions CONTENT {
+            user: type::thing('users', $user_id),
+            user_agent: $user_agent,
+            client_ip: $client_ip,
+            expire_on: time::now() + duration::from::secs($expire_sec)
+        };";
+        let mut result = db
+            .query(sql)
+            .bind_req_id(ctx)
+            .bind(("user_id", user_id))
+            .bind(("user_agent", user_agent))
+            .bind(("client_ip", client_ip))
+            .bind(("expire_sec", expire_sec))
+            .traced("SessionsBmc::create", sql)
+            .await?;
+
+        result
+            .take::<Option<Sessions>>(0)?
+            .ok_or(Error::DataNotFoundForCreated)
+    }
+
+    /// List the active sessions of the user, the last seen first.
+    pub async fn list_by_user(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        user_id: &str,
+    ) -> Result<Vec<Sessions>> {
+        let db = mm.db();
+        let sql = "SELECT * FROM sessions WHERE user = type::thing('users', $user_id) AND revoked_on IS NONE AND expire_on > time::now() ORDER BY last_seen_on DESC;";
+        let mut result = db
+            .query(sql)
+            .bind_req_id(ctx)
+            .bind(("user_id", user_id))
+            .traced("SessionsBmc::list_by_user", sql)
+            .await?;
+        let sessions: Vec<Sessions> = result.take(0)?;
+
+        Ok(sessions)
+    }
+
+    /// Track the last time the session was used.
+    pub async fn touch(ctx: &Ctx, mm: &ModelManager, id: &str) -> Result<()> {
+        let db = mm.db();
+        let sql = "UPDATE type::thing('sessions', $id) SET last_seen_on = time::now();";
+        let mut result = db
+            .query(sql)
+            .bind_req_id(ctx)
+            .bind(("id", id))
+            .traced("SessionsBmc::touch", sql)
+            .await?;
+
+        let _sessions_record = result
+            .take::<Option<SessionsRecord>>(0)?
+            .ok_or(Error::DataNotFoundForUpdate)?;
+
+        Ok(())
+    }
+
+    /// Revoke the session, its jwt is rejected from now on.
+    pub async fn revoke(ctx: &Ctx, mm: &ModelManager, id: &str) -> Result<()> {
+        let db = mm.db();
+        let sql = "UPDATE type::thing('sessions', $id) SET revoked_on = time::now() WHERE revoked_on IS NONE;";
+        let mut result = db
+            .query(sql)
+            .bind_req_id(ctx)
+            .bind(("id", id))
+            .traced("SessionsBmc::revoke", sql)
+            .await?;
+
+        let _sessions_record = result
+            .take::<Option<SessionsRecord>>(0)?
+            .ok_or(Error::DataNotFoundForUpdate)?;
+
+        Ok(())
+    }
+
+    /// Revoke every active session of the user, e.g., on a password change.
+    pub async fn revoke_by_user(ctx: &Ctx, mm: &ModelManager, user_id: &str) -> Result<()> {
+        let db = mm.db();
+        let sql = "UPDATE sessions SET revoked_on = time::now() WHERE user = type::thing('users', $user_id) AND revoked_on IS NONE;";
+        let mut result = db
+            .query(sql)
+            .bind_req_id(ctx)
+            .bind(("user_id", user_id))
+            .traced("SessionsBmc::revoke_by_user", sql)
+            .await?;
+
+        let _sessions_records = result.take::<Vec<SessionsRecord>>(0)?;
+
+        Ok(())
+    }
+}
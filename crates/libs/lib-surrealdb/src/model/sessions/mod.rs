@@ -0,0 +1,32 @@
+pub mod bmc;
+
+use serde::{Deserialize, Serialize};
+use surrealdb::sql;
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Sessions {
+    pub id: sql::Thing,   // The `jti` of the session jwt
+    pub user: sql::Thing, // Users ID Table
+
+    // -- client info
+    pub user_agent: Option<String>,
+    pub client_ip: Option<String>,
+
+    pub expire_on: sql::Datetime,
+    pub last_seen_on: sql::Datetime,
+    pub revoked_on: Option<sql::Datetime>,
+    pub create_on: sql::Datetime,
+}
+
+#[derive(Debug)]
+pub struct SessionsForCreate {
+    pub user_agent: Option<String>,
+    pub client_ip: Option<String>,
+    /// Seconds before the session expires, the duration of its jwt.
+    pub expire_sec: i64,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SessionsRecord {
+    pub id: sql::Thing,
+}
@@ -1,16 +1,24 @@
 mod error;
 
-use std::sync::Arc;
+use std::{
+    future::{Future, IntoFuture},
+    sync::Arc,
+    time::Instant,
+};
 
 use surrealdb::{
     engine::remote::ws::{Client, Ws},
+    method::Query,
     opt::auth::Root,
     Surreal,
 };
 
+use metrics::histogram;
+use tracing::{info_span, Instrument};
+
 pub use self::error::{Error, Result};
 
-use crate::core_config;
+use crate::{core_config, ctx::Ctx};
 
 pub type Db = Arc<Surreal<Client>>;
 
@@ -36,3 +44,46 @@ pub async fn new_db_pool() -> Result<Db> {
 
     Ok(db)
 }
+
+/// Run the SurrealDB call of the Bmc `operation` (e.g., `UsersBmc::get`) in a `db.query` span,
+/// child of the span of the request, and record its duration.
+pub(in crate::model) trait Traced: IntoFuture + Sized {
+    fn traced(
+        self,
+        operation: &'static str,
+        statement: &str,
+    ) -> impl Future<Output = Self::Output> {
+        let span = info_span!(
+            "db.query",
+            otel.kind = "client",
+            db.system = "surrealdb",
+            db.operation = operation,
+            db.statement = statement,
+        );
+        let future = self.into_future();
+
+        async move {
+            let start = Instant::now();
+            let output = future.await;
+            histogram!("db_query_duration_seconds", "operation" => operation)
+                .record(start.elapsed().as_secs_f64());
+
+            output
+        }
+        .instrument(span)
+    }
+}
+
+impl<F: IntoFuture> Traced for F {}
+
+/// Bind the request id of the ctx as the `$req_id` param of the query (`NONE` without one),
+/// so the queries in the SurrealDB logs can be correlated with the request.
+pub(in crate::model) trait BindReqId {
+    fn bind_req_id(self, ctx: &Ctx) -> Self;
+}
+
+impl BindReqId for Query<'_, Client> {
+    fn bind_req_id(self, ctx: &Ctx) -> Self {
+        self.bind(("req_id", ctx.req_id().map(|req_id| req_id.to_string())))
+    }
+}
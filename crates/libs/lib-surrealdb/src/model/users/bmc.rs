@@ -7,33 +7,39 @@ use uuid::Uuid;
 use crate::{
     ctx::Ctx,
     model::{
-        users::{UsersForAuth, UsersForDelete},
+        store::{BindReqId, Traced},
+        users::UsersForDelete,
         Error, ModelManager, Result,
     },
 };
 
 use super::{
-    UsersCreated, UsersDeleted, UsersForCreate, UsersForUpdate, UsersForUpdateByAdmin, UsersRecord,
-    UsersUpdated, UsersUpdatedByAdmin,
+    UsersCreated, UsersDeleted, UsersForCreate, UsersForImport, UsersForUpdate,
+    UsersForUpdateByAdmin, UsersRecord, UsersUpdated, UsersUpdatedByAdmin,
 };
 
 pub struct UsersBmc;
 
 impl UsersBmc {
-    pub async fn get<'de, E>(_ctx: &Ctx, mm: &ModelManager, id: &str) -> Result<Option<E>>
+    pub async fn get<'de, E>(ctx: &Ctx, mm: &ModelManager, id: &str) -> Result<Option<E>>
     where
         E: DeserializeOwned,
     {
         let db = mm.db();
         let sql = format!("SELECT * FROM ONLY type::thing('users', $id) WHERE deleted_on IS NONE;");
-        let mut result = db.query(sql).bind(("id", id)).await?;
+        let mut result = db
+            .query(sql.as_str())
+            .bind_req_id(ctx)
+            .bind(("id", id))
+            .traced("UsersBmc::get", &sql)
+            .await?;
         let user = result.take(0)?;
 
         Ok(user)
     }
 
     pub async fn list<'de, E>(
-        _ctx: &Ctx,
+        ctx: &Ctx,
         mm: &ModelManager,
         limit: Option<u32>,
         offset: Option<u32>,
@@ -52,9 +58,11 @@ impl UsersBmc {
         let sql =
             format!("SELECT * FROM users WHERE deleted_on IS NONE ORDER BY create_on {order} LIMIT $limit START $offset;");
         let mut result = db
-            .query(sql)
+            .query(sql.as_str())
+            .bind_req_id(ctx)
             .bind(("limit", limit.unwrap_or(50)))
             .bind(("offset", offset.unwrap_or(0)))
+            .traced("UsersBmc::list", &sql)
             .await?;
 
         let users: Vec<E> = result.take(0)?;
@@ -63,7 +71,7 @@ impl UsersBmc {
     }
 
     pub async fn first_by_username<'de, E>(
-        _ctx: &Ctx,
+        ctx: &Ctx,
         mm: &ModelManager,
         username: &str,
     ) -> Result<Option<E>>
@@ -74,7 +82,9 @@ impl UsersBmc {
         let sql = "SELECT * FROM users WHERE username = $username AND deleted_on IS NONE LIMIT 1;";
         let mut result = db
             .query(sql)
+            .bind_req_id(ctx)
             .bind(("username", username.to_string()))
+            .traced("UsersBmc::first_by_username", sql)
             .await?;
 
         let users_for_auth: Option<E> = result.take(0)?;
@@ -82,6 +92,76 @@ impl UsersBmc {
         Ok(users_for_auth)
     }
 
+    pub async fn first_by_email<'de, E>(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        email: &str,
+    ) -> Result<Option<E>>
+    where
+        E: DeserializeOwned,
+    {
+        let db = mm.db();
+        let sql = "SELECT * FROM users WHERE email = $email AND deleted_on IS NONE LIMIT 1;";
+        let mut result = db
+            .query(sql)
+            .bind_req_id(ctx)
+            .bind(("email", email.to_string()))
+            .traced("UsersBmc::first_by_email", sql)
+            .await?;
+
+        let users: Option<E> = result.take(0)?;
+
+        Ok(users)
+    }
+
+    /// Get the user linked to the OpenID Connect identity `oidc_subject`.
+    pub async fn first_by_oidc_subject<'de, E>(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        oidc_subject: &str,
+    ) -> Result<Option<E>>
+    where
+        E: DeserializeOwned,
+    {
+        let db = mm.db();
+        let sql =
+            "SELECT * FROM users WHERE oidc_subject = $oidc_subject AND deleted_on IS NONE LIMIT 1;";
+        let mut result = db
+            .query(sql)
+            .bind_req_id(ctx)
+            .bind(("oidc_subject", oidc_subject.to_string()))
+            .traced("UsersBmc::first_by_oidc_subject", sql)
+            .await?;
+
+        let users: Option<E> = result.take(0)?;
+
+        Ok(users)
+    }
+
+    /// Link the user to the OpenID Connect identity `oidc_subject`.
+    pub async fn link_oidc_subject(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        id: &str,
+        oidc_subject: &str,
+    ) -> Result<()> {
+        let db = mm.db();
+        let sql = "UPDATE type::thing('users', $id) SET oidc_subject = $oidc_subject, update_on = time::now();";
+        let mut result = db
+            .query(sql)
+            .bind_req_id(ctx)
+            .bind(("id", id))
+            .bind(("oidc_subject", oidc_subject))
+            .traced("UsersBmc::link_oidc_subject", sql)
+            .await?;
+
+        let _users_record = result
+            .take::<Option<UsersRecord>>(0)?
+            .ok_or(Error::DataNotFoundForUpdate)?;
+
+        Ok(())
+    }
+
     pub async fn delete(
         _ctx: &Ctx,
         mm: &ModelManager,
@@ -168,27 +248,62 @@ impl UsersBmc {
         user_record
     }
 
-    pub async fn update_pwd(
+    /// Replace the hash of an unchanged password (e.g., outdated scheme or params).
+    ///
+    /// Works with the root ctx (no `update_by`), and only if the stored hash is still
+    /// `pwd_ref`, so a password changed meanwhile is never overwritten.
+    pub async fn rehash_pwd(
         ctx: &Ctx,
         mm: &ModelManager,
         id: &str,
         password: String,
         password_salt: Uuid,
+        pwd_ref: &str,
     ) -> Result<()> {
         let db = mm.db();
-        let user_id = ctx.user_id().ok_or(Error::CannotGetUserIdFromCtx)?;
 
         // -- Hashing Password
         let to_hash = ContentToHash::new(password, password_salt);
         let password_hash = pwd::hash_pwd(to_hash).await?;
 
-        let sql =
-            "UPDATE type::thing('users',$id) SET password = $password_hash, update_by = type::thing('users', $update_by), update_on = time::now();";
+        let sql = "UPDATE type::thing('users', $id) SET password = $password_hash WHERE password = $pwd_ref;";
+        let mut result = db
+            .query(sql)
+            .bind_req_id(ctx)
+            .bind(("id", id))
+            .bind(("password_hash", password_hash))
+            .bind(("pwd_ref", pwd_ref))
+            .traced("UsersBmc::rehash_pwd", sql)
+            .await?;
+
+        let _users_record = result
+            .take::<Option<UsersRecord>>(0)?
+            .ok_or(Error::DataNotFoundForUpdate)?;
+
+        Ok(())
+    }
+
+    /// Set a new password with a fresh password salt, and rotate the token salt
+    /// so every token issued before is invalidated.
+    pub async fn reset_pwd(ctx: &Ctx, mm: &ModelManager, id: &str, password: String) -> Result<()> {
+        let db = mm.db();
+        let user_id = ctx.user_id().ok_or(Error::CannotGetUserIdFromCtx)?;
+
+        let password_salt = sql::Uuid::new_v4();
+
+        // -- Hashing Password
+        let to_hash = ContentToHash::new(password, Uuid::from(password_salt));
+        let password_hash = pwd::hash_pwd(to_hash).await?;
+
+        let sql = "UPDATE type::thing('users',$id) SET password = $password_hash, password_salt = $password_salt, token_salt = rand::uuid::v4(), update_by = type::thing('users', $update_by), update_on = time::now();";
         let mut result = db
             .query(sql)
+            .bind_req_id(ctx)
             .bind(("id", id))
             .bind(("password_hash", password_hash))
+            .bind(("password_salt", password_salt))
             .bind(("update_by", user_id))
+            .traced("UsersBmc::reset_pwd", sql)
             .await?;
 
         let _users_record = result
@@ -198,22 +313,164 @@ impl UsersBmc {
         Ok(())
     }
 
+    /// Stamp `email_verified`, only if `email` is still the email of the user.
+    pub async fn verify_email(ctx: &Ctx, mm: &ModelManager, id: &str, email: &str) -> Result<()> {
+        let db = mm.db();
+        let sql = "UPDATE type::thing('users', $id) SET email_verified = time::now() WHERE email = $email AND deleted_on IS NONE;";
+        let mut result = db
+            .query(sql)
+            .bind_req_id(ctx)
+            .bind(("id", id))
+            .bind(("email", email))
+            .traced("UsersBmc::verify_email", sql)
+            .await?;
+
+        let _users_record = result
+            .take::<Option<UsersRecord>>(0)?
+            .ok_or(Error::EmailVerificationEmailChanged)?;
+
+        Ok(())
+    }
+
+    /// Clear `email_verified`, e.g., after the email was changed.
+    pub async fn unverify_email(ctx: &Ctx, mm: &ModelManager, id: &str) -> Result<()> {
+        let db = mm.db();
+        let sql = "UPDATE type::thing('users', $id) SET email_verified = NONE;";
+        let mut result = db
+            .query(sql)
+            .bind_req_id(ctx)
+            .bind(("id", id))
+            .traced("UsersBmc::unverify_email", sql)
+            .await?;
+
+        let _users_record = result
+            .take::<Option<UsersRecord>>(0)?
+            .ok_or(Error::DataNotFoundForUpdate)?;
+
+        Ok(())
+    }
+
+    /// Store a new TOTP secret, pending until confirmed with `enable_totp`.
+    pub async fn set_totp_pending_secret(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        id: &str,
+        secret: &str,
+    ) -> Result<()> {
+        let db = mm.db();
+        let sql = "UPDATE type::thing('users', $id) SET totp_pending_secret = $secret WHERE deleted_on IS NONE;";
+        let mut result = db
+            .query(sql)
+            .bind_req_id(ctx)
+            .bind(("id", id))
+            .bind(("secret", secret))
+            .traced("UsersBmc::set_totp_pending_secret", sql)
+            .await?;
+
+        let _users_record = result
+            .take::<Option<UsersRecord>>(0)?
+            .ok_or(Error::DataNotFoundForUpdate)?;
+
+        Ok(())
+    }
+
+    /// Promote the pending secret (only if still `secret`), and replace the recovery codes.
+    pub async fn enable_totp(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        id: &str,
+        secret: &str,
+        last_step: i64,
+        recovery_code_hashes: Vec<String>,
+    ) -> Result<()> {
+        let db = mm.db();
+        let sql = "UPDATE type::thing('users', $id) SET totp_secret = $secret, totp_pending_secret = NONE, totp_enabled_on = time::now(), totp_last_step = $last_step, totp_recovery_codes = $recovery_codes WHERE totp_pending_secret = $secret AND deleted_on IS NONE;";
+        let mut result = db
+            .query(sql)
+            .bind_req_id(ctx)
+            .bind(("id", id))
+            .bind(("secret", secret))
+            .bind(("last_step", last_step))
+            .bind(("recovery_codes", recovery_code_hashes))
+            .traced("UsersBmc::enable_totp", sql)
+            .await?;
+
+        let _users_record = result
+            .take::<Option<UsersRecord>>(0)?
+            .ok_or(Error::TotpNotPending)?;
+
+        Ok(())
+    }
+
+    /// Remove the second factor, e.g., when an admin resets a lost device.
+    pub async fn disable_totp(ctx: &Ctx, mm: &ModelManager, id: &str) -> Result<()> {
+        let db = mm.db();
+        let sql = "UPDATE type::thing('users', $id) SET totp_secret = NONE, totp_pending_secret = NONE, totp_enabled_on = NONE, totp_last_step = NONE, totp_recovery_codes = [] WHERE deleted_on IS NONE;";
+        let mut result = db
+            .query(sql)
+            .bind_req_id(ctx)
+            .bind(("id", id))
+            .traced("UsersBmc::disable_totp", sql)
+            .await?;
+
+        let _users_record = result
+            .take::<Option<UsersRecord>>(0)?
+            .ok_or(Error::DataNotFoundForUpdate)?;
+
+        Ok(())
+    }
+
+    /// Record the time step of an accepted code, only if newer than the last one,
+    /// so the same code cannot be used twice (even by concurrent requests).
+    pub async fn use_totp_step(ctx: &Ctx, mm: &ModelManager, id: &str, step: i64) -> Result<()> {
+        let db = mm.db();
+        let sql = "UPDATE type::thing('users', $id) SET totp_last_step = $step WHERE totp_last_step IS NONE OR totp_last_step < $step;";
+        let mut result = db
+            .query(sql)
+            .bind_req_id(ctx)
+            .bind(("id", id))
+            .bind(("step", step))
+            .traced("UsersBmc::use_totp_step", sql)
+            .await?;
+
+        let _users_record = result
+            .take::<Option<UsersRecord>>(0)?
+            .ok_or(Error::TotpCodeAlreadyUsed)?;
+
+        Ok(())
+    }
+
+    /// Remove a recovery code by its hash, fails if the user does not have it (anymore).
+    pub async fn use_totp_recovery_code(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        id: &str,
+        code_hash: &str,
+    ) -> Result<()> {
+        let db = mm.db();
+        let sql = "UPDATE type::thing('users', $id) SET totp_recovery_codes -= $code_hash WHERE totp_recovery_codes CONTAINS $code_hash;";
+        let mut result = db
+            .query(sql)
+            .bind_req_id(ctx)
+            .bind(("id", id))
+            .bind(("code_hash", code_hash))
+            .traced("UsersBmc::use_totp_recovery_code", sql)
+            .await?;
+
+        let _users_record = result
+            .take::<Option<UsersRecord>>(0)?
+            .ok_or(Error::TotpRecoveryCodeInvalid)?;
+
+        Ok(())
+    }
+
     pub async fn create(
         ctx: &Ctx,
         mm: &ModelManager,
         users_for_create: UsersForCreate,
         is_email_verified: bool,
     ) -> Result<UsersRecord> {
-        // Verify Username in DB
-        let users =
-            UsersBmc::first_by_username::<UsersRecord>(ctx, mm, &users_for_create.username).await?;
-        if let Some(_) = users {
-            return Err(Error::UsernameAlreadyExists);
-        }
-        let validate_username = UsersBmc::validate_username(mm, &users_for_create.username).await?;
-        if !validate_username {
-            return Err(Error::UsernameNotValidFormat);
-        }
+        UsersBmc::validate_new_username(ctx, mm, &users_for_create.username).await?;
 
         let email_verified = match is_email_verified {
             true => Some(Datetime::default()),
@@ -227,6 +484,49 @@ impl UsersBmc {
         Ok(user_record)
     }
 
+    /// Create a user with an already hashed password (e.g., migrated from another system).
+    ///
+    /// The password is stored verbatim, tagged with its scheme (e.g., `#legacy-bcrypt#$2b$...`),
+    /// and is upgraded to the default scheme on the first successful login.
+    pub async fn import(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        users_for_import: UsersForImport,
+    ) -> Result<UsersRecord> {
+        UsersBmc::validate_new_username(ctx, mm, &users_for_import.username).await?;
+        pwd::validate_pwd_ref_scheme(&users_for_import.password)?;
+
+        let db = mm.db();
+
+        let email_verified = users_for_import.email_verified.then(Datetime::default);
+        let user_id_create = ctx.user_id_thing();
+
+        let users_created = UsersCreated {
+            username: &users_for_import.username,
+            email: &users_for_import.email,
+            email_verified,
+            title: users_for_import.title,
+            firstname: users_for_import.firstname,
+            middlename: users_for_import.middlename,
+            lastname: users_for_import.lastname,
+            password: users_for_import.password,
+            // Note: Not used by the imported hash, but by its upgrade.
+            password_salt: sql::Uuid::new_v4(),
+            create_by: &user_id_create,
+            update_by: &user_id_create,
+        };
+
+        let mut created: Vec<UsersRecord> = db
+            .create("users")
+            .content(users_created)
+            .traced("UsersBmc::import", "CREATE users")
+            .await?;
+
+        let users = created.pop().ok_or(Error::DataNotFound)?;
+
+        Ok(users)
+    }
+
     pub async fn validate_password(
         password: String,
         password_salt: Uuid,
@@ -247,23 +547,32 @@ impl UsersBmc {
         Ok(())
     }
 
-    pub async fn is_admin(ctx: &Ctx, mm: &ModelManager) -> Result<bool> {
-        let user_id = ctx.user_id().ok_or(Error::UserIdNotFound)?;
-        let user_for_auth = UsersBmc::get::<UsersForAuth>(ctx, mm, user_id)
-            .await?
-            .ok_or(Error::UserIdNotFound)?;
-        match user_for_auth.role.as_str() {
-            "ADMIN" => Ok(true),
-            _ => Ok(false),
+    /// Verify the username is not used yet, and is in a valid format.
+    async fn validate_new_username(ctx: &Ctx, mm: &ModelManager, username: &str) -> Result<()> {
+        // Verify Username in DB
+        let users = UsersBmc::first_by_username::<UsersRecord>(ctx, mm, username).await?;
+        if let Some(_) = users {
+            return Err(Error::UsernameAlreadyExists);
+        }
+        let validate_username = UsersBmc::validate_username(ctx, mm, username).await?;
+        if !validate_username {
+            return Err(Error::UsernameNotValidFormat);
         }
+
+        Ok(())
     }
 
-    async fn validate_username(mm: &ModelManager, username: &str) -> Result<bool> {
+    async fn validate_username(ctx: &Ctx, mm: &ModelManager, username: &str) -> Result<bool> {
         let db = mm.db();
 
         let sql = "RETURN string::is::email($username);";
 
-        let mut result = db.query(sql).bind(("username", username)).await?;
+        let mut result = db
+            .query(sql)
+            .bind_req_id(ctx)
+            .bind(("username", username))
+            .traced("UsersBmc::validate_username", sql)
+            .await?;
 
         result
             .take::<Option<bool>>(0)?
@@ -286,7 +595,7 @@ impl UsersBmc {
 
         let users_created = UsersCreated {
             username: &users_for_create.username,
-            email: &users_for_create.username,
+            email: &users_for_create.email,
             email_verified,
             title: users_for_create.title,
             firstname: users_for_create.firstname,
@@ -298,7 +607,11 @@ impl UsersBmc {
             update_by: &user_id_create,
         };
 
-        let mut created: Vec<UsersRecord> = db.create("users").content(users_created).await?;
+        let mut created: Vec<UsersRecord> = db
+            .create("users")
+            .content(users_created)
+            .traced("UsersBmc::inner_create", "CREATE users")
+            .await?;
 
         let users = created.pop().ok_or(Error::DataNotFound)?;
 
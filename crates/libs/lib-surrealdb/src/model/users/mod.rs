@@ -3,6 +3,9 @@ pub mod bmc;
 use serde::{Deserialize, Serialize};
 use surrealdb::sql;
 
+/// The titles a user can have, as asserted by the `users` table.
+pub const TITLES: [&str; 3] = ["นาย", "นางสาว", "นาง"];
+
 #[derive(Debug, Deserialize)]
 pub struct Users {
     pub id: sql::Thing,
@@ -74,6 +77,18 @@ pub struct UsersForCreate {
     pub password: String,
 }
 
+#[derive(Debug, Serialize)]
+pub struct UsersForImport {
+    pub username: String,
+    pub email: String,
+    pub email_verified: bool,
+    pub title: String,
+    pub firstname: String,
+    pub middlename: Option<String>,
+    pub lastname: String,
+    pub password: String, // already hashed, #_scheme_id_#....
+}
+
 #[derive(Debug, Serialize)]
 pub struct UsersForUpdate {
     pub email: Option<String>,
@@ -138,6 +153,7 @@ pub struct UsersDeleted {
 pub struct UsersForLogin {
     pub id: sql::Thing,
     pub username: String,
+    pub email_verified: Option<sql::Datetime>,
     pub title: String,
     pub firstname: String,
     pub middlename: Option<String>,
@@ -146,6 +162,7 @@ pub struct UsersForLogin {
     pub password_salt: sql::Uuid,
     pub token_salt: sql::Uuid,
     pub role: String,
+    pub totp_enabled_on: Option<sql::Datetime>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -158,6 +175,20 @@ pub struct UsersForAuth {
     pub token_salt: sql::Uuid,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct UsersForTotp {
+    pub id: sql::Thing,
+    pub username: String,
+    pub role: String,
+    pub token_salt: sql::Uuid,
+
+    // -- totp info
+    pub totp_secret: Option<String>,
+    pub totp_pending_secret: Option<String>,
+    pub totp_enabled_on: Option<sql::Datetime>,
+    pub totp_last_step: Option<i64>,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct UsersRecord {
     pub id: sql::Thing,
@@ -0,0 +1,112 @@
+//! Base32 (RFC 4648, no padding), as used by the otpauth URIs of authenticator apps.
+
+const ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+pub fn b32_encode(content: impl AsRef<[u8]>) -> String {
+    let content = content.as_ref();
+    let mut encoded = String::with_capacity(content.len().div_ceil(5) * 8);
+
+    let mut buffer: u16 = 0;
+    let mut bits = 0;
+    for byte in content {
+        buffer = (buffer << 8) | u16::from(*byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            encoded.push(ALPHABET[usize::from((buffer >> bits) & 0x1f)] as char);
+        }
+    }
+    if bits > 0 {
+        encoded.push(ALPHABET[usize::from((buffer << (5 - bits)) & 0x1f)] as char);
+    }
+
+    encoded
+}
+
+/// Decode a base32 string, ignoring the case, spaces and padding.
+pub fn b32_decode(b32: &str) -> Result<Vec<u8>> {
+    let mut decoded = Vec::with_capacity(b32.len() * 5 / 8);
+
+    let mut buffer: u16 = 0;
+    let mut bits = 0;
+    for c in b32.chars().filter(|c| !c.is_whitespace() && *c != '=') {
+        // -- Not truncated to a byte, e.g. `Ł` (U+0141) would decode as `A`.
+        if !c.is_ascii() {
+            return Err(Error::FailToB32Decode);
+        }
+        let c = c.to_ascii_uppercase() as u8;
+        let value = ALPHABET
+            .iter()
+            .position(|a| *a == c)
+            .ok_or(Error::FailToB32Decode)?;
+        buffer = (buffer << 5) | value as u16;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            decoded.push((buffer >> bits) as u8);
+        }
+    }
+
+    Ok(decoded)
+}
+
+// region:		--- Error
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug)]
+pub enum Error {
+    FailToB32Decode,
+}
+
+// region:		--- Error Boilerplate
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        write!(f, "{self:?}")
+    }
+}
+
+impl std::error::Error for Error {}
+// endregion:	--- Error Boilerplate
+
+// endregion:	--- Error
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_b32_encode_rfc4648_vectors() {
+        // -- Setup & Fixtures
+        let fx_vectors = [
+            ("", ""),
+            ("f", "MY"),
+            ("fo", "MZXQ"),
+            ("foo", "MZXW6"),
+            ("foob", "MZXW6YQ"),
+            ("fooba", "MZXW6YTB"),
+            ("foobar", "MZXW6YTBOI"),
+        ];
+
+        for (content, fx_encoded) in fx_vectors {
+            // -- Exec & Check
+            assert_eq!(b32_encode(content), fx_encoded);
+            assert_eq!(b32_decode(fx_encoded).unwrap(), content.as_bytes());
+        }
+    }
+
+    #[test]
+    fn test_b32_decode_lowercase_and_padding() {
+        // -- Exec & Check
+        assert_eq!(b32_decode("mzxw 6ytb oi======").unwrap(), b"foobar");
+        assert!(b32_decode("MZXW1").is_err());
+    }
+
+    #[test]
+    fn test_b32_decode_err_not_ascii() {
+        // -- Exec & Check
+        assert!(b32_decode("MZXW6YT\u{141}").is_err());
+        assert!(b32_decode("MZXW6YTB\u{e9}").is_err());
+    }
+}
+// endregion: --- Tests
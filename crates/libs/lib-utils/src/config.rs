@@ -0,0 +1,520 @@
+use std::{
+    collections::{HashMap, HashSet},
+    env, fmt, fs,
+    ops::Deref,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
+
+use crate::b64::b64u_decode;
+
+// region:    --- Config Sources
+/// The sources of the config values, by increasing priority:
+/// defaults, TOML file, environment, CLI flags.
+///
+/// The values are named as the env vars in every source, e.g., `SERVICE_WEB_PORT`.
+#[derive(Debug, Default)]
+pub struct ConfigSources {
+    defaults: HashMap<String, String>,
+    file: HashMap<String, String>,
+    env: bool,
+    args: HashMap<String, String>,
+}
+
+impl ConfigSources {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn defaults(mut self, defaults: &[(&str, &str)]) -> Self {
+        for (name, value) in defaults {
+            self.defaults.insert(name.to_string(), value.to_string());
+        }
+        self
+    }
+
+    /// Add the top-level keys of the TOML file, e.g., `SERVICE_WEB_PORT = 8080`.
+    pub fn toml_file(self, path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path).map_err(|ex| Error::FileCannotRead {
+            path: path.display().to_string(),
+            cause: ex.to_string(),
+        })?;
+
+        self.toml_str(&content).map_err(|ex| match ex {
+            Error::FileNotValid { cause, .. } => Error::FileNotValid {
+                path: path.display().to_string(),
+                cause,
+            },
+            ex => ex,
+        })
+    }
+
+    pub fn toml_str(mut self, content: &str) -> Result<Self> {
+        let file_not_valid = |cause: String| Error::FileNotValid {
+            path: String::new(),
+            cause,
+        };
+        let table: toml::Table = content
+            .parse()
+            .map_err(|ex: toml::de::Error| file_not_valid(ex.message().to_string()))?;
+
+        for (name, value) in table {
+            let value = match value {
+                toml::Value::String(value) => value,
+                toml::Value::Integer(_)
+                | toml::Value::Float(_)
+                | toml::Value::Boolean(_)
+                | toml::Value::Datetime(_) => value.to_string(),
+                toml::Value::Array(_) | toml::Value::Table(_) => {
+                    return Err(file_not_valid(format!("{name} is not a scalar")));
+                }
+            };
+            self.file.insert(name, value);
+        }
+
+        Ok(self)
+    }
+
+    pub fn env(mut self) -> Self {
+        self.env = true;
+        self
+    }
+
+    pub fn args(mut self, args: ConfigArgs) -> Self {
+        self.args.extend(args.values);
+        self
+    }
+
+    pub fn reader(&self) -> ConfigReader<'_> {
+        ConfigReader {
+            sources: self,
+            names_read: HashSet::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    fn get(&self, name: &str) -> Option<String> {
+        self.args
+            .get(name)
+            .cloned()
+            .or_else(|| self.env.then(|| env::var(name).ok()).flatten())
+            .or_else(|| self.file.get(name).cloned())
+            .or_else(|| self.defaults.get(name).cloned())
+    }
+}
+// endregion: --- Config Sources
+
+// region:    --- Config Args
+/// The config CLI flags, `--config <file>` and `--set <NAME>=<value>` (repeatable).
+#[derive(Debug, Default)]
+pub struct ConfigArgs {
+    pub file: Option<PathBuf>,
+    pub values: Vec<(String, String)>,
+}
+
+impl ConfigArgs {
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let mut config_args = ConfigArgs::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--config" => {
+                    let file = args.next().ok_or(Error::ArgNotValid(arg))?;
+                    config_args.file = Some(PathBuf::from(file));
+                }
+                "--set" => {
+                    let value = args.next().ok_or_else(|| Error::ArgNotValid(arg.clone()))?;
+                    let (name, value) = value
+                        .split_once('=')
+                        .ok_or_else(|| Error::ArgNotValid(value.clone()))?;
+                    config_args
+                        .values
+                        .push((name.to_string(), value.to_string()));
+                }
+                _ => return Err(Error::ArgNotValid(arg)),
+            }
+        }
+
+        Ok(config_args)
+    }
+}
+// endregion: --- Config Args
+
+// region:    --- Config Reader
+/// Read typed values from the sources, collecting every error until `finish`.
+///
+/// On error, a getter returns the default of its type, so the loading goes on
+/// and all the errors are reported at once.
+pub struct ConfigReader<'a> {
+    sources: &'a ConfigSources,
+    names_read: HashSet<&'static str>,
+    errors: Vec<Error>,
+}
+
+impl ConfigReader<'_> {
+    pub fn get(&mut self, name: &'static str) -> String {
+        self.get_required(name).unwrap_or_default()
+    }
+
+    /// `None` when missing or empty.
+    pub fn get_opt(&mut self, name: &'static str) -> Option<String> {
+        self.names_read.insert(name);
+        self.sources.get(name).filter(|value| !value.is_empty())
+    }
+
+    pub fn parse<T: FromStr + Default>(&mut self, name: &'static str) -> T {
+        self.parse_with(name, |value| value.parse().ok())
+    }
+
+    /// A duration like `90`, `15m` or `1h30m`, see `parse_duration`.
+    pub fn duration(&mut self, name: &'static str) -> Duration {
+        self.parse_with(name, parse_duration)
+    }
+
+    pub fn b64u(&mut self, name: &'static str) -> Vec<u8> {
+        self.parse_with(name, |value| b64u_decode(value).ok())
+    }
+
+    pub fn parse_with<T: Default>(
+        &mut self,
+        name: &'static str,
+        parse_fn: impl FnOnce(&str) -> Option<T>,
+    ) -> T {
+        let Some(value) = self.get_required(name) else {
+            return T::default();
+        };
+
+        parse_fn(&value).unwrap_or_else(|| {
+            self.errors.push(Error::WrongFormat(name));
+            T::default()
+        })
+    }
+
+    fn get_required(&mut self, name: &'static str) -> Option<String> {
+        self.names_read.insert(name);
+        let value = self.sources.get(name);
+        if value.is_none() {
+            self.errors.push(Error::MissingKey(name));
+        }
+
+        value
+    }
+
+    /// Report `name` as not valid, unless `valid`.
+    pub fn check(&mut self, valid: bool, name: &'static str, reason: &'static str) {
+        if !valid {
+            self.errors.push(Error::NotValid { name, reason });
+        }
+    }
+
+    /// Report `old_name` as renamed when still set, rather than ignoring its value.
+    pub fn renamed(&mut self, old_name: &'static str, new_name: &'static str) {
+        self.names_read.insert(old_name);
+        if self.sources.get(old_name).is_some() {
+            self.errors.push(Error::Renamed {
+                name: old_name,
+                new_name,
+            });
+        }
+    }
+
+    /// Return every error found, including the keys of the file and the flags never read
+    /// (most likely a typo).
+    pub fn finish(mut self) -> Result<()> {
+        let mut unknown_names: Vec<&String> = self
+            .sources
+            .file
+            .keys()
+            .chain(self.sources.args.keys())
+            .filter(|name| !self.names_read.contains(name.as_str()))
+            .collect();
+        unknown_names.sort();
+        unknown_names.dedup();
+        self.errors.extend(
+            unknown_names
+                .into_iter()
+                .map(|name| Error::UnknownKey(name.to_string())),
+        );
+
+        match self.errors.len() {
+            0 => Ok(()),
+            1 => Err(self.errors.remove(0)),
+            _ => Err(Error::Many(self.errors)),
+        }
+    }
+}
+// endregion: --- Config Reader
+
+// region:    --- Duration
+/// Parse a duration as a sequence of `<number><unit>`, the units being
+/// `ms`, `s`, `m`, `h` and `d` (e.g., `1h30m`). A bare number is in seconds.
+pub fn parse_duration(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(sec) = value.parse::<u64>() {
+        return Some(Duration::from_secs(sec));
+    }
+
+    let mut duration = Duration::ZERO;
+    let mut rest = value;
+    while !rest.is_empty() {
+        let number_len = rest.find(|c: char| !c.is_ascii_digit())?;
+        let unit_len = rest[number_len..]
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len() - number_len);
+        let (number, unit) = rest[..number_len + unit_len].split_at(number_len);
+        let number: u64 = number.parse().ok()?;
+
+        // -- An overflowing value is a wrong format, not a panic.
+        let part = match unit {
+            "ms" => Duration::from_millis(number),
+            "s" => Duration::from_secs(number),
+            "m" => Duration::from_secs(number.checked_mul(60)?),
+            "h" => Duration::from_secs(number.checked_mul(60 * 60)?),
+            "d" => Duration::from_secs(number.checked_mul(60 * 60 * 24)?),
+            _ => return None,
+        };
+        duration = duration.checked_add(part)?;
+        rest = &rest[number_len + unit_len..];
+    }
+
+    (!value.is_empty()).then_some(duration)
+}
+// endregion: --- Duration
+
+// region:    --- Secret
+/// A config value never shown by `Debug`, e.g., a key or a password.
+#[derive(Clone, Default)]
+pub struct Secret<T>(T);
+
+impl<T> Secret<T> {
+    pub fn new(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T> Deref for Secret<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> fmt::Debug for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[REDACTED]")
+    }
+}
+// endregion: --- Secret
+
+// region:    --- Error
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    MissingKey(&'static str),
+    WrongFormat(&'static str),
+    NotValid {
+        name: &'static str,
+        reason: &'static str,
+    },
+    UnknownKey(String),
+    /// A key set under its former name.
+    Renamed {
+        name: &'static str,
+        new_name: &'static str,
+    },
+    FileCannotRead {
+        path: String,
+        cause: String,
+    },
+    FileNotValid {
+        path: String,
+        cause: String,
+    },
+    ArgNotValid(String),
+    AlreadyInitialized(&'static str),
+    /// Every error found while loading.
+    Many(Vec<Error>),
+}
+
+// region:    --- Error Boilerplate
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Many(errors) => {
+                for error in errors {
+                    writeln!(f, "{error:?}")?;
+                }
+                Ok(())
+            }
+            _ => write!(f, "{self:?}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+// endregion: --- Error Boilerplate
+// endregion: --- Error
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration_ok() {
+        // -- Setup & Fixtures
+        let fx_durations = [
+            ("900", 900),
+            ("45s", 45),
+            ("15m", 900),
+            ("1h30m", 5400),
+            ("1d", 86400),
+        ];
+
+        for (value, fx_sec) in fx_durations {
+            // -- Exec & Check
+            assert_eq!(parse_duration(value), Some(Duration::from_secs(fx_sec)));
+        }
+        assert_eq!(parse_duration("250ms"), Some(Duration::from_millis(250)));
+    }
+
+    #[test]
+    fn test_parse_duration_err() {
+        for value in [
+            "",
+            "m",
+            "15x",
+            "1.5h",
+            "h15",
+            "15m5",
+            "999999999999999d",
+            "18446744073709551615s1s",
+        ] {
+            // -- Exec & Check
+            assert_eq!(parse_duration(value), None, "{value}");
+        }
+    }
+
+    #[test]
+    fn test_config_sources_priority() -> Result<()> {
+        // -- Setup & Fixtures
+        let args = ConfigArgs::parse(
+            ["--set", "FX_CONFIG_A=args"]
+                .into_iter()
+                .map(str::to_string),
+        )?;
+        let sources = ConfigSources::new()
+            .defaults(&[
+                ("FX_CONFIG_A", "default"),
+                ("FX_CONFIG_B", "default"),
+                ("FX_CONFIG_C", "default"),
+            ])
+            .toml_str("FX_CONFIG_A = 'file'\nFX_CONFIG_B = 8080")?
+            .args(args);
+
+        // -- Exec
+        let mut reader = sources.reader();
+        let a = reader.get("FX_CONFIG_A");
+        let b: u16 = reader.parse("FX_CONFIG_B");
+        let c = reader.get("FX_CONFIG_C");
+
+        // -- Check
+        assert_eq!(a, "args");
+        assert_eq!(b, 8080);
+        assert_eq!(c, "default");
+        reader.finish()?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_config_reader_all_errors() -> Result<()> {
+        // -- Setup & Fixtures
+        let sources = ConfigSources::new()
+            .defaults(&[("FX_CONFIG_PORT", "http"), ("FX_CONFIG_TIMEOUT", "10x")])
+            .toml_str("FX_CONFIG_TYPO = 1")?;
+
+        // -- Exec
+        let mut reader = sources.reader();
+        let _missing = reader.get("FX_CONFIG_MISSING");
+        let _port: u16 = reader.parse("FX_CONFIG_PORT");
+        let _timeout = reader.duration("FX_CONFIG_TIMEOUT");
+        reader.check(false, "FX_CONFIG_PORT", "not in range");
+
+        // -- Check
+        assert_eq!(
+            reader.finish(),
+            Err(Error::Many(vec![
+                Error::MissingKey("FX_CONFIG_MISSING"),
+                Error::WrongFormat("FX_CONFIG_PORT"),
+                Error::WrongFormat("FX_CONFIG_TIMEOUT"),
+                Error::NotValid {
+                    name: "FX_CONFIG_PORT",
+                    reason: "not in range"
+                },
+                Error::UnknownKey("FX_CONFIG_TYPO".to_string()),
+            ]))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_config_reader_duration_overflow() -> Result<()> {
+        // -- Setup & Fixtures
+        let sources = ConfigSources::new().defaults(&[("FX_CONFIG_TIMEOUT", "999999999999999d")]);
+
+        // -- Exec
+        let mut reader = sources.reader();
+        let _timeout = reader.duration("FX_CONFIG_TIMEOUT");
+
+        // -- Check
+        assert_eq!(
+            reader.finish(),
+            Err(Error::WrongFormat("FX_CONFIG_TIMEOUT"))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_config_reader_renamed() -> Result<()> {
+        // -- Setup & Fixtures
+        let sources = ConfigSources::new()
+            .defaults(&[("FX_CONFIG_TIMEOUT", "10s")])
+            .toml_str("FX_CONFIG_TIMEOUT_SEC = 30")?;
+
+        // -- Exec
+        let mut reader = sources.reader();
+        let timeout = reader.duration("FX_CONFIG_TIMEOUT");
+        reader.renamed("FX_CONFIG_TIMEOUT_SEC", "FX_CONFIG_TIMEOUT");
+        reader.renamed("FX_CONFIG_OTHER_SEC", "FX_CONFIG_OTHER");
+
+        // -- Check
+        assert_eq!(timeout, Duration::from_secs(10));
+        assert_eq!(
+            reader.finish(),
+            Err(Error::Renamed {
+                name: "FX_CONFIG_TIMEOUT_SEC",
+                new_name: "FX_CONFIG_TIMEOUT"
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_secret_debug_redacted() {
+        // -- Setup & Fixtures
+        let fx_secret = Secret::new("fx-password".to_string());
+
+        // -- Exec & Check
+        assert_eq!(format!("{fx_secret:?}"), "[REDACTED]");
+        assert_eq!(fx_secret.as_str(), "fx-password");
+    }
+}
+// endregion: --- Tests
@@ -1,3 +1,4 @@
-pub mod envs;
+pub mod b32;
 pub mod b64;
+pub mod config;
 pub mod time;
\ No newline at end of file
@@ -1,25 +1,395 @@
-use std::sync::OnceLock;
+use std::{path::PathBuf, str::FromStr, sync::OnceLock, time::Duration};
 
-use lib_utils::envs::get_env;
+use axum::http::{HeaderValue, Method};
+
+use lib_auth::{init_auth_config, pwd::init_pwd_policy, AuthConfig};
+use lib_surrealdb::{
+    config::{init_core_config, CoreConfig},
+    model::users::TITLES,
+};
+use lib_utils::config::{ConfigArgs, ConfigReader, ConfigSources, Error, Result, Secret};
+
+static INSTANCE: OnceLock<WebConfig> = OnceLock::new();
+
+/// Set the config loaded at startup, before any call to `web_config`.
+pub fn init_web_config(config: WebConfig) -> Result<()> {
+    INSTANCE
+        .set(config)
+        .map_err(|_| Error::AlreadyInitialized("web"))
+}
 
 pub fn web_config() -> &'static WebConfig {
-    static INSTANCE: OnceLock<WebConfig> = OnceLock::new();
+    INSTANCE
+        .get()
+        .expect("FATAL - WHILE LOADING CONF - Cause: web config not initialized")
+}
+
+// region:    --- App Config
+/// The whole config of the service, loaded and validated once at startup.
+#[derive(Debug)]
+pub struct AppConfig {
+    pub core: CoreConfig,
+    pub auth: AuthConfig,
+    pub web: WebConfig,
+}
+
+impl AppConfig {
+    /// Load the config from, by increasing priority, the defaults, the TOML file
+    /// (`--config <file>` or `SERVICE_CONFIG_FILE`), the env and the `--set` flags.
+    ///
+    /// All the errors are returned at once.
+    pub fn load(args: impl IntoIterator<Item = String>) -> Result<AppConfig> {
+        let args = ConfigArgs::parse(args)?;
+        let file = args
+            .file
+            .clone()
+            .or_else(|| std::env::var_os("SERVICE_CONFIG_FILE").map(PathBuf::from))
+            .filter(|file| !file.as_os_str().is_empty());
+
+        let mut sources = ConfigSources::new()
+            .defaults(CoreConfig::DEFAULTS)
+            .defaults(AuthConfig::DEFAULTS)
+            .defaults(WebConfig::DEFAULTS);
+        if let Some(file) = file {
+            sources = sources.toml_file(&file)?;
+        }
+        let sources = sources.env().args(args);
 
-    INSTANCE.get_or_init(|| {
-        WebConfig::load_from_env()
-            .unwrap_or_else(|ex| panic!("FATAL - WHILE LOADING CONF - Cause: {ex:?}"))
-    })
+        let mut reader = sources.reader();
+        let config = AppConfig {
+            core: CoreConfig::load(&mut reader),
+            auth: AuthConfig::load(&mut reader),
+            web: WebConfig::load(&mut reader),
+        };
+        reader.finish()?;
+
+        Ok(config)
+    }
+
+    /// Make the config available to `core_config`, `auth_config` and `web_config`,
+    /// and load the password policy from it.
+    pub fn init(self) -> Result<()> {
+        init_core_config(self.core)?;
+        init_auth_config(self.auth)?;
+        init_pwd_policy()?;
+        init_web_config(self.web)?;
+
+        Ok(())
+    }
 }
+// endregion: --- App Config
 
+// region:    --- Web Config
 #[allow(non_snake_case)]
+#[derive(Debug)]
 pub struct WebConfig {
     pub WEB_FOLDER: String,
+    pub WEB_URL: String,
+
+    // -- Listener
+    pub WEB_HOST: String,
+    pub WEB_PORT: u16,
+    pub WEB_SHUTDOWN_TIMEOUT: Duration,
+
+    // -- Http
+    pub CORS_ALLOWED_ORIGINS: Vec<HeaderValue>,
+    pub CORS_ALLOWED_METHODS: Vec<Method>,
+    pub COMPRESSION_ENCODINGS: Vec<String>,
+    pub REQUEST_BODY_LIMIT: usize,
+    pub REQUEST_TIMEOUT: Duration,
+
+    // -- Login
+    pub LOGIN_REQUIRE_EMAIL_VERIFIED: bool,
+    pub LOGIN_ATTEMPT_WINDOW: Duration,
+    pub LOGIN_LOCKOUT_USERNAME_THRESHOLD: i64,
+    pub LOGIN_LOCKOUT_IP_THRESHOLD: i64,
+    pub LOGIN_LOCKOUT_BASE: Duration,
+    pub LOGIN_LOCKOUT_MAX: Duration,
+
+    // -- Rate Limit
+    pub RATE_LIMIT_AUTH_BURST: u32,
+    pub RATE_LIMIT_AUTH_REFILL: Duration,
+    pub RATE_LIMIT_API_BURST: u32,
+    pub RATE_LIMIT_API_REFILL: Duration,
+
+    // -- Request Log
+    pub REQUEST_LOG_SINK: String,
+    pub REQUEST_LOG_BUFFER_SIZE: usize,
+    pub REQUEST_LOG_BATCH_SIZE: usize,
+    pub REQUEST_LOG_FLUSH_INTERVAL: Duration,
+    pub REQUEST_LOG_FILE_FOLDER: String,
+    pub REQUEST_LOG_FILE_MAX_SIZE: u64,
+    pub REQUEST_LOG_FILE_MAX_FILES: usize,
+    pub REQUEST_LOG_HTTP_URL: String,
+    pub REQUEST_LOG_HTTP_TIMEOUT: Duration,
+
+    // -- Telemetry
+    pub OTEL_ENDPOINT: Option<String>,
+    pub OTEL_SERVICE_NAME: String,
+    /// `GET /metrics` is only served with this bearer token, and not at all when unset.
+    pub METRICS_TOKEN: Option<Secret<String>>,
+
+    // -- Mail
+    pub MAIL_SENDER: String,
+    pub MAIL_OUTBOX_FOLDER: String,
+
+    // -- Oidc
+    pub OIDC_ISSUER_URL: Option<String>,
+    pub OIDC_CLIENT_ID: String,
+    pub OIDC_CLIENT_SECRET: Option<Secret<String>>,
+    pub OIDC_REDIRECT_URL: String,
+    pub OIDC_SCOPES: String,
+    pub OIDC_LOGIN_DURATION: Duration,
+    pub OIDC_JWKS_CACHE: Duration,
+    pub OIDC_PROVISION_USERS: bool,
+    pub OIDC_PROVISION_TITLE: String,
 }
 
 impl WebConfig {
-    fn load_from_env() -> lib_utils::envs::Result<WebConfig> {
-        Ok(WebConfig {
-            WEB_FOLDER: get_env("SERVICE_WEB_FOLDER")?,
-        })
+    pub const DEFAULTS: &'static [(&'static str, &'static str)] = &[
+        ("SERVICE_WEB_HOST", "127.0.0.1"),
+        ("SERVICE_WEB_PORT", "8080"),
+        ("SERVICE_WEB_SHUTDOWN_TIMEOUT", "30s"),
+        ("SERVICE_CORS_ALLOWED_ORIGINS", ""),
+        ("SERVICE_CORS_ALLOWED_METHODS", "GET,POST,PUT,PATCH,DELETE"),
+        ("SERVICE_COMPRESSION_ENCODINGS", "gzip,br,zstd"),
+        ("SERVICE_REQUEST_BODY_LIMIT", "2097152"),
+        ("SERVICE_REQUEST_TIMEOUT", "30s"),
+        ("SERVICE_LOGIN_REQUIRE_EMAIL_VERIFIED", "false"),
+        ("SERVICE_LOGIN_ATTEMPT_WINDOW", "15m"),
+        ("SERVICE_LOGIN_LOCKOUT_USERNAME_THRESHOLD", "5"),
+        ("SERVICE_LOGIN_LOCKOUT_IP_THRESHOLD", "20"),
+        ("SERVICE_LOGIN_LOCKOUT_BASE", "30s"),
+        ("SERVICE_LOGIN_LOCKOUT_MAX", "1h"),
+        ("SERVICE_RATE_LIMIT_AUTH_BURST", "10"),
+        ("SERVICE_RATE_LIMIT_AUTH_REFILL", "6s"),
+        ("SERVICE_RATE_LIMIT_API_BURST", "100"),
+        ("SERVICE_RATE_LIMIT_API_REFILL", "100ms"),
+        ("SERVICE_REQUEST_LOG_SINK", "stdout"),
+        ("SERVICE_REQUEST_LOG_BUFFER_SIZE", "10000"),
+        ("SERVICE_REQUEST_LOG_BATCH_SIZE", "100"),
+        ("SERVICE_REQUEST_LOG_FLUSH_INTERVAL", "1s"),
+        ("SERVICE_REQUEST_LOG_FILE_FOLDER", "request-log/"),
+        ("SERVICE_REQUEST_LOG_FILE_MAX_SIZE", "10485760"),
+        ("SERVICE_REQUEST_LOG_FILE_MAX_FILES", "5"),
+        ("SERVICE_REQUEST_LOG_HTTP_URL", ""),
+        ("SERVICE_REQUEST_LOG_HTTP_TIMEOUT", "5s"),
+        ("SERVICE_OTEL_ENDPOINT", ""),
+        ("SERVICE_OTEL_SERVICE_NAME", "web-server"),
+        ("SERVICE_METRICS_TOKEN", ""),
+        ("SERVICE_MAIL_SENDER", "log"),
+        ("SERVICE_MAIL_OUTBOX_FOLDER", "mail-outbox/"),
+        ("SERVICE_OIDC_ISSUER_URL", ""),
+        ("SERVICE_OIDC_CLIENT_ID", ""),
+        ("SERVICE_OIDC_CLIENT_SECRET", ""),
+        ("SERVICE_OIDC_REDIRECT_URL", ""),
+        ("SERVICE_OIDC_SCOPES", "openid email profile"),
+        ("SERVICE_OIDC_LOGIN_DURATION", "10m"),
+        ("SERVICE_OIDC_JWKS_CACHE", "1h"),
+        ("SERVICE_OIDC_PROVISION_USERS", "false"),
+        ("SERVICE_OIDC_PROVISION_TITLE", ""),
+    ];
+
+    /// Read the config, the errors being collected by `reader`.
+    pub fn load(reader: &mut ConfigReader) -> WebConfig {
+        let config = WebConfig {
+            WEB_FOLDER: reader.get("SERVICE_WEB_FOLDER"),
+            WEB_URL: reader.get("SERVICE_WEB_URL"),
+
+            // -- Listener
+            WEB_HOST: reader.get("SERVICE_WEB_HOST"),
+            WEB_PORT: reader.parse("SERVICE_WEB_PORT"),
+            WEB_SHUTDOWN_TIMEOUT: reader.duration("SERVICE_WEB_SHUTDOWN_TIMEOUT"),
+
+            // -- Http
+            CORS_ALLOWED_ORIGINS: reader.parse_with("SERVICE_CORS_ALLOWED_ORIGINS", |value| {
+                parse_list(value)
+                    .map(HeaderValue::from_str)
+                    .collect::<Result<_, _>>()
+                    .ok()
+            }),
+            CORS_ALLOWED_METHODS: reader.parse_with("SERVICE_CORS_ALLOWED_METHODS", |value| {
+                parse_list(value)
+                    .map(Method::from_str)
+                    .collect::<Result<_, _>>()
+                    .ok()
+            }),
+            COMPRESSION_ENCODINGS: reader.parse_with("SERVICE_COMPRESSION_ENCODINGS", |value| {
+                Some(parse_list(value).map(str::to_string).collect())
+            }),
+            REQUEST_BODY_LIMIT: reader.parse("SERVICE_REQUEST_BODY_LIMIT"),
+            REQUEST_TIMEOUT: reader.duration("SERVICE_REQUEST_TIMEOUT"),
+
+            // -- Login
+            LOGIN_REQUIRE_EMAIL_VERIFIED: reader.parse("SERVICE_LOGIN_REQUIRE_EMAIL_VERIFIED"),
+            LOGIN_ATTEMPT_WINDOW: reader.duration("SERVICE_LOGIN_ATTEMPT_WINDOW"),
+            LOGIN_LOCKOUT_USERNAME_THRESHOLD: reader
+                .parse("SERVICE_LOGIN_LOCKOUT_USERNAME_THRESHOLD"),
+            LOGIN_LOCKOUT_IP_THRESHOLD: reader.parse("SERVICE_LOGIN_LOCKOUT_IP_THRESHOLD"),
+            LOGIN_LOCKOUT_BASE: reader.duration("SERVICE_LOGIN_LOCKOUT_BASE"),
+            LOGIN_LOCKOUT_MAX: reader.duration("SERVICE_LOGIN_LOCKOUT_MAX"),
+
+            // -- Rate Limit
+            RATE_LIMIT_AUTH_BURST: reader.parse("SERVICE_RATE_LIMIT_AUTH_BURST"),
+            RATE_LIMIT_AUTH_REFILL: reader.duration("SERVICE_RATE_LIMIT_AUTH_REFILL"),
+            RATE_LIMIT_API_BURST: reader.parse("SERVICE_RATE_LIMIT_API_BURST"),
+            RATE_LIMIT_API_REFILL: reader.duration("SERVICE_RATE_LIMIT_API_REFILL"),
+
+            // -- Request Log
+            REQUEST_LOG_SINK: reader.get("SERVICE_REQUEST_LOG_SINK"),
+            REQUEST_LOG_BUFFER_SIZE: reader.parse("SERVICE_REQUEST_LOG_BUFFER_SIZE"),
+            REQUEST_LOG_BATCH_SIZE: reader.parse("SERVICE_REQUEST_LOG_BATCH_SIZE"),
+            REQUEST_LOG_FLUSH_INTERVAL: reader.duration("SERVICE_REQUEST_LOG_FLUSH_INTERVAL"),
+            REQUEST_LOG_FILE_FOLDER: reader.get("SERVICE_REQUEST_LOG_FILE_FOLDER"),
+            REQUEST_LOG_FILE_MAX_SIZE: reader.parse("SERVICE_REQUEST_LOG_FILE_MAX_SIZE"),
+            REQUEST_LOG_FILE_MAX_FILES: reader.parse("SERVICE_REQUEST_LOG_FILE_MAX_FILES"),
+            REQUEST_LOG_HTTP_URL: reader.get("SERVICE_REQUEST_LOG_HTTP_URL"),
+            REQUEST_LOG_HTTP_TIMEOUT: reader.duration("SERVICE_REQUEST_LOG_HTTP_TIMEOUT"),
+
+            // -- Telemetry
+            OTEL_ENDPOINT: reader.get_opt("SERVICE_OTEL_ENDPOINT"),
+            OTEL_SERVICE_NAME: reader.get("SERVICE_OTEL_SERVICE_NAME"),
+            METRICS_TOKEN: reader.get_opt("SERVICE_METRICS_TOKEN").map(Secret::new),
+
+            // -- Mail
+            MAIL_SENDER: reader.get("SERVICE_MAIL_SENDER"),
+            MAIL_OUTBOX_FOLDER: reader.get("SERVICE_MAIL_OUTBOX_FOLDER"),
+
+            // -- Oidc
+            OIDC_ISSUER_URL: reader.get_opt("SERVICE_OIDC_ISSUER_URL"),
+            OIDC_CLIENT_ID: reader.get("SERVICE_OIDC_CLIENT_ID"),
+            OIDC_CLIENT_SECRET: reader
+                .get_opt("SERVICE_OIDC_CLIENT_SECRET")
+                .map(Secret::new),
+            OIDC_REDIRECT_URL: reader.get("SERVICE_OIDC_REDIRECT_URL"),
+            OIDC_SCOPES: reader.get("SERVICE_OIDC_SCOPES"),
+            OIDC_LOGIN_DURATION: reader.duration("SERVICE_OIDC_LOGIN_DURATION"),
+            OIDC_JWKS_CACHE: reader.duration("SERVICE_OIDC_JWKS_CACHE"),
+            OIDC_PROVISION_USERS: reader.parse("SERVICE_OIDC_PROVISION_USERS"),
+            OIDC_PROVISION_TITLE: reader.get("SERVICE_OIDC_PROVISION_TITLE"),
+        };
+
+        // -- Validate
+        reader.check(
+            config
+                .COMPRESSION_ENCODINGS
+                .iter()
+                .all(|encoding| matches!(encoding.as_str(), "gzip" | "br" | "zstd")),
+            "SERVICE_COMPRESSION_ENCODINGS",
+            "must be a list of gzip, br or zstd",
+        );
+        reader.check(
+            config.REQUEST_BODY_LIMIT > 0,
+            "SERVICE_REQUEST_BODY_LIMIT",
+            "must not be zero",
+        );
+        reader.check(
+            !config.REQUEST_TIMEOUT.is_zero(),
+            "SERVICE_REQUEST_TIMEOUT",
+            "must not be zero",
+        );
+        reader.check(
+            matches!(config.REQUEST_LOG_SINK.as_str(), "stdout" | "file" | "http"),
+            "SERVICE_REQUEST_LOG_SINK",
+            "must be stdout, file or http",
+        );
+        reader.check(
+            config.REQUEST_LOG_BUFFER_SIZE > 0,
+            "SERVICE_REQUEST_LOG_BUFFER_SIZE",
+            "must not be zero",
+        );
+        reader.check(
+            config.REQUEST_LOG_BATCH_SIZE > 0,
+            "SERVICE_REQUEST_LOG_BATCH_SIZE",
+            "must not be zero",
+        );
+        if config.REQUEST_LOG_SINK == "http" {
+            reader.check(
+                !config.REQUEST_LOG_HTTP_URL.is_empty(),
+                "SERVICE_REQUEST_LOG_HTTP_URL",
+                "required when SERVICE_REQUEST_LOG_SINK is http",
+            );
+        }
+        reader.check(
+            matches!(config.MAIL_SENDER.as_str(), "log" | "file"),
+            "SERVICE_MAIL_SENDER",
+            "must be log or file",
+        );
+        reader.check(
+            config.LOGIN_LOCKOUT_BASE <= config.LOGIN_LOCKOUT_MAX,
+            "SERVICE_LOGIN_LOCKOUT_BASE",
+            "greater than SERVICE_LOGIN_LOCKOUT_MAX",
+        );
+        for (burst, burst_name, refill, refill_name) in [
+            (
+                config.RATE_LIMIT_AUTH_BURST,
+                "SERVICE_RATE_LIMIT_AUTH_BURST",
+                config.RATE_LIMIT_AUTH_REFILL,
+                "SERVICE_RATE_LIMIT_AUTH_REFILL",
+            ),
+            (
+                config.RATE_LIMIT_API_BURST,
+                "SERVICE_RATE_LIMIT_API_BURST",
+                config.RATE_LIMIT_API_REFILL,
+                "SERVICE_RATE_LIMIT_API_REFILL",
+            ),
+        ] {
+            reader.check(burst > 0, burst_name, "must not be zero");
+            reader.check(!refill.is_zero(), refill_name, "must not be zero");
+        }
+        if config.OIDC_ISSUER_URL.is_some() {
+            reader.check(
+                !config.OIDC_CLIENT_ID.is_empty(),
+                "SERVICE_OIDC_CLIENT_ID",
+                "required when SERVICE_OIDC_ISSUER_URL is set",
+            );
+            reader.check(
+                !config.OIDC_REDIRECT_URL.is_empty(),
+                "SERVICE_OIDC_REDIRECT_URL",
+                "required when SERVICE_OIDC_ISSUER_URL is set",
+            );
+        }
+        if config.OIDC_PROVISION_USERS {
+            reader.check(
+                TITLES.contains(&config.OIDC_PROVISION_TITLE.as_str()),
+                "SERVICE_OIDC_PROVISION_TITLE",
+                "must be one of the user titles when SERVICE_OIDC_PROVISION_USERS is set",
+            );
+        }
+
+        // -- Former names (the durations were in seconds)
+        for (old_name, new_name) in [
+            (
+                "SERVICE_WEB_SHUTDOWN_TIMEOUT_SEC",
+                "SERVICE_WEB_SHUTDOWN_TIMEOUT",
+            ),
+            (
+                "SERVICE_LOGIN_ATTEMPT_WINDOW_SEC",
+                "SERVICE_LOGIN_ATTEMPT_WINDOW",
+            ),
+            (
+                "SERVICE_LOGIN_LOCKOUT_BASE_SEC",
+                "SERVICE_LOGIN_LOCKOUT_BASE",
+            ),
+            ("SERVICE_LOGIN_LOCKOUT_MAX_SEC", "SERVICE_LOGIN_LOCKOUT_MAX"),
+            (
+                "SERVICE_OIDC_LOGIN_DURATION_SEC",
+                "SERVICE_OIDC_LOGIN_DURATION",
+            ),
+            ("SERVICE_OIDC_JWKS_CACHE_SEC", "SERVICE_OIDC_JWKS_CACHE"),
+        ] {
+            reader.renamed(old_name, new_name);
+        }
+
+        config
     }
 }
+
+/// The items of a comma separated list, e.g., `GET, POST`.
+fn parse_list(value: &str) -> impl Iterator<Item = &str> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+}
+// endregion: --- Web Config
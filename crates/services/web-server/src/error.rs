@@ -7,7 +7,19 @@ pub type Result<T> = std::result::Result<T, Error>;
 pub enum Error {
     // -- Modules
     #[from]
+    Config(lib_utils::config::Error),
+    #[from]
+    Log(crate::log::Error),
+    #[from]
     Model(model::Error),
+
+    // -- Externals
+    #[from]
+    Io(std::io::Error),
+    #[from]
+    Trace(opentelemetry::trace::TraceError),
+    #[from]
+    Metrics(metrics_exporter_prometheus::BuildError),
 }
 
 // region:    --- Error Boilerplate
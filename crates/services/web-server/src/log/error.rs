@@ -0,0 +1,31 @@
+use derive_more::From;
+use serde::Serialize;
+use serde_with::{serde_as, DisplayFromStr};
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[serde_as]
+#[derive(Debug, Serialize, From)]
+pub enum Error {
+    LogSinkNotFound(String),
+    LogWriterAlreadyStarted,
+    HttpExportFail(u16),
+
+    // -- Externals
+    #[from]
+    Io(#[serde_as(as = "DisplayFromStr")] std::io::Error),
+    #[from]
+    Reqwest(#[serde_as(as = "DisplayFromStr")] reqwest::Error),
+    #[from]
+    SerdeJson(#[serde_as(as = "DisplayFromStr")] serde_json::Error),
+}
+
+// region:    --- Error Boilerplate
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+impl std::error::Error for Error {}
+// endregion: --- Error Boilerplate
@@ -0,0 +1,124 @@
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use tokio::{
+    fs::{self, OpenOptions},
+    io::AsyncWriteExt,
+};
+
+use super::{LogSink, Result};
+
+const FILE_NAME: &str = "request-log";
+const FILE_EXT: &str = "jsonl";
+
+/// Append the log lines as JSON lines to `request-log.jsonl` in the log folder.
+///
+/// Once the file would exceed `max_size` bytes, it is rotated to `request-log.1.jsonl`
+/// (the previous ones being shifted), and only `max_files` rotated files are kept.
+pub struct FileLogSink {
+    folder: PathBuf,
+    max_size: u64,
+    max_files: usize,
+}
+
+impl FileLogSink {
+    pub fn new(folder: &str, max_size: u64, max_files: usize) -> Self {
+        FileLogSink {
+            folder: PathBuf::from(folder),
+            max_size,
+            max_files,
+        }
+    }
+
+    fn file_path(&self, index: usize) -> PathBuf {
+        match index {
+            0 => self.folder.join(format!("{FILE_NAME}.{FILE_EXT}")),
+            _ => self.folder.join(format!("{FILE_NAME}.{index}.{FILE_EXT}")),
+        }
+    }
+
+    /// Drop the oldest file (the current one when `max_files` is 0), then shift
+    /// `.{n}` to `.{n + 1}`, the current file becoming `.1`.
+    async fn rotate(&self) -> Result<()> {
+        let oldest_path = self.file_path(self.max_files);
+        if fs::try_exists(&oldest_path).await? {
+            fs::remove_file(&oldest_path).await?;
+        }
+
+        for index in (0..self.max_files).rev() {
+            let path = self.file_path(index);
+            if fs::try_exists(&path).await? {
+                fs::rename(&path, self.file_path(index + 1)).await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl LogSink for FileLogSink {
+    async fn write(&mut self, lines: &[String]) -> Result<()> {
+        let content: String = lines.iter().map(|line| format!("{line}\n")).collect();
+
+        fs::create_dir_all(&self.folder).await?;
+
+        let path = self.file_path(0);
+        let size = match fs::metadata(&path).await {
+            Ok(metadata) => metadata.len(),
+            Err(_) => 0,
+        };
+        if size > 0 && size + content.len() as u64 > self.max_size {
+            self.rotate().await?;
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .await?;
+        file.write_all(content.as_bytes()).await?;
+        file.flush().await?;
+
+        Ok(())
+    }
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    type Error = Box<dyn std::error::Error>;
+    type Result<T> = core::result::Result<T, Error>; // For tests.
+
+    use std::env;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_file_log_sink_rotate() -> Result<()> {
+        // -- Setup & Fixtures
+        //    (each line being 11 bytes, every write rotates)
+        let fx_folder = env::temp_dir().join(format!("request-log-{}", uuid::Uuid::new_v4()));
+        let mut sink = FileLogSink::new(fx_folder.to_str().ok_or("Should be utf-8")?, 20, 2);
+
+        // -- Exec
+        for index in 1..=4 {
+            sink.write(&[format!("fx-line-{index:02}")]).await?;
+        }
+
+        // -- Check
+        let content = |index| std::fs::read_to_string(sink.file_path(index));
+        assert_eq!(content(0)?, "fx-line-04\n");
+        assert_eq!(content(1)?, "fx-line-03\n");
+        assert_eq!(content(2)?, "fx-line-02\n");
+        assert!(
+            !sink.file_path(3).exists(),
+            "should keep only 2 rotated files"
+        );
+
+        std::fs::remove_dir_all(&fx_folder)?;
+
+        Ok(())
+    }
+}
+// endregion: --- Tests
@@ -0,0 +1,130 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use reqwest::{header::CONTENT_TYPE, Client};
+
+use super::{Error, LogSink, Result};
+
+/// Post every batch of log lines as one JSON array to the log collector url.
+pub struct HttpLogSink {
+    client: Client,
+    url: String,
+}
+
+impl HttpLogSink {
+    pub fn new(url: &str, timeout: Duration) -> Result<Self> {
+        Ok(HttpLogSink {
+            client: Client::builder().timeout(timeout).build()?,
+            url: url.to_string(),
+        })
+    }
+}
+
+#[async_trait]
+impl LogSink for HttpLogSink {
+    async fn write(&mut self, lines: &[String]) -> Result<()> {
+        // -- The lines are already JSON, so the array is joined as is.
+        let body = format!("[{}]", lines.join(","));
+
+        let response = self
+            .client
+            .post(&self.url)
+            .header(CONTENT_TYPE, "application/json")
+            .body(body)
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(Error::HttpExportFail(status.as_u16()));
+        }
+
+        Ok(())
+    }
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    type Error = Box<dyn std::error::Error>;
+    type Result<T> = core::result::Result<T, Error>; // For tests.
+
+    use std::sync::{Arc, Mutex};
+
+    use axum::{http::StatusCode, routing::post, Router};
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    /// A local log collector answering `status`, returning its url and the received bodies.
+    async fn fx_collector(status: StatusCode) -> Result<(String, Arc<Mutex<Vec<String>>>)> {
+        let bodies = Arc::new(Mutex::new(Vec::new()));
+        let received = bodies.clone();
+        let router = Router::new().route(
+            "/logs",
+            post(move |body: String| {
+                let received = received.clone();
+                async move {
+                    received.lock().unwrap().push(body);
+                    status
+                }
+            }),
+        );
+
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}/logs", listener.local_addr()?);
+        tokio::spawn(async move { axum::serve(listener, router).await });
+
+        Ok((url, bodies))
+    }
+
+    #[tokio::test]
+    async fn test_http_log_sink_batch_body() -> Result<()> {
+        // -- Setup & Fixtures
+        let (url, bodies) = fx_collector(StatusCode::OK).await?;
+        let mut sink = HttpLogSink::new(&url, Duration::from_secs(5))?;
+        let fx_lines = [
+            r#"{"uuid":"fx-1"}"#.to_string(),
+            r#"{"uuid":"fx-2"}"#.to_string(),
+        ];
+
+        // -- Exec
+        sink.write(&fx_lines).await?;
+
+        // -- Check
+        let bodies = bodies.lock().unwrap();
+        assert_eq!(*bodies, [r#"[{"uuid":"fx-1"},{"uuid":"fx-2"}]"#]);
+        let batch: Vec<serde_json::Value> = serde_json::from_str(&bodies[0])?;
+        assert_eq!(batch.len(), 2);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_http_log_sink_err() -> Result<()> {
+        // -- Setup & Fixtures
+        let (url, _bodies) = fx_collector(StatusCode::SERVICE_UNAVAILABLE).await?;
+        let mut failing_sink = HttpLogSink::new(&url, Duration::from_secs(5))?;
+        // -- A port with no listener anymore.
+        let closed_addr = TcpListener::bind("127.0.0.1:0").await?.local_addr()?;
+        let mut down_sink = HttpLogSink::new(
+            &format!("http://{closed_addr}/logs"),
+            Duration::from_secs(5),
+        )?;
+        let fx_lines = [r#"{"uuid":"fx-1"}"#.to_string()];
+
+        // -- Exec
+        let failing_res = failing_sink.write(&fx_lines).await;
+        let down_res = down_sink.write(&fx_lines).await;
+
+        // -- Check
+        assert!(matches!(
+            failing_res,
+            Err(crate::log::Error::HttpExportFail(503))
+        ));
+        assert!(matches!(down_res, Err(crate::log::Error::Reqwest(_))));
+
+        Ok(())
+    }
+}
+// endregion: --- Tests
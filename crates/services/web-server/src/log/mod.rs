@@ -1,17 +1,179 @@
+mod error;
+mod file_sink;
+mod http_sink;
+mod stdout_sink;
+
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex, OnceLock,
+    },
+    time::Duration,
+};
+
+use async_trait::async_trait;
 use axum::http::{Method, Uri};
 use lib_surrealdb::ctx::Ctx;
 
 use lib_utils::time::{format_time, now_utc};
 use serde::Serialize;
-use serde_json::{json, Value};
+use serde_json::Value;
 use serde_with::skip_serializing_none;
-use tracing::debug;
+use tokio::{
+    sync::{mpsc, oneshot},
+    task::JoinHandle,
+    time::{timeout_at, Instant},
+};
+use tracing::{debug, warn};
 
+use crate::config::web_config;
 use crate::middlewares::{self, ReqStamp};
 use crate::routes::{self, ClientError};
 // use crate::routes::{self, routes_rpc::RpcInfo};
-use crate::Result;
+use crate::telemetry;
+
+pub use self::error::{Error, Result};
+pub use self::file_sink::FileLogSink;
+pub use self::http_sink::HttpLogSink;
+pub use self::stdout_sink::StdoutLogSink;
+
+// region:    --- Log Sink
+/// The destination of the request log lines, each line being a JSON object.
+#[async_trait]
+pub trait LogSink: Send + Sync {
+    async fn write(&mut self, lines: &[String]) -> Result<()>;
+}
+
+fn new_log_sink(sink_name: &str) -> Result<Box<dyn LogSink>> {
+    let config = web_config();
+    match sink_name {
+        "stdout" => Ok(Box::new(StdoutLogSink)),
+        "file" => Ok(Box::new(FileLogSink::new(
+            &config.REQUEST_LOG_FILE_FOLDER,
+            config.REQUEST_LOG_FILE_MAX_SIZE,
+            config.REQUEST_LOG_FILE_MAX_FILES,
+        ))),
+        "http" => Ok(Box::new(HttpLogSink::new(
+            &config.REQUEST_LOG_HTTP_URL,
+            config.REQUEST_LOG_HTTP_TIMEOUT,
+        )?)),
+        _ => Err(Error::LogSinkNotFound(sink_name.to_string())),
+    }
+}
+// endregion: --- Log Sink
+
+// region:    --- Log Writer
+/// The buffer of the log lines, written to the sink by a background task,
+/// so a slow or failing sink never delays nor fails a request.
+struct LogWriter {
+    line_tx: mpsc::Sender<String>,
+    close_tx: Mutex<Option<oneshot::Sender<()>>>,
+    handle: Mutex<Option<JoinHandle<()>>>,
+}
+
+static LOG_WRITER: OnceLock<LogWriter> = OnceLock::new();
+
+/// The lines dropped because the buffer was full, reported by the background task.
+static DROPPED_COUNT: AtomicU64 = AtomicU64::new(0);
+
+/// Start the background task writing the request log lines to the configured sink.
+pub fn start_log_writer() -> Result<()> {
+    let config = web_config();
+    let sink = new_log_sink(&config.REQUEST_LOG_SINK)?;
+
+    let (line_tx, line_rx) = mpsc::channel(config.REQUEST_LOG_BUFFER_SIZE);
+    let (close_tx, close_rx) = oneshot::channel();
+    let handle = tokio::spawn(run_log_writer(
+        line_rx,
+        close_rx,
+        sink,
+        config.REQUEST_LOG_BATCH_SIZE,
+        config.REQUEST_LOG_FLUSH_INTERVAL,
+    ));
+
+    LOG_WRITER
+        .set(LogWriter {
+            line_tx,
+            close_tx: Mutex::new(Some(close_tx)),
+            handle: Mutex::new(Some(handle)),
+        })
+        .map_err(|_| Error::LogWriterAlreadyStarted)
+}
+
+/// Write the buffered lines, and stop the background task.
+pub async fn close_log_writer() {
+    let Some(log_writer) = LOG_WRITER.get() else {
+        return;
+    };
+
+    if let Some(close_tx) = log_writer.close_tx.lock().ok().and_then(|mut tx| tx.take()) {
+        let _ = close_tx.send(());
+    }
+    let handle = log_writer
+        .handle
+        .lock()
+        .ok()
+        .and_then(|mut handle| handle.take());
+    if let Some(handle) = handle {
+        let _ = handle.await;
+    }
+}
+
+/// Write the lines by batch, once `batch_size` lines are buffered or
+/// `flush_interval` after the first line of the batch.
+async fn run_log_writer(
+    mut line_rx: mpsc::Receiver<String>,
+    mut close_rx: oneshot::Receiver<()>,
+    mut sink: Box<dyn LogSink>,
+    batch_size: usize,
+    flush_interval: Duration,
+) {
+    let mut lines = Vec::with_capacity(batch_size);
+    let mut closing = false;
+
+    loop {
+        // -- Wait for the first line of the batch.
+        //    (once closing, the buffered lines are still received, then `0` ends the loop)
+        tokio::select! {
+            count = line_rx.recv_many(&mut lines, batch_size) => {
+                if count == 0 {
+                    break;
+                }
+            }
+            _ = &mut close_rx, if !closing => {
+                closing = true;
+                line_rx.close();
+                continue;
+            }
+        }
 
+        // -- Fill the batch until full or the flush deadline.
+        let deadline = Instant::now() + flush_interval;
+        while !closing && lines.len() < batch_size {
+            let limit = batch_size - lines.len();
+            match timeout_at(deadline, line_rx.recv_many(&mut lines, limit)).await {
+                Ok(0) | Err(_) => break,
+                Ok(_) => (),
+            }
+        }
+
+        if let Err(ex) = sink.write(&lines).await {
+            warn!("{:<12} - {} lines lost - {ex}", "REQUEST LOG", lines.len());
+        }
+        lines.clear();
+
+        let dropped_count = DROPPED_COUNT.swap(0, Ordering::Relaxed);
+        if dropped_count > 0 {
+            warn!(
+                "{:<12} - {dropped_count} lines dropped, buffer full",
+                "REQUEST LOG"
+            );
+        }
+    }
+}
+// endregion: --- Log Writer
+
+// region:    --- Log Request
 pub async fn log_request(
     http_method: Method,
     uri: Uri,
@@ -37,9 +199,12 @@ pub async fn log_request(
     // -- duration_ms in milliseconds with microseconds precision.
     let duration_ms = (duration.as_seconds_f64() * 1_000_000.).floor() / 1_000.;
 
-    let user_id = match ctx {
-        None => None,
-        Some(ctx) => ctx.user_id().as_ref().map(|s| s.to_string()),
+    let (user_id, impersonator_id) = match ctx {
+        None => (None, None),
+        Some(ctx) => (
+            ctx.user_id().as_ref().map(|s| s.to_string()),
+            ctx.impersonator_id().map(|s| s.to_string()),
+        ),
     };
     // -- Create the RequestLogLine
     let log_line = RequestLogLine {
@@ -47,10 +212,13 @@ pub async fn log_request(
         timestamp: format_time(now).unwrap_or("error generate time stamp".to_string()), // Logline timestamp ("time_out")
         time_in: format_time(time_in).unwrap_or("error generate time in".to_string()),
         duration_ms,
+        trace_id: telemetry::current_trace_id(),
 
         user_id,
+        impersonator_id,
 
-        http_path: uri.to_string(),
+        // -- Not the query, it may carry a secret (e.g. the `?token=` of `/verify-email`).
+        http_path: uri.path().to_string(),
         http_method: http_method.to_string(),
 
         // rpc_id: rpc_info.and_then(|rpc| rpc.id.as_ref().map(|id| id.to_string())),
@@ -60,9 +228,16 @@ pub async fn log_request(
         error_data,
     };
 
-    debug!("REQUEST LOG LINE:\n{}", json!(log_line));
-
-    // TODO: Send to cloud-watch or log monitor
+    // -- Buffer the line, never waiting for the sink.
+    let line = serde_json::to_string(&log_line)?;
+    match LOG_WRITER.get() {
+        Some(log_writer) => {
+            if log_writer.line_tx.try_send(line).is_err() {
+                DROPPED_COUNT.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        None => debug!("REQUEST LOG LINE:\n{line}"),
+    }
 
     Ok(())
 }
@@ -93,9 +268,11 @@ struct RequestLogLine {
     timestamp: String, // (Rfc3339)
     time_in: String,   // (Rfx3339)
     duration_ms: f64,
+    trace_id: Option<String>,
 
     // -- User and context attributes.
     user_id: Option<String>,
+    impersonator_id: Option<String>,
 
     // -- http request attributes.
     http_path: String,
@@ -110,3 +287,63 @@ struct RequestLogLine {
     error_type: Option<String>,
     error_data: Option<Value>,
 }
+// endregion: --- Log Request
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+
+    /// A sink recording the batches, and failing the first one.
+    struct FxSink {
+        batches: Arc<Mutex<Vec<Vec<String>>>>,
+    }
+
+    #[async_trait]
+    impl LogSink for FxSink {
+        async fn write(&mut self, lines: &[String]) -> Result<()> {
+            let mut batches = self.batches.lock().unwrap();
+            batches.push(lines.to_vec());
+            if batches.len() == 1 {
+                return Err(Error::HttpExportFail(503));
+            }
+
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_log_writer_batches_after_sink_fail() {
+        // -- Setup & Fixtures
+        let batches = Arc::new(Mutex::new(Vec::new()));
+        let fx_sink = FxSink {
+            batches: batches.clone(),
+        };
+        let (line_tx, line_rx) = mpsc::channel(10);
+        let (close_tx, close_rx) = oneshot::channel();
+        for line in ["fx-line-1", "fx-line-2", "fx-line-3"] {
+            line_tx.try_send(line.to_string()).unwrap();
+        }
+
+        // -- Exec
+        let handle = tokio::spawn(run_log_writer(
+            line_rx,
+            close_rx,
+            Box::new(fx_sink),
+            2,
+            Duration::from_millis(10),
+        ));
+        close_tx.send(()).unwrap();
+        handle.await.unwrap();
+
+        // -- Check
+        //    (the failed batch is lost, the writer goes on)
+        assert_eq!(
+            *batches.lock().unwrap(),
+            [vec!["fx-line-1", "fx-line-2"], vec!["fx-line-3"]]
+        );
+    }
+}
+// endregion: --- Tests
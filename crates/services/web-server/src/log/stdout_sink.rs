@@ -0,0 +1,20 @@
+use async_trait::async_trait;
+use tokio::io::{self, AsyncWriteExt};
+
+use super::{LogSink, Result};
+
+/// Write the log lines as JSON lines to stdout, e.g., for a container log collector.
+pub struct StdoutLogSink;
+
+#[async_trait]
+impl LogSink for StdoutLogSink {
+    async fn write(&mut self, lines: &[String]) -> Result<()> {
+        let content: String = lines.iter().map(|line| format!("{line}\n")).collect();
+
+        let mut stdout = io::stdout();
+        stdout.write_all(content.as_bytes()).await?;
+        stdout.flush().await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,41 @@
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use lib_utils::time::now_utc;
+use tokio::fs;
+use tracing::debug;
+use uuid::Uuid;
+
+use super::{Mail, MailSender, Result};
+
+/// Write every mail as a file in the outbox folder, for local development.
+pub struct FileMailSender {
+    outbox_folder: PathBuf,
+}
+
+impl FileMailSender {
+    pub fn new(outbox_folder: &str) -> Self {
+        FileMailSender {
+            outbox_folder: PathBuf::from(outbox_folder),
+        }
+    }
+}
+
+#[async_trait]
+impl MailSender for FileMailSender {
+    async fn send(&self, mail: Mail) -> Result<()> {
+        let Mail { to, subject, body } = mail;
+
+        fs::create_dir_all(&self.outbox_folder).await?;
+
+        let file_name = format!("{}-{}.txt", now_utc().unix_timestamp(), Uuid::new_v4());
+        let file_path = self.outbox_folder.join(file_name);
+        let content = format!("To: {to}\nSubject: {subject}\n\n{body}\n");
+
+        fs::write(&file_path, content).await?;
+
+        debug!("{:<12} - FileMailSender - {file_path:?}", "MAIL");
+
+        Ok(())
+    }
+}
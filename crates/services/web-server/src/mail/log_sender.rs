@@ -0,0 +1,18 @@
+use async_trait::async_trait;
+use tracing::info;
+
+use super::{Mail, MailSender, Result};
+
+/// Only log the mail, for local development.
+pub struct LogMailSender;
+
+#[async_trait]
+impl MailSender for LogMailSender {
+    async fn send(&self, mail: Mail) -> Result<()> {
+        let Mail { to, subject, body } = mail;
+
+        info!("{:<12} - to: {to} - subject: {subject}\n{body}", "MAIL");
+
+        Ok(())
+    }
+}
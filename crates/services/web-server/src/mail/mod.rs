@@ -0,0 +1,46 @@
+mod error;
+mod file_sender;
+mod log_sender;
+
+use std::sync::OnceLock;
+
+use async_trait::async_trait;
+
+use crate::config::web_config;
+
+pub use self::error::{Error, Result};
+pub use self::file_sender::FileMailSender;
+pub use self::log_sender::LogMailSender;
+
+pub fn mail_sender() -> &'static dyn MailSender {
+    static INSTANCE: OnceLock<Box<dyn MailSender>> = OnceLock::new();
+
+    INSTANCE
+        .get_or_init(|| {
+            new_mail_sender(&web_config().MAIL_SENDER)
+                .unwrap_or_else(|ex| panic!("FATAL - WHILE LOADING MAIL SENDER - Cause: {ex:?}"))
+        })
+        .as_ref()
+}
+
+#[derive(Debug)]
+pub struct Mail {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+#[async_trait]
+pub trait MailSender: Send + Sync {
+    async fn send(&self, mail: Mail) -> Result<()>;
+}
+
+fn new_mail_sender(sender_name: &str) -> Result<Box<dyn MailSender>> {
+    match sender_name {
+        "log" => Ok(Box::new(LogMailSender)),
+        "file" => Ok(Box::new(FileMailSender::new(
+            &web_config().MAIL_OUTBOX_FOLDER,
+        ))),
+        _ => Err(Error::MailSenderNotFound(sender_name.to_string())),
+    }
+}
@@ -1,29 +1,49 @@
 mod config;
 mod error;
 mod log;
+mod mail;
 mod middlewares;
+mod oidc;
 mod params;
 mod routes;
+mod telemetry;
 
-use axum::{middleware, Router};
+use std::{future::IntoFuture, net::SocketAddr};
+
+use axum::{extract::DefaultBodyLimit, middleware, Router};
 use lib_surrealdb::model::ModelManager;
-use tokio::net::TcpListener;
+use tokio::{net::TcpListener, signal, sync::oneshot};
+use tower_http::{limit::RequestBodyLimitLayer, timeout::TimeoutLayer};
 // use tokio::net::TcpListener;
-use tracing::info;
-use tracing_subscriber::EnvFilter;
+use tracing::{debug, error, info, warn};
+
+use crate::config::{web_config, AppConfig};
 
 pub use error::Result;
 
 #[tokio::main]
-async fn main() {
-    tracing_subscriber::fmt()
-        .with_env_filter(EnvFilter::from_default_env())
-        .init();
+async fn main() -> Result<()> {
+    // -- Load and validate the whole config, before anything uses it.
+    let config = AppConfig::load(std::env::args().skip(1));
+
+    // -- Tracing, with the spans exported once the config is valid.
+    telemetry::init_tracing(config.as_ref().ok().map(|config| &config.web))?;
+    let config = config.inspect_err(|ex| {
+        error!("{:<12} - invalid config:\n{ex}", "CONFIG");
+    })?;
+    debug!("{:<12} - {config:?}", "CONFIG");
+    config.init()?;
+
+    // -- Record the metrics, rendered by `GET /metrics`.
+    telemetry::init_metrics()?;
+
+    // -- Start writing the request log lines to the configured sink.
+    log::start_log_writer()?;
 
     info!("Starting Connection to SurrealDB");
 
     // -- Initialize ModelManager.
-    let mm = ModelManager::new().await.unwrap();
+    let mm = ModelManager::new().await?;
 
     // -- Testing Connection to DB
     mm.test_connection().await;
@@ -34,18 +54,82 @@ async fn main() {
     // let routes_rpc =
     //     web::routes_rpc::routes(rpc_state).route_layer(middleware::from_fn(mw_ctx_require));
 
+    let config = web_config();
     let routes_all = Router::new()
         .merge(routes::route(mm.clone()))
         // .merge(routes_login::routes(mm.clone()))
         // .nest("/api", routes_rpc)
+        // -- Inside the response map, so their 413 and 408 get the JSON error body.
+        .layer(DefaultBodyLimit::disable())
+        .layer(RequestBodyLimitLayer::new(config.REQUEST_BODY_LIMIT))
+        .layer(TimeoutLayer::new(config.REQUEST_TIMEOUT))
         .layer(middleware::map_response(middlewares::mw_response_map))
-        .layer(middleware::from_fn(middlewares::mw_req_stamp))
         // .layer(CookieManagerLayer::new())
-        .fallback_service(routes::static_file::serve_dir());
+        .fallback_service(routes::static_file::serve_dir())
+        // -- After the fallback, so the static files are compressed and get a request id too.
+        .layer(middlewares::compression_layer())
+        .layer(middlewares::cors_layer())
+        .layer(middleware::from_fn(middlewares::mw_req_stamp));
 
-    let listener = TcpListener::bind("127.0.0.1:8080").await.unwrap();
+    let listener = TcpListener::bind((config.WEB_HOST.as_str(), config.WEB_PORT)).await?;
     info!("{:<12} - {:?}", "LISTENING", listener.local_addr());
-    axum::serve(listener, routes_all.into_make_service())
-        .await
-        .unwrap();
+
+    // -- On the signal, stop accepting connections and drain the in-flight requests.
+    //    (in a block, so the routes and their ModelManager clones are dropped before closing)
+    {
+        let (shutdown_tx, shutdown_rx) = oneshot::channel();
+        let server = axum::serve(
+            listener,
+            routes_all.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .with_graceful_shutdown(async move {
+            shutdown_signal().await;
+            let _ = shutdown_tx.send(());
+        })
+        .into_future();
+        tokio::pin!(server);
+
+        tokio::select! {
+            res = &mut server => res?,
+            _ = shutdown_rx => {
+                info!("{:<12} - draining in-flight requests", "SHUTDOWN");
+                let timeout = config.WEB_SHUTDOWN_TIMEOUT;
+                match tokio::time::timeout(timeout, &mut server).await {
+                    Ok(res) => res?,
+                    Err(_) => warn!("{:<12} - drain timeout, dropping in-flight requests", "SHUTDOWN"),
+                }
+            }
+        }
+    }
+
+    log::close_log_writer().await;
+    mm.close().await?;
+    telemetry::shutdown_tracing();
+    info!("{:<12} - done", "SHUTDOWN");
+
+    Ok(())
+}
+
+/// Resolve on SIGINT (Ctrl+C) or SIGTERM.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        signal::ctrl_c()
+            .await
+            .expect("FATAL - cannot install the SIGINT handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        signal::unix::signal(signal::unix::SignalKind::terminate())
+            .expect("FATAL - cannot install the SIGTERM handler")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => info!("{:<12} - SIGINT received", "SHUTDOWN"),
+        _ = terminate => info!("{:<12} - SIGTERM received", "SHUTDOWN"),
+    }
 }
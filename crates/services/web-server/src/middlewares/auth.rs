@@ -1,23 +1,43 @@
+use std::{
+    net::{IpAddr, SocketAddr},
+    str::FromStr,
+};
+
 use async_trait::async_trait;
-use lib_auth::token;
+use lib_auth::{
+    api_key::{self, ApiKeyParts},
+    token,
+};
 use lib_surrealdb::{
-    ctx::Ctx,
+    ctx::{Ctx, CtxBuilder},
     model::{
+        api_keys::bmc::ApiKeysBmc,
+        audit_logs::{bmc::AuditLogsBmc, AuditAction, AuditLogsForCreate},
+        role_policies::{bmc::RolePoliciesBmc, Permission, Role},
+        sessions::bmc::SessionsBmc,
         users::{bmc::UsersBmc, Users},
         ModelManager,
     },
 };
 use serde::Serialize;
 
-use super::error::{Error, Result};
+use super::{
+    error::{Error, Result},
+    req_stamp::ReqStamp,
+};
 use axum::{
     body::Body,
-    extract::{FromRequestParts, Request, State},
-    http::{request::Parts, HeaderMap},
+    extract::{ConnectInfo, FromRequestParts, Request, State},
+    http::{request::Parts, HeaderMap, Method},
     middleware::Next,
     response::Response,
 };
-use tracing::debug;
+use tracing::{debug, error};
+use uuid::Uuid;
+
+use crate::config::web_config;
+
+const X_API_KEY: &str = "X-Api-Key";
 
 pub async fn mw_ctx_resolve(
     mm: State<ModelManager>,
@@ -26,6 +46,82 @@ pub async fn mw_ctx_resolve(
     next: Next,
 ) -> Result<Response> {
     debug!("{:<12} - mw_ctx_resolve {:?}", "MIDDLEWARE", headers);
+    let token = match headers.get(X_API_KEY) {
+        Some(api_key) => api_key
+            .to_str()
+            .map_err(|_| Error::CannotConvertApiKeyToStr)?,
+        None => bearer_token(&headers)?,
+    };
+
+    debug!("{:<12} - mw_ctx_resolve {:?}", "MIDDLEWARE", token);
+
+    // -- Request info from the outer layers.
+    let req_id = req.extensions().get::<ReqStamp>().map(|stamp| stamp.uuid);
+    let client_ip = req
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(client_addr)| client_addr.ip());
+
+    let ctx_ext_result = if api_key::is_api_key(token) {
+        inner_api_key_resolve(mm.clone(), token, req_id, client_ip).await
+    } else {
+        inner_ctx_resolve(mm.clone(), token, req_id, client_ip).await
+    };
+    let ctxw = ctx_ext_result.as_ref().ok().cloned();
+    let method = req.method().clone();
+    let uri = req.uri().clone();
+
+    // -- Store the ctx_ext_result in the request extension
+    // (for Ctx extractor)
+    let _ctxw = req.extensions_mut().insert(ctx_ext_result);
+
+    let mut response = next.run(req).await;
+
+    if let Some(ctxw) = ctxw {
+        // -- Audit what is done while impersonating, reads excepted.
+        if ctxw.0.impersonator_id().is_some() && !matches!(method, Method::GET | Method::HEAD) {
+            let audit_logs_for_create = AuditLogsForCreate {
+                action: AuditAction::ImpersonatedRequest,
+                user_id: ctxw.0.user_id().map(str::to_string),
+                detail: Some(format!("{method} {uri} {}", response.status().as_u16())),
+            };
+            if let Err(ex) = AuditLogsBmc::create(&ctxw.0, &mm, audit_logs_for_create).await {
+                error!("{:<12} - audit impersonated request - {ex:?}", "MIDDLEWARE");
+            }
+        }
+
+        // -- The ctx is resolved by this inner layer, give it back for the request log line.
+        response.extensions_mut().insert(ctxw);
+    }
+
+    Ok(response)
+}
+
+/// Require the `SERVICE_METRICS_TOKEN` as the `Authorization: Bearer <token>` header.
+pub async fn mw_metrics_token(
+    headers: HeaderMap,
+    req: Request<Body>,
+    next: Next,
+) -> Result<Response> {
+    let token = bearer_token(&headers)?;
+    let metrics_token = web_config()
+        .METRICS_TOKEN
+        .as_ref()
+        .ok_or(Error::MetricsTokenNotMatching)?;
+    if !eq_constant_time(token.as_bytes(), metrics_token.as_bytes()) {
+        return Err(Error::MetricsTokenNotMatching);
+    }
+
+    Ok(next.run(req).await)
+}
+
+/// Compare without stopping at the first difference, so the time does not leak the token.
+fn eq_constant_time(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// Get the token of the `Authorization: Bearer <token>` header.
+fn bearer_token(headers: &HeaderMap) -> Result<&str> {
     let mut authorization = headers
         .get("Authorization")
         .ok_or(Error::NoAuthorizationHeader)?
@@ -44,32 +140,112 @@ pub async fn mw_ctx_resolve(
         })??;
     let token = authorization.next().ok_or(Error::InvalidBearerToken)?;
 
-    debug!("{:<12} - mw_ctx_resolve {:?}", "MIDDLEWARE", token);
-
-    let ctx_ext_result = inner_ctx_resolve(mm, token).await;
-
-    // -- Store the ctx_ext_result in the request extension
-    // (for Ctx extractor)
-    let _ctxw = req.extensions_mut().insert(ctx_ext_result);
-
-    let response = next.run(req).await;
-
-    Ok(response)
+    Ok(token)
 }
 
-async fn inner_ctx_resolve(mm: State<ModelManager>, token: &str) -> CtxExtResult {
+async fn inner_ctx_resolve(
+    mm: State<ModelManager>,
+    token: &str,
+    req_id: Option<Uuid>,
+    client_ip: Option<IpAddr>,
+) -> CtxExtResult {
     let user_id = token::decode_kid_from_jwt_headers(token)
         .map_err(|_| CtxExtError::InvalidJwtTokenHeader)?;
-    let _ctx = Ctx::root_ctx();
-    let user = UsersBmc::get::<Users>(&_ctx, &mm, &user_id.as_str())
+    let root_ctx = req_id.map_or_else(Ctx::root_ctx, Ctx::root_ctx_for_req);
+    let user = UsersBmc::get::<Users>(&root_ctx, &mm, &user_id.as_str())
         .await
         .map_err(|e| CtxExtError::ModelAccessError(e.to_string()))?
         .ok_or(CtxExtError::UserNotFound)?;
 
     let token_salt = user.token_salt.as_ref();
-    let sub = token::decode_sub_from_jwt(token, token_salt)
+    let jwt_session = token::decode_session_from_jwt(token, token_salt)
         .map_err(|e| CtxExtError::JwtDecodeError(e.to_string()))?;
-    let ctxw = Ctx::new(Some(sub))
+
+    let mut builder = CtxBuilder::new(jwt_session.sub);
+
+    // -- The impersonator must still be a user, and still be granted `users:impersonate`.
+    if let Some(impersonator_id) = jwt_session.impersonator_id {
+        let impersonator = UsersBmc::get::<Users>(&root_ctx, &mm, &impersonator_id)
+            .await
+            .map_err(|e| CtxExtError::ModelAccessError(e.to_string()))?
+            .ok_or(CtxExtError::ImpersonatorNotFound)?;
+        let role = Role::from_str(&impersonator.role).map_err(|_| CtxExtError::UserRoleNotValid)?;
+        let permissions = RolePoliciesBmc::permissions(&root_ctx, &mm, role)
+            .await
+            .map_err(|e| CtxExtError::ModelAccessError(e.to_string()))?;
+        if !permissions.contains(&Permission::UsersImpersonate) {
+            return Err(CtxExtError::ImpersonatorNotAllowed);
+        }
+
+        builder = builder.impersonator_id(impersonator_id);
+    }
+
+    // -- Reject the jwt of a revoked session.
+    //    (A jwt without session is from before the sessions, it is only bound by its expiration)
+    if let Some(session_id) = jwt_session.session_id {
+        let _session = SessionsBmc::get_active(&root_ctx, &mm, &session_id)
+            .await
+            .map_err(|e| CtxExtError::ModelAccessError(e.to_string()))?
+            .ok_or(CtxExtError::SessionRevoked)?;
+
+        SessionsBmc::touch(&root_ctx, &mm, &session_id)
+            .await
+            .map_err(|e| CtxExtError::ModelAccessError(e.to_string()))?;
+
+        builder = builder.session_id(session_id);
+    }
+
+    new_ctxw(builder, &user, req_id, client_ip)
+}
+
+async fn inner_api_key_resolve(
+    mm: State<ModelManager>,
+    api_key: &str,
+    req_id: Option<Uuid>,
+    client_ip: Option<IpAddr>,
+) -> CtxExtResult {
+    let ApiKeyParts { key_id, secret } =
+        api_key::parse_api_key(api_key).map_err(|_| CtxExtError::ApiKeyInvalid)?;
+    let root_ctx = req_id.map_or_else(Ctx::root_ctx, Ctx::root_ctx_for_req);
+    let key = ApiKeysBmc::get_active(&root_ctx, &mm, &key_id)
+        .await
+        .map_err(|e| CtxExtError::ModelAccessError(e.to_string()))?
+        .ok_or(CtxExtError::ApiKeyInvalid)?;
+    api_key::validate_secret(&secret, &key.secret_hash).map_err(|_| CtxExtError::ApiKeyInvalid)?;
+
+    let user_id = key.user.id.to_raw();
+    let user = UsersBmc::get::<Users>(&root_ctx, &mm, &user_id)
+        .await
+        .map_err(|e| CtxExtError::ModelAccessError(e.to_string()))?
+        .ok_or(CtxExtError::UserNotFound)?;
+
+    ApiKeysBmc::touch(&root_ctx, &mm, &key_id)
+        .await
+        .map_err(|e| CtxExtError::ModelAccessError(e.to_string()))?;
+
+    let builder = CtxBuilder::new(user_id).api_key(key_id, key.scopes);
+    new_ctxw(builder, &user, req_id, client_ip)
+}
+
+/// Complete the ctx `builder` of `user`, with its roles and the request info.
+fn new_ctxw(
+    builder: CtxBuilder,
+    user: &Users,
+    req_id: Option<Uuid>,
+    client_ip: Option<IpAddr>,
+) -> CtxExtResult {
+    let role = Role::from_str(&user.role).map_err(|_| CtxExtError::UserRoleNotValid)?;
+
+    // -- Carry the user roles, handlers do not need to load the user again.
+    let mut builder = builder.roles(vec![role]);
+    if let Some(req_id) = req_id {
+        builder = builder.req_id(req_id);
+    }
+    if let Some(client_ip) = client_ip {
+        builder = builder.client_ip(client_ip);
+    }
+
+    let ctxw = Ctx::new(builder)
         .map_err(|_| CtxExtError::CannotCreateCtxFromJwt)
         .map(CtxW);
     ctxw
@@ -106,9 +282,64 @@ pub enum CtxExtError {
     JwtDecodeError(String),
     InvalidJwtTokenHeader,
     CannotCreateCtxFromJwt,
+    ApiKeyInvalid,
+    SessionRevoked,
+    ImpersonatorNotFound,
+    ImpersonatorNotAllowed,
 
     ModelAccessError(String),
     UserNotFound,
+    UserRoleNotValid,
     CtxNotInRequestExt,
 }
 // endregion: --- Ctx Extractor Result/Error
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    type Error = Box<dyn std::error::Error>;
+    type Result<T> = core::result::Result<T, Error>; // For tests.
+
+    use super::*;
+    use serial_test::serial;
+
+    #[serial]
+    #[tokio::test]
+    async fn test_inner_ctx_resolve_err_impersonator_not_allowed() -> Result<()> {
+        // -- Setup & Fixtures
+        //    (demo1 is a `USER`, a role without `users:impersonate`)
+        let mm = ModelManager::new().await?;
+        let ctx = Ctx::root_ctx();
+        let fx_user_id = "iR1f8i7Wg7jipR3uhDhJ";
+        let fx_impersonator_id = "iR1f8i7Wg7jipR3uhDhJ";
+        let user = UsersBmc::get::<Users>(&ctx, &mm, fx_user_id)
+            .await?
+            .ok_or("Should have user 'demo1'")?;
+        let fx_token = token::encode_impersonation_jwt(
+            fx_user_id,
+            fx_impersonator_id,
+            user.token_salt.as_ref(),
+        )?;
+
+        // -- Exec
+        let res = inner_ctx_resolve(State(mm), &fx_token, None, None).await;
+
+        // -- Check
+        assert!(
+            matches!(res, Err(CtxExtError::ImpersonatorNotAllowed)),
+            "should be ImpersonatorNotAllowed, but was {res:?}"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_eq_constant_time() {
+        // -- Exec & Check
+        assert!(eq_constant_time(b"fx-metrics-token", b"fx-metrics-token"));
+        assert!(!eq_constant_time(b"fx-metrics-token", b"fx-metrics-tokeN"));
+        assert!(!eq_constant_time(b"fx-metrics-token", b"fx-metrics"));
+        assert!(!eq_constant_time(b"", b"fx-metrics-token"));
+    }
+}
+// endregion: --- Tests
@@ -6,6 +6,8 @@ use serde::Serialize;
 use serde_with::serde_as;
 use tracing::debug;
 
+use lib_surrealdb::model;
+
 use crate::routes::ClientError;
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -21,14 +23,31 @@ pub enum Error {
     NoAuthorizationBearer,
     NoAuthorizationHeader,
     CannotConvertAuthorizationToStr,
+    CannotConvertApiKeyToStr,
     InvalidBearerToken,
 
     // -- CtxExtError
     #[from]
     CtxExt(super::auth::CtxExtError),
+
+    // -- Permission
+    PermissionDenied {
+        permission: &'static str,
+    },
+
+    // -- Metrics
+    MetricsTokenNotMatching,
+
+    // -- Rate Limit
+    RateLimited {
+        policy: &'static str,
+        retry_after_sec: i64,
+    },
+
     // -- Modules
     // Token(token::Error),
-    // Model(model::Error),
+    #[from]
+    Model(model::Error),
 }
 
 // region:    --- Axum IntoResponse
@@ -65,13 +84,30 @@ impl Error {
             NoAuthorizationBearer
             | NoAuthorizationHeader
             | CannotConvertAuthorizationToStr
+            | CannotConvertApiKeyToStr
             | InvalidBearerToken => (
                 StatusCode::FORBIDDEN,
                 ClientError::INVALID_AUTHORIZATION_HEADER,
             ),
 
             // -- Auth
-            CtxExt(_) => (StatusCode::FORBIDDEN, ClientError::NO_AUTH),
+            CtxExt(_) | MetricsTokenNotMatching => (StatusCode::FORBIDDEN, ClientError::NO_AUTH),
+            PermissionDenied { permission } => (
+                StatusCode::FORBIDDEN,
+                ClientError::PERMISSION_DENIED {
+                    permission: *permission,
+                },
+            ),
+
+            // -- Rate Limit
+            RateLimited {
+                retry_after_sec, ..
+            } => (
+                StatusCode::TOO_MANY_REQUESTS,
+                ClientError::RATE_LIMITED {
+                    retry_after_sec: *retry_after_sec,
+                },
+            ),
 
             // -- Fallback,
             _ => (
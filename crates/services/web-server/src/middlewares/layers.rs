@@ -0,0 +1,60 @@
+use axum::http::{
+    header::{AUTHORIZATION, CONTENT_TYPE, RETRY_AFTER},
+    HeaderName,
+};
+use tower_http::{
+    compression::CompressionLayer,
+    cors::{AllowOrigin, CorsLayer},
+};
+
+use super::{
+    rate_limit::{RATELIMIT_LIMIT, RATELIMIT_REMAINING, RATELIMIT_RESET},
+    req_stamp::X_REQUEST_ID,
+};
+use crate::config::web_config;
+
+/// The CORS of the configured origins (`*` for any origin, none when empty).
+///
+/// The api is authenticated by headers, not cookies, so credentials are not allowed.
+pub fn cors_layer() -> CorsLayer {
+    let config = web_config();
+
+    let allow_origin = if config
+        .CORS_ALLOWED_ORIGINS
+        .iter()
+        .any(|origin| origin == "*")
+    {
+        AllowOrigin::any()
+    } else {
+        AllowOrigin::list(config.CORS_ALLOWED_ORIGINS.iter().cloned())
+    };
+
+    CorsLayer::new()
+        .allow_origin(allow_origin)
+        .allow_methods(config.CORS_ALLOWED_METHODS.clone())
+        .allow_headers([
+            AUTHORIZATION,
+            CONTENT_TYPE,
+            HeaderName::from_static("x-api-key"),
+            HeaderName::from_static("traceparent"),
+            X_REQUEST_ID,
+        ])
+        .expose_headers([
+            RETRY_AFTER,
+            X_REQUEST_ID,
+            RATELIMIT_LIMIT,
+            RATELIMIT_REMAINING,
+            RATELIMIT_RESET,
+        ])
+}
+
+/// The compression of the responses, with the configured encodings only.
+pub fn compression_layer() -> CompressionLayer {
+    let encodings = &web_config().COMPRESSION_ENCODINGS;
+    let enabled = |name: &str| encodings.iter().any(|encoding| encoding == name);
+
+    CompressionLayer::new()
+        .gzip(enabled("gzip"))
+        .br(enabled("br"))
+        .zstd(enabled("zstd"))
+}
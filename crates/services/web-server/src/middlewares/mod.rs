@@ -1,9 +1,14 @@
 pub(crate) mod auth;
 mod error;
+mod layers;
+pub(crate) mod permission;
+mod rate_limit;
 mod req_stamp;
 mod res_map;
 
 pub use self::error::{Error, Result};
-pub use auth::mw_ctx_resolve;
+pub use auth::{mw_ctx_resolve, mw_metrics_token};
+pub use layers::{compression_layer, cors_layer};
+pub use rate_limit::{mw_rate_limit, RateLimiter};
 pub use req_stamp::{mw_req_stamp, ReqStamp};
 pub use res_map::mw_response_map;
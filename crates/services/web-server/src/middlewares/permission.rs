@@ -0,0 +1,88 @@
+use std::marker::PhantomData;
+
+use async_trait::async_trait;
+use axum::{
+    extract::{FromRef, FromRequestParts},
+    http::request::Parts,
+};
+use lib_surrealdb::{
+    ctx::Ctx,
+    model::{
+        role_policies::{bmc::RolePoliciesBmc, Permission},
+        ModelManager,
+    },
+};
+use tracing::debug;
+
+use super::{
+    auth::CtxW,
+    error::{Error, Result},
+};
+
+// region:    --- Permission Markers
+/// A permission a route requires, see `RequirePermission`.
+pub trait RequiredPermission: Send + Sync {
+    const PERMISSION: Permission;
+}
+
+macro_rules! required_permissions {
+    ($($name:ident),+ $(,)?) => {
+        $(
+            pub struct $name;
+
+            impl RequiredPermission for $name {
+                const PERMISSION: Permission = Permission::$name;
+            }
+        )+
+    };
+}
+
+required_permissions!(
+    UsersRead,
+    UsersWrite,
+    UsersDelete,
+    UsersImpersonate,
+    TasksRead,
+    TasksWrite,
+    TasksDelete,
+    RolesRead,
+    RolesWrite,
+);
+// endregion: --- Permission Markers
+
+// region:    --- RequirePermission Extractor
+/// Extract the ctx, only if the role of its user is granted `P`.
+///
+/// e.g., `ctxw: RequirePermission<UsersWrite>`, then `let ctx = ctxw.0;`
+pub struct RequirePermission<P: RequiredPermission>(pub Ctx, PhantomData<P>);
+
+#[async_trait]
+impl<S, P> FromRequestParts<S> for RequirePermission<P>
+where
+    ModelManager: FromRef<S>,
+    S: Send + Sync,
+    P: RequiredPermission,
+{
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self> {
+        let permission = P::PERMISSION;
+        debug!(
+            "{:<12} - RequirePermission {}",
+            "EXTRACTOR",
+            permission.as_str()
+        );
+
+        let CtxW(ctx) = CtxW::from_request_parts(parts, state).await?;
+        let mm = ModelManager::from_ref(state);
+
+        if !RolePoliciesBmc::has_permission(&ctx, &mm, permission).await? {
+            return Err(Error::PermissionDenied {
+                permission: permission.as_str(),
+            });
+        }
+
+        Ok(RequirePermission(ctx, PhantomData))
+    }
+}
+// endregion: --- RequirePermission Extractor
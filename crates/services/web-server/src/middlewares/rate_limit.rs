@@ -0,0 +1,255 @@
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    body::Body,
+    extract::{ConnectInfo, Request, State},
+    http::{HeaderMap, HeaderName, HeaderValue},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use tracing::debug;
+
+use super::{auth::CtxW, Error};
+use crate::config::web_config;
+
+pub(super) const RATELIMIT_LIMIT: HeaderName = HeaderName::from_static("ratelimit-limit");
+pub(super) const RATELIMIT_REMAINING: HeaderName = HeaderName::from_static("ratelimit-remaining");
+pub(super) const RATELIMIT_RESET: HeaderName = HeaderName::from_static("ratelimit-reset");
+
+/// How often the full buckets are dropped (a full bucket is a new one).
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
+// region:    --- Rate Limiter
+/// A token bucket per client, for one group of routes.
+///
+/// A client can make `burst` requests at once, then one more every `refill`.
+/// The client is its user when the ctx is resolved, its ip otherwise.
+#[derive(Clone)]
+pub struct RateLimiter {
+    policy: &'static str,
+    burst: u32,
+    refill: Duration,
+    buckets: Arc<Mutex<Buckets>>,
+}
+
+struct Buckets {
+    by_key: HashMap<String, Bucket>,
+    pruned: Instant,
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// The outcome of taking a token, for the `RateLimit-*` headers.
+struct RateLimitStatus {
+    allowed: bool,
+    remaining: u32,
+    /// Seconds until the bucket is full again.
+    reset_sec: u64,
+    /// Seconds until the next token, when not allowed.
+    retry_after_sec: i64,
+}
+
+impl RateLimiter {
+    pub fn new(policy: &'static str, burst: u32, refill: Duration) -> Self {
+        RateLimiter {
+            policy,
+            burst,
+            refill,
+            buckets: Arc::new(Mutex::new(Buckets {
+                by_key: HashMap::new(),
+                pruned: Instant::now(),
+            })),
+        }
+    }
+
+    /// The policy of the public login, password and email routes.
+    pub fn auth() -> Self {
+        let config = web_config();
+        Self::new(
+            "auth",
+            config.RATE_LIMIT_AUTH_BURST,
+            config.RATE_LIMIT_AUTH_REFILL,
+        )
+    }
+
+    /// The policy of the authenticated api routes.
+    pub fn api() -> Self {
+        let config = web_config();
+        Self::new(
+            "api",
+            config.RATE_LIMIT_API_BURST,
+            config.RATE_LIMIT_API_REFILL,
+        )
+    }
+
+    fn take(&self, key: &str, now: Instant) -> RateLimitStatus {
+        let burst = f64::from(self.burst);
+        let refill_sec = self.refill.as_secs_f64();
+
+        let mut buckets = self.buckets.lock().unwrap_or_else(|ex| ex.into_inner());
+        // -- One scan per interval, not one per request.
+        if now.duration_since(buckets.pruned) >= PRUNE_INTERVAL {
+            buckets.by_key.retain(|_, bucket| {
+                let elapsed_sec = now.duration_since(bucket.updated).as_secs_f64();
+                bucket.tokens + elapsed_sec / refill_sec < burst
+            });
+            buckets.pruned = now;
+        }
+
+        let bucket = buckets.by_key.entry(key.to_string()).or_insert(Bucket {
+            tokens: burst,
+            updated: now,
+        });
+        let elapsed_sec = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed_sec / refill_sec).min(burst);
+        bucket.updated = now;
+
+        let allowed = bucket.tokens >= 1.0;
+        if allowed {
+            bucket.tokens -= 1.0;
+        }
+
+        RateLimitStatus {
+            allowed,
+            remaining: bucket.tokens.floor() as u32,
+            reset_sec: ((burst - bucket.tokens) * refill_sec).ceil() as u64,
+            retry_after_sec: ((1.0 - bucket.tokens) * refill_sec).ceil().max(1.0) as i64,
+        }
+    }
+}
+// endregion: --- Rate Limiter
+
+/// Take a token of the client bucket, or reject the request with `RATE_LIMITED`.
+///
+/// Must be layered inside `mw_ctx_resolve` for the user to be the key.
+pub async fn mw_rate_limit(
+    State(limiter): State<RateLimiter>,
+    ctx: Option<CtxW>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    debug!("{:<12} - mw_rate_limit", "MIDDLEWARE");
+
+    let key = match ctx.as_ref().and_then(|ctx| ctx.0.user_id()) {
+        Some(user_id) => format!("user:{user_id}"),
+        None => {
+            let client_ip = req
+                .extensions()
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|ConnectInfo(client_addr)| client_addr.ip().to_string());
+            format!("ip:{}", client_ip.as_deref().unwrap_or("unknown"))
+        }
+    };
+
+    let status = limiter.take(&key, Instant::now());
+
+    let mut res = if status.allowed {
+        next.run(req).await
+    } else {
+        debug!(
+            "{:<12} - rate limited - {} {key}",
+            "MIDDLEWARE", limiter.policy
+        );
+        Error::RateLimited {
+            policy: limiter.policy,
+            retry_after_sec: status.retry_after_sec,
+        }
+        .into_response()
+    };
+    insert_headers(res.headers_mut(), limiter.burst, &status);
+
+    res
+}
+
+fn insert_headers(headers: &mut HeaderMap, burst: u32, status: &RateLimitStatus) {
+    headers.insert(RATELIMIT_LIMIT, HeaderValue::from(burst));
+    headers.insert(RATELIMIT_REMAINING, HeaderValue::from(status.remaining));
+    headers.insert(RATELIMIT_RESET, HeaderValue::from(status.reset_sec));
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fx_limiter() -> RateLimiter {
+        RateLimiter::new("fx", 3, Duration::from_secs(10))
+    }
+
+    #[test]
+    fn test_take_burst() {
+        // -- Setup & Fixtures
+        let limiter = fx_limiter();
+        let fx_now = Instant::now();
+
+        // -- Exec
+        let statuses: Vec<RateLimitStatus> =
+            (0..4).map(|_| limiter.take("fx-key", fx_now)).collect();
+
+        // -- Check
+        let allowed: Vec<bool> = statuses.iter().map(|status| status.allowed).collect();
+        assert_eq!(allowed, [true, true, true, false]);
+        let remaining: Vec<u32> = statuses.iter().map(|status| status.remaining).collect();
+        assert_eq!(remaining, [2, 1, 0, 0]);
+        assert_eq!(statuses[3].reset_sec, 30);
+        assert_eq!(statuses[3].retry_after_sec, 10);
+        // -- Another client has its own bucket.
+        assert!(limiter.take("fx-other-key", fx_now).allowed);
+    }
+
+    #[test]
+    fn test_take_refill() {
+        // -- Setup & Fixtures
+        let limiter = fx_limiter();
+        let fx_now = Instant::now();
+        for _ in 0..3 {
+            limiter.take("fx-key", fx_now);
+        }
+
+        // -- Exec
+        let half_token = limiter.take("fx-key", fx_now + Duration::from_secs(5));
+        let one_token = limiter.take("fx-key", fx_now + Duration::from_secs(10));
+        let full = limiter.take("fx-key", fx_now + Duration::from_secs(60));
+
+        // -- Check
+        assert!(!half_token.allowed);
+        assert_eq!(half_token.retry_after_sec, 5);
+        assert!(one_token.allowed);
+        assert_eq!(one_token.remaining, 0);
+        assert!(full.allowed);
+        assert_eq!(full.remaining, 2);
+        assert_eq!(full.reset_sec, 10);
+    }
+
+    #[test]
+    fn test_take_prune_full_buckets() {
+        // -- Setup & Fixtures
+        let limiter = fx_limiter();
+        let fx_now = Instant::now();
+        limiter.take("fx-key-full", fx_now);
+        for _ in 0..3 {
+            limiter.take(
+                "fx-key-empty",
+                fx_now + PRUNE_INTERVAL - Duration::from_secs(1),
+            );
+        }
+
+        // -- Exec
+        limiter.take("fx-key-new", fx_now + PRUNE_INTERVAL);
+
+        // -- Check
+        let buckets = limiter.buckets.lock().unwrap();
+        let mut keys: Vec<&str> = buckets.by_key.keys().map(String::as_str).collect();
+        keys.sort();
+        assert_eq!(keys, ["fx-key-empty", "fx-key-new"]);
+    }
+}
+// endregion: --- Tests
@@ -3,15 +3,21 @@ use async_trait::async_trait;
 use axum::{
     body::Body,
     extract::FromRequestParts,
-    http::{request::Parts, Request},
+    http::{request::Parts, HeaderName, HeaderValue, Request},
     middleware::Next,
     response::Response,
 };
 use lib_utils::time::now_utc;
 use time::OffsetDateTime;
-use tracing::debug;
+use tracing::{debug, Instrument};
 use uuid::Uuid;
 
+use crate::telemetry;
+
+/// The id of the request, taken from the client (or a proxy) when it is a valid uuid,
+/// and given back on every response.
+pub const X_REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
 #[derive(Debug, Clone)]
 pub struct ReqStamp {
     pub uuid: Uuid,
@@ -22,16 +28,30 @@ pub async fn mw_req_stamp(mut req: Request<Body>, next: Next) -> Result<Response
     debug!("{:<12} - mw_req_stamp_resolver", "MIDDLEWARE");
 
     let time_in = now_utc();
-    let uuid = Uuid::new_v4();
+    let uuid = req
+        .headers()
+        .get(&X_REQUEST_ID)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| Uuid::parse_str(value).ok())
+        .unwrap_or_else(Uuid::new_v4);
 
     debug!(
         "{:<12} - mw_req_stamp_resolver - {:?}",
         "MODDLEWARE", time_in
     );
 
+    // -- The span of the request, parent of the spans of its handling.
+    let span = telemetry::request_span(&req, uuid);
+
     req.extensions_mut().insert(ReqStamp { uuid, time_in });
 
-    Ok(next.run(req).await)
+    let mut res = next.run(req).instrument(span.clone()).await;
+    telemetry::record_response(&span, &mut res);
+    if let Ok(req_id) = HeaderValue::from_str(&uuid.to_string()) {
+        res.headers_mut().insert(X_REQUEST_ID, req_id);
+    }
+
+    Ok(res)
 }
 
 // region:    --- ReqStamp Extractor
@@ -1,12 +1,18 @@
 use std::sync::Arc;
 
 use axum::{
-    http::{Method, StatusCode, Uri},
+    extract::MatchedPath,
+    http::{
+        header::{CONTENT_LENGTH, CONTENT_TYPE, RETRY_AFTER},
+        HeaderValue, Method, StatusCode, Uri,
+    },
     response::{IntoResponse, Response},
     Json,
 };
+use lib_utils::time::now_utc;
+use metrics::{counter, histogram};
 use serde_json::{json, to_value};
-use tracing::debug;
+use tracing::{debug, warn};
 
 use super::Error;
 use crate::{
@@ -21,10 +27,14 @@ pub async fn mw_response_map(
     ctx: Option<CtxW>,
     uri: Uri,
     req_method: Method,
+    matched_path: Option<MatchedPath>,
     req_stamp: ReqStamp,
     res: Response,
 ) -> Response {
-    let ctx = ctx.map(|ctx| ctx.0);
+    // -- The ctx of the protected routes is given back by `mw_ctx_resolve`.
+    let ctx = ctx
+        .or_else(|| res.extensions().get::<CtxW>().cloned())
+        .map(|ctx| ctx.0);
 
     debug!("{:<12} - mw_response_map", "RES_MAPPER");
     let uuid = req_stamp.uuid;
@@ -40,7 +50,8 @@ pub async fn mw_response_map(
         .get::<Arc<routes::Error>>()
         .map(Arc::as_ref);
     let client_status_error =
-        convert_middlewares_routes_error_to_client_error(middlewares_error, routes_error);
+        convert_middlewares_routes_error_to_client_error(middlewares_error, routes_error)
+            .or_else(|| convert_status_to_client_error(res.status()));
     // debug!("{:<12?} - client status error", client_status_error);
 
     // -- If client error, build the new response.
@@ -48,6 +59,7 @@ pub async fn mw_response_map(
         .as_ref()
         .map(|(status_code, client_error)| {
             debug!("{:<12} - client_status_error", "RES_MAPPER");
+            let retry_after_sec = client_error.retry_after_sec();
             let client_error = to_value(client_error).ok();
             let message = client_error.as_ref().and_then(|v| v.get("message"));
             let detail = client_error.as_ref().and_then(|v| v.get("detail"));
@@ -66,14 +78,46 @@ pub async fn mw_response_map(
             debug!("CLIENT ERROR BODY:\n{client_error_body}");
 
             // -- Build the new response from the client_error_body
-            (*status_code, Json(client_error_body)).into_response()
+            let mut response = (*status_code, Json(client_error_body)).into_response();
+
+            // -- Keep the headers of the inner layers (e.g., `RateLimit-*`), not of the body.
+            for (name, value) in res.headers() {
+                if name != CONTENT_TYPE && name != CONTENT_LENGTH {
+                    response.headers_mut().append(name, value.clone());
+                }
+            }
+            if let Some(retry_after_sec) = retry_after_sec {
+                response
+                    .headers_mut()
+                    .insert(RETRY_AFTER, HeaderValue::from(retry_after_sec));
+            }
+
+            response
         });
 
+    // -- Record the request metrics, by route template (not by path, to bound the series).
+    let status = error_response.as_ref().unwrap_or(&res).status();
+    let route = matched_path
+        .as_ref()
+        .map_or("unmatched", MatchedPath::as_str);
+    let duration_sec = (now_utc() - req_stamp.time_in).as_seconds_f64();
+    let labels = [
+        ("method", req_method.to_string()),
+        ("route", route.to_string()),
+        ("status", status.as_u16().to_string()),
+    ];
+    counter!("http_requests_total", &labels).increment(1);
+    histogram!("http_request_duration_seconds", &labels).record(duration_sec);
+
     // -- Build and log the server log line.
     let client_error = client_status_error.unzip().1;
+    if let Some(client_error) = &client_error {
+        counter!("http_client_errors_total", "client_error" => client_error.as_ref().to_string())
+            .increment(1);
+    }
 
-    // TODO: Need to hander if log_request fail (but should not fail request)
-    let _log_request = log_request(
+    // -- A log failure never fails the request.
+    if let Err(ex) = log_request(
         req_method,
         uri,
         req_stamp,
@@ -83,7 +127,10 @@ pub async fn mw_response_map(
         middlewares_error,
         client_error,
     )
-    .await;
+    .await
+    {
+        warn!("{:<12} - log_request fail - {ex}", "REQUEST LOG");
+    }
 
     debug!("\n");
 
@@ -101,3 +148,13 @@ fn convert_middlewares_routes_error_to_client_error(
     let client_status_error = routes_error.map(|se| se.client_status_and_error());
     client_status_error
 }
+
+/// The errors without an error extension, e.g., of the tower-http layers (timeout, body limit)
+/// or of the axum extractors (body over the limit).
+fn convert_status_to_client_error(status: StatusCode) -> Option<(StatusCode, ClientError)> {
+    match status {
+        StatusCode::PAYLOAD_TOO_LARGE => Some((status, ClientError::PAYLOAD_TOO_LARGE)),
+        StatusCode::REQUEST_TIMEOUT => Some((status, ClientError::REQUEST_TIMEOUT)),
+        _ => None,
+    }
+}
@@ -0,0 +1,36 @@
+use derive_more::From;
+use lib_auth::oidc;
+use serde::Serialize;
+use serde_with::{serde_as, DisplayFromStr};
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[serde_as]
+#[derive(Debug, Serialize, From)]
+pub enum Error {
+    OidcNotConfigured,
+    IssuerNotMatching {
+        expected: String,
+        actual: String,
+    },
+    AuthorizationUrlInvalid(String),
+    TokenExchangeFail(String),
+
+    // -- Modules
+    #[from]
+    IdToken(oidc::Error),
+
+    // -- Externals
+    #[from]
+    Http(#[serde_as(as = "DisplayFromStr")] reqwest::Error),
+}
+
+// region:    --- Error Boilerplate
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+impl std::error::Error for Error {}
+// endregion: --- Error Boilerplate
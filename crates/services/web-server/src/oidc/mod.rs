@@ -0,0 +1,196 @@
+//! Client of the OpenID Connect provider configured by `SERVICE_OIDC_*`.
+//!
+//! The provider metadata and keys are fetched from its discovery document, and cached
+//! for `OIDC_JWKS_CACHE` (or refetched when an id token uses an unknown key).
+
+mod error;
+
+use std::{sync::OnceLock, time::Instant};
+
+use lib_auth::{
+    jsonwebtoken::jwk::JwkSet,
+    oidc::{self, IdTokenClaims},
+};
+use reqwest::{Client, Url};
+use serde::Deserialize;
+use tokio::sync::RwLock;
+use tracing::debug;
+
+use crate::config::web_config;
+use crate::telemetry::trace_headers;
+
+pub use self::error::{Error, Result};
+
+#[derive(Debug, Clone, Deserialize)]
+struct ProviderMetadata {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+}
+
+#[derive(Debug, Clone)]
+struct Provider {
+    metadata: ProviderMetadata,
+    jwks: JwkSet,
+    fetched_on: Instant,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    id_token: String,
+}
+
+/// Build the url of the provider login page the user is redirected to.
+pub async fn authorization_url(state: &str, nonce: &str, code_challenge: &str) -> Result<String> {
+    let config = web_config();
+    let provider = get_provider(false).await?;
+
+    let url = Url::parse_with_params(
+        &provider.metadata.authorization_endpoint,
+        &[
+            ("response_type", "code"),
+            ("client_id", config.OIDC_CLIENT_ID.as_str()),
+            ("redirect_uri", config.OIDC_REDIRECT_URL.as_str()),
+            ("scope", config.OIDC_SCOPES.as_str()),
+            ("state", state),
+            ("nonce", nonce),
+            ("code_challenge", code_challenge),
+            ("code_challenge_method", "S256"),
+        ],
+    )
+    .map_err(|ex| Error::AuthorizationUrlInvalid(ex.to_string()))?;
+
+    Ok(url.into())
+}
+
+/// Exchange the authorization code for the tokens, and return the id token.
+pub async fn exchange_code(code: &str, code_verifier: &str) -> Result<String> {
+    let config = web_config();
+    let provider = get_provider(false).await?;
+
+    let mut form = vec![
+        ("grant_type", "authorization_code"),
+        ("code", code),
+        ("redirect_uri", config.OIDC_REDIRECT_URL.as_str()),
+        ("client_id", config.OIDC_CLIENT_ID.as_str()),
+        ("code_verifier", code_verifier),
+    ];
+    if let Some(client_secret) = &config.OIDC_CLIENT_SECRET {
+        form.push(("client_secret", client_secret.as_str()));
+    }
+
+    let response = http_client()
+        .post(&provider.metadata.token_endpoint)
+        .headers(trace_headers())
+        .form(&form)
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(Error::TokenExchangeFail(format!("{status} - {body}")));
+    }
+    let token_response: TokenResponse = response.json().await?;
+
+    Ok(token_response.id_token)
+}
+
+/// Validate the id token with the provider keys, refreshing them once if the token
+/// is signed by an unknown key (e.g., after a key rotation).
+pub async fn validate_id_token(id_token: &str, nonce: &str) -> Result<IdTokenClaims> {
+    let config = web_config();
+
+    let provider = get_provider(false).await?;
+    let validate = |provider: &Provider| {
+        oidc::validate_id_token(
+            id_token,
+            &provider.jwks,
+            &provider.metadata.issuer,
+            &config.OIDC_CLIENT_ID,
+            nonce,
+        )
+    };
+
+    let claims = match validate(&provider) {
+        Err(oidc::Error::KeyNotFound { .. }) => validate(&get_provider(true).await?)?,
+        claims => claims?,
+    };
+
+    Ok(claims)
+}
+
+// region:    --- Provider Cache
+fn http_client() -> &'static Client {
+    static INSTANCE: OnceLock<Client> = OnceLock::new();
+
+    INSTANCE.get_or_init(Client::new)
+}
+
+fn provider_cache() -> &'static RwLock<Option<Provider>> {
+    static INSTANCE: OnceLock<RwLock<Option<Provider>>> = OnceLock::new();
+
+    INSTANCE.get_or_init(|| RwLock::new(None))
+}
+
+/// Get the cached provider, fetching it if absent, expired, or if `force_refresh`.
+async fn get_provider(force_refresh: bool) -> Result<Provider> {
+    let cache_duration = web_config().OIDC_JWKS_CACHE;
+
+    if !force_refresh {
+        let cached = provider_cache().read().await;
+        if let Some(provider) = cached
+            .as_ref()
+            .filter(|provider| provider.fetched_on.elapsed() < cache_duration)
+        {
+            return Ok(provider.clone());
+        }
+    }
+
+    let provider = fetch_provider().await?;
+    *provider_cache().write().await = Some(provider.clone());
+
+    Ok(provider)
+}
+
+async fn fetch_provider() -> Result<Provider> {
+    let issuer_url = web_config()
+        .OIDC_ISSUER_URL
+        .as_deref()
+        .ok_or(Error::OidcNotConfigured)?;
+    let issuer_url = issuer_url.trim_end_matches('/');
+    debug!("{:<12} - fetch provider {issuer_url}", "OIDC");
+
+    let metadata: ProviderMetadata = http_client()
+        .get(format!("{issuer_url}/.well-known/openid-configuration"))
+        .headers(trace_headers())
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    // -- The discovery document must be the one of the configured issuer.
+    if metadata.issuer.trim_end_matches('/') != issuer_url {
+        return Err(Error::IssuerNotMatching {
+            expected: issuer_url.to_string(),
+            actual: metadata.issuer,
+        });
+    }
+
+    let jwks: JwkSet = http_client()
+        .get(&metadata.jwks_uri)
+        .headers(trace_headers())
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    Ok(Provider {
+        metadata,
+        jwks,
+        fetched_on: Instant::now(),
+    })
+}
+// endregion: --- Provider Cache
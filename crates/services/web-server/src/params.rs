@@ -1,8 +1,10 @@
 use std::{fmt, str::FromStr};
 
 use serde::{de, Deserialize, Deserializer};
+use utoipa::IntoParams;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 #[allow(dead_code)]
 pub struct PaginationParams {
     #[serde(default, deserialize_with = "empty_string_as_none")]
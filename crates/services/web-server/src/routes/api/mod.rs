@@ -7,3 +7,7 @@ pub fn route(mm: ModelManager) -> Router {
     let routes = Router::new();
     routes.nest("/api", v1::route(mm))
 }
+
+pub fn openapi() -> utoipa::openapi::OpenApi {
+    utoipa::openapi::OpenApi::default().nest("/api", v1::openapi())
+}
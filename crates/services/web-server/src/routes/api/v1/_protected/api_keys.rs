@@ -0,0 +1,212 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    routing::{delete, get},
+    Json, Router,
+};
+use lib_auth::api_key;
+use lib_surrealdb::model::{
+    api_keys::{bmc::ApiKeysBmc, ApiKeys, ApiKeysForCreate},
+    role_policies::{bmc::RolePoliciesBmc, Permission},
+    ModelManager,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tracing::debug;
+use utoipa::{IntoParams, ToSchema};
+use validator::Validate;
+
+use crate::{
+    middlewares::{
+        auth::CtxW,
+        permission::{RequirePermission, UsersRead},
+    },
+    routes::{Error, ErrorBody, Result, ValidJson},
+};
+
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+struct ApiKeysForCreatePayload {
+    #[validate(length(min = 1, max = 100))]
+    name: String,
+    /// The permissions of the key, e.g. `tasks:read`, within those of the user.
+    #[serde(default)]
+    #[schema(value_type = Vec<String>)]
+    scopes: Vec<Permission>,
+    /// Days before the key expires, it never expires when not set.
+    expire_days: Option<i64>,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Path)]
+struct PageParams {
+    key_id: String,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Path)]
+struct UserPageParams {
+    user_id: String,
+}
+
+pub fn route(mm: ModelManager) -> Router {
+    Router::new()
+        .route(
+            "/api_keys",
+            get(list_api_keys_handler).post(create_api_keys_handler),
+        )
+        .route("/api_keys/:key_id", delete(revoke_api_keys_handler))
+        .route("/users/:user_id/api_keys", get(list_user_api_keys_handler))
+        .with_state(mm)
+}
+
+// region:    --- Api Keys
+#[utoipa::path(
+    get,
+    path = "/api_keys",
+    tag = "api_keys",
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 200, description = "The api keys of the ctx user, without their secret."),
+        (status = "4XX", response = ErrorBody),
+        (status = "5XX", response = ErrorBody),
+    )
+)]
+async fn list_api_keys_handler(State(mm): State<ModelManager>, ctxw: CtxW) -> Result<Json<Value>> {
+    debug!("{:<12} - list_api_keys_handler", "HANDLER");
+    let ctx = ctxw.0;
+    let user_id_from_ctx = ctx.user_id().ok_or(Error::UserIdInCtxNotFound)?;
+
+    let api_keys = ApiKeysBmc::list_by_user(&ctx, &mm, user_id_from_ctx).await?;
+
+    // -- Create the success body.
+    let body = Json(json!(api_keys));
+
+    Ok(body)
+}
+
+/// Create an api key for the ctx user. The key is only returned by this call.
+#[utoipa::path(
+    post,
+    path = "/api_keys",
+    tag = "api_keys",
+    request_body = ApiKeysForCreatePayload,
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 201, description = "The `api_key` created, and its `key`, which is only returned by this call."),
+        (status = "4XX", response = ErrorBody),
+        (status = "5XX", response = ErrorBody),
+    )
+)]
+async fn create_api_keys_handler(
+    State(mm): State<ModelManager>,
+    ctxw: CtxW,
+    ValidJson(payload): ValidJson<ApiKeysForCreatePayload>,
+) -> Result<(StatusCode, Json<Value>)> {
+    debug!("{:<12} - create_api_keys_handler", "HANDLER");
+    let ctx = ctxw.0;
+
+    // -- An api key cannot create other keys.
+    if ctx.api_key_id().is_some() {
+        return Err(Error::YourUserNotAuthorize);
+    }
+    if ctx.impersonator_id().is_some() {
+        return Err(Error::ImpersonationNotAllowed);
+    }
+
+    if payload
+        .expire_days
+        .is_some_and(|expire_days| expire_days < 1)
+    {
+        return Err(Error::ApiKeyExpireDaysInvalid);
+    }
+
+    let secret = api_key::generate_secret();
+    let api_keys_for_create = ApiKeysForCreate {
+        name: payload.name,
+        secret_hash: api_key::hash_secret(&secret)?,
+        scopes: payload.scopes,
+        expire_days: payload.expire_days,
+    };
+
+    let api_key_get = ApiKeysBmc::create(&ctx, &mm, api_keys_for_create).await?;
+    let key = api_key::to_api_key(&api_key_get.id.id.to_raw(), &secret);
+
+    // -- Create the success body.
+    let body = Json(json!({
+        "api_key": api_key_get,
+        "key": key,
+    }));
+
+    Ok((StatusCode::CREATED, body))
+}
+
+/// Revoke an api key of the ctx user, or of any user with `users:write`.
+///
+/// The key of another user is not found, as an unknown key.
+#[utoipa::path(
+    delete,
+    path = "/api_keys/{key_id}",
+    tag = "api_keys",
+    params(PageParams),
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 204, description = "The api key is revoked."),
+        (status = "4XX", response = ErrorBody),
+        (status = "5XX", response = ErrorBody),
+    )
+)]
+async fn revoke_api_keys_handler(
+    State(mm): State<ModelManager>,
+    ctxw: CtxW,
+    Path(PageParams { key_id }): Path<PageParams>,
+) -> Result<StatusCode> {
+    debug!("{:<12} - revoke_api_keys_handler", "HANDLER");
+    let ctx = ctxw.0;
+    let user_id_from_ctx = ctx.user_id().ok_or(Error::UserIdInCtxNotFound)?;
+
+    // -- Someone else's key is not found either, unless with `users:write`,
+    //    so its id does not tell it exists.
+    let mut api_key =
+        ApiKeysBmc::get_by_user::<ApiKeys>(&ctx, &mm, user_id_from_ctx, &key_id).await?;
+    if api_key.is_none()
+        && RolePoliciesBmc::has_permission(&ctx, &mm, Permission::UsersWrite).await?
+    {
+        api_key = ApiKeysBmc::get::<ApiKeys>(&ctx, &mm, &key_id).await?;
+    }
+    let api_key = api_key.ok_or(Error::DataNotFound)?;
+
+    if api_key.revoked_on.is_none() {
+        ApiKeysBmc::revoke(&ctx, &mm, &key_id).await?;
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    get,
+    path = "/users/{user_id}/api_keys",
+    tag = "api_keys",
+    params(UserPageParams),
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 200, description = "The api keys of the user, without their secret. Needs `users:read`."),
+        (status = "4XX", response = ErrorBody),
+        (status = "5XX", response = ErrorBody),
+    )
+)]
+async fn list_user_api_keys_handler(
+    State(mm): State<ModelManager>,
+    ctxw: RequirePermission<UsersRead>,
+    Path(UserPageParams { user_id }): Path<UserPageParams>,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - list_user_api_keys_handler", "HANDLER");
+    let ctx = ctxw.0;
+
+    let api_keys = ApiKeysBmc::list_by_user(&ctx, &mm, &user_id).await?;
+
+    // -- Create the success body.
+    let body = Json(json!(api_keys));
+
+    Ok(body)
+}
+// endregion: --- Api Keys
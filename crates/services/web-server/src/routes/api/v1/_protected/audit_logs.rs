@@ -0,0 +1,58 @@
+use axum::{
+    extract::{Path, State},
+    routing::get,
+    Json, Router,
+};
+use lib_surrealdb::model::{audit_logs::bmc::AuditLogsBmc, ModelManager};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tracing::debug;
+use utoipa::IntoParams;
+
+use crate::{
+    middlewares::permission::{RequirePermission, UsersRead},
+    routes::{ErrorBody, Result},
+};
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Path)]
+struct PageParams {
+    user_id: String,
+}
+
+pub fn route(mm: ModelManager) -> Router {
+    Router::new()
+        .route("/users/:user_id/audit_logs", get(list_audit_logs_handler))
+        .with_state(mm)
+}
+
+// region:    --- Audit Logs
+/// List the audited actions done by or on the user.
+#[utoipa::path(
+    get,
+    path = "/users/{user_id}/audit_logs",
+    tag = "audit_logs",
+    params(PageParams),
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 200, description = "The audit logs of the user, the latest first. Needs `users:read`."),
+        (status = "4XX", response = ErrorBody),
+        (status = "5XX", response = ErrorBody),
+    )
+)]
+async fn list_audit_logs_handler(
+    State(mm): State<ModelManager>,
+    ctxw: RequirePermission<UsersRead>,
+    Path(PageParams { user_id }): Path<PageParams>,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - list_audit_logs_handler", "HANDLER");
+    let ctx = ctxw.0;
+
+    let audit_logs = AuditLogsBmc::list_by_user(&ctx, &mm, &user_id).await?;
+
+    // -- Create the success body.
+    let body = Json(json!(audit_logs));
+
+    Ok(body)
+}
+// endregion: --- Audit Logs
@@ -0,0 +1,109 @@
+use std::str::FromStr;
+
+use axum::{
+    extract::{Path, State},
+    routing::post,
+    Json, Router,
+};
+use lib_auth::{auth_config, token};
+use lib_surrealdb::model::{
+    audit_logs::{bmc::AuditLogsBmc, AuditAction, AuditLogsForCreate},
+    role_policies::{bmc::RolePoliciesBmc, Role},
+    users::{bmc::UsersBmc, UsersForLogin},
+    ModelManager,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tracing::debug;
+use utoipa::IntoParams;
+
+use crate::{
+    middlewares::permission::{RequirePermission, UsersImpersonate},
+    routes::{Error, ErrorBody, Result},
+};
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Path)]
+struct PageParams {
+    user_id: String,
+}
+
+pub fn route(mm: ModelManager) -> Router {
+    Router::new()
+        .route("/users/:user_id/impersonate", post(impersonate_handler))
+        .with_state(mm)
+}
+
+// region:    --- Impersonate
+/// Issue a short-lived jwt to act as the user, carrying the ctx user as its impersonator.
+#[utoipa::path(
+    post,
+    path = "/users/{user_id}/impersonate",
+    tag = "users",
+    params(PageParams),
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 200, description = "The `jwt` to act as the user. Needs `users:impersonate`."),
+        (status = "4XX", response = ErrorBody),
+        (status = "5XX", response = ErrorBody),
+    )
+)]
+async fn impersonate_handler(
+    State(mm): State<ModelManager>,
+    ctxw: RequirePermission<UsersImpersonate>,
+    Path(PageParams { user_id }): Path<PageParams>,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - impersonate_handler", "HANDLER");
+    let ctx = ctxw.0;
+    let user_id_from_ctx = ctx.user_id().ok_or(Error::UserIdInCtxNotFound)?;
+
+    // -- Only by a login session, and not to impersonate again.
+    if ctx.api_key_id().is_some() || ctx.impersonator_id().is_some() || user_id_from_ctx == user_id
+    {
+        return Err(Error::ImpersonationNotAllowed);
+    }
+
+    let user = UsersBmc::get::<UsersForLogin>(&ctx, &mm, &user_id)
+        .await?
+        .ok_or(Error::DataNotFound)?;
+
+    // -- The user cannot have a permission the impersonator has not.
+    let role = Role::from_str(&user.role)?;
+    for permission in RolePoliciesBmc::permissions(&ctx, &mm, role).await? {
+        if !RolePoliciesBmc::has_permission(&ctx, &mm, permission).await? {
+            return Err(Error::ImpersonationNotAllowed);
+        }
+    }
+
+    let jwt =
+        token::encode_impersonation_jwt(&user_id, user_id_from_ctx, user.token_salt.as_ref())?;
+
+    AuditLogsBmc::create(
+        &ctx,
+        &mm,
+        AuditLogsForCreate {
+            action: AuditAction::ImpersonationStart,
+            user_id: Some(user_id.clone()),
+            detail: None,
+        },
+    )
+    .await?;
+
+    // -- Create the success body.
+    let body = Json(json!({
+        "result": {
+            "success": true,
+        },
+        "data": {
+            "id": user_id,
+            "email": user.username,
+            "role": user.role,
+        },
+        "impersonator_id": user_id_from_ctx,
+        "expires_in": auth_config().IMPERSONATION_TOKEN_DURATION.as_secs(),
+        "jwt": jwt
+    }));
+
+    Ok(body)
+}
+// endregion: --- Impersonate
@@ -0,0 +1,118 @@
+use std::net::SocketAddr;
+
+use axum::{
+    extract::{ConnectInfo, State},
+    http::HeaderMap,
+    routing::put,
+    Json, Router,
+};
+use lib_auth::pwd::{self, ContentToHash};
+use lib_surrealdb::model::{
+    login_attempts::bmc::LoginAttemptsBmc,
+    sessions::bmc::SessionsBmc,
+    users::{bmc::UsersBmc, Users, UsersForLogin},
+    ModelManager,
+};
+use serde::Deserialize;
+use serde_json::Value;
+use tracing::debug;
+use utoipa::ToSchema;
+
+use crate::{
+    config::web_config,
+    middlewares::auth::CtxW,
+    routes::{
+        api::v1::login::{
+            check_not_locked, login_success_body, record_failure, session_for_create,
+        },
+        Error, ErrorBody, Result,
+    },
+};
+
+#[derive(Debug, Deserialize, ToSchema)]
+struct MePasswordPayload {
+    current_password: String,
+    new_password: String,
+}
+
+pub fn route(mm: ModelManager) -> Router {
+    Router::new()
+        .route("/me/password", put(update_me_pwd_handler))
+        .with_state(mm)
+}
+
+// region:    --- Me
+/// Change the password of the ctx user, who must prove the current one.
+///
+/// Every session of the user is logged out, the body carries a new jwt for this client.
+#[utoipa::path(
+    put,
+    path = "/me/password",
+    tag = "users",
+    request_body = MePasswordPayload,
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "The login body, with the new `jwt` of this client."),
+        (status = "4XX", response = ErrorBody),
+        (status = "5XX", response = ErrorBody),
+    )
+)]
+async fn update_me_pwd_handler(
+    State(mm): State<ModelManager>,
+    ConnectInfo(client_addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    ctxw: CtxW,
+    Json(payload): Json<MePasswordPayload>,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - update_me_pwd_handler", "HANDLER");
+    let ctx = ctxw.0;
+
+    // -- Only by the user, not by an api key or an impersonator.
+    if ctx.api_key_id().is_some() {
+        return Err(Error::YourUserNotAuthorize);
+    }
+    if ctx.impersonator_id().is_some() {
+        return Err(Error::ImpersonationNotAllowed);
+    }
+    let user_id = ctx.user_id().ok_or(Error::UserIdInCtxNotFound)?;
+
+    let MePasswordPayload {
+        current_password,
+        new_password,
+    } = payload;
+
+    let Users {
+        username,
+        email,
+        password,
+        password_salt,
+        ..
+    } = UsersBmc::get(&ctx, &mm, user_id)
+        .await?
+        .ok_or(Error::DataNotFound)?;
+
+    // -- Validate the current password, its wrong guesses lock as the login ones.
+    let username_key = LoginAttemptsBmc::username_key(&username);
+    check_not_locked(&ctx, &mm, &[&username_key]).await?;
+
+    let to_hash = ContentToHash::new(current_password, uuid::Uuid::from(password_salt));
+    if pwd::validate_pwd(to_hash, password).await.is_err() {
+        let threshold = web_config().LOGIN_LOCKOUT_USERNAME_THRESHOLD;
+        record_failure(&ctx, &mm, &username_key, threshold).await?;
+        return Err(Error::PwdCurrentNotMatching);
+    }
+    LoginAttemptsBmc::reset(&ctx, &mm, &username_key).await?;
+
+    pwd::validate_pwd_policy(&new_password, &[&username, &email])?;
+
+    // -- A fresh password salt, and a rotated token salt.
+    UsersBmc::reset_pwd(&ctx, &mm, user_id, new_password).await?;
+    SessionsBmc::revoke_by_user(&ctx, &mm, user_id).await?;
+
+    let user: UsersForLogin = UsersBmc::get(&ctx, &mm, user_id)
+        .await?
+        .ok_or(Error::DataNotFound)?;
+
+    login_success_body(&ctx, &mm, user, session_for_create(&headers, client_addr)).await
+}
+// endregion: --- Me
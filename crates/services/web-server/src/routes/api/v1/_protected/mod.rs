@@ -1,10 +1,18 @@
 use axum::{middleware::from_fn_with_state, Router};
 use lib_surrealdb::model::ModelManager;
+use utoipa::OpenApi;
 
-use crate::middlewares::mw_ctx_resolve;
+use crate::middlewares::{mw_ctx_resolve, mw_rate_limit, RateLimiter};
 
+mod api_keys;
+mod audit_logs;
+mod impersonate;
+mod me;
 mod register;
+mod role_policies;
+mod sessions;
 mod tasks;
+pub(crate) mod totp;
 mod users;
 
 pub fn route(mm: ModelManager) -> Router {
@@ -12,6 +20,54 @@ pub fn route(mm: ModelManager) -> Router {
     route
         .merge(users::route(mm.clone()))
         .merge(register::route(mm.clone()))
+        .merge(role_policies::route(mm.clone()))
         .merge(tasks::route(mm.clone()))
+        .merge(totp::route(mm.clone()))
+        .merge(api_keys::route(mm.clone()))
+        .merge(sessions::route(mm.clone()))
+        .merge(impersonate::route(mm.clone()))
+        .merge(audit_logs::route(mm.clone()))
+        .merge(me::route(mm.clone()))
+        // -- Inside `mw_ctx_resolve`, so the quota is per user.
+        .route_layer(from_fn_with_state(RateLimiter::api(), mw_rate_limit))
         .route_layer(from_fn_with_state(mm, mw_ctx_resolve))
 }
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    users::list_users_handler,
+    users::create_user_handler,
+    users::import_users_handler,
+    users::get_users_handler,
+    users::update_user_handler,
+    users::delete_user_handler,
+    users::update_pwd_user_handler,
+    users::unlock_user_handler,
+    users::reset_2fa_user_handler,
+    users::update_user_by_admin_handler,
+    register::api_register_handler,
+    role_policies::list_role_policies_handler,
+    role_policies::update_role_policies_handler,
+    role_policies::update_role_permissions_handler,
+    tasks::list_tasks_handler,
+    tasks::create_tasks_handler,
+    tasks::get_tasks_handler,
+    tasks::update_tasks_handler,
+    tasks::delete_tasks_handler,
+    totp::enroll_totp_handler,
+    totp::confirm_totp_handler,
+    api_keys::list_api_keys_handler,
+    api_keys::create_api_keys_handler,
+    api_keys::revoke_api_keys_handler,
+    api_keys::list_user_api_keys_handler,
+    sessions::list_sessions_handler,
+    sessions::revoke_sessions_handler,
+    impersonate::impersonate_handler,
+    audit_logs::list_audit_logs_handler,
+    me::update_me_pwd_handler,
+))]
+struct ApiDoc;
+
+pub fn openapi() -> utoipa::openapi::OpenApi {
+    ApiDoc::openapi()
+}
@@ -1,4 +1,5 @@
 use axum::{extract::State, http::StatusCode, routing::post, Json, Router};
+use lib_auth::pwd;
 use lib_surrealdb::model::{
     users::{bmc::UsersBmc, UsersForCreate},
     ModelManager,
@@ -6,19 +7,30 @@ use lib_surrealdb::model::{
 use serde::Deserialize;
 use serde_json::{json, Value};
 use tracing::debug;
+use utoipa::ToSchema;
+use validator::Validate;
 
 use crate::{
-    middlewares::auth::CtxW,
-    routes::{error::Result, Error},
+    middlewares::permission::{RequirePermission, UsersWrite},
+    routes::{
+        api::v1::verify_email::send_verification_email, error::Result, validation::validate_title,
+        ErrorBody, ValidJson,
+    },
 };
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema, Validate)]
 struct RegisterPayload {
+    #[validate(email)]
     username: String,
+    #[validate(email)]
     email: String,
+    #[validate(custom(function = "validate_title"))]
     title: String,
+    #[validate(length(min = 1, max = 100))]
     firstname: String,
+    #[validate(length(max = 100))]
     middlename: Option<String>,
+    #[validate(length(min = 1, max = 100))]
     lastname: String,
     password: String,
 }
@@ -29,21 +41,27 @@ pub fn route(mm: ModelManager) -> Router {
         .with_state(mm)
 }
 
+#[utoipa::path(
+    post,
+    path = "/register",
+    tag = "users",
+    request_body = RegisterPayload,
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 201, description = "The user created, who is mailed a verification link. Needs `users:write`."),
+        (status = "4XX", response = ErrorBody),
+        (status = "5XX", response = ErrorBody),
+    )
+)]
 async fn api_register_handler(
     State(mm): State<ModelManager>,
-    ctxw: CtxW,
-    Json(payload): Json<RegisterPayload>,
+    ctxw: RequirePermission<UsersWrite>,
+    ValidJson(payload): ValidJson<RegisterPayload>,
 ) -> Result<(StatusCode, Json<Value>)> {
     debug!("{:<12} - api_register_handler", "HANLDER");
     // let root_ctx = Ctx::root_ctx();
     let ctx = ctxw.0;
 
-    // check authorize admin
-    let user_id = UsersBmc::is_admin(&ctx, &mm).await?;
-    if user_id == false {
-        return Err(Error::YourUserNotAuthorize);
-    }
-
     let RegisterPayload {
         username,
         email,
@@ -54,9 +72,11 @@ async fn api_register_handler(
         password,
     } = payload;
 
+    pwd::validate_pwd_policy(&password, &[&username, &email])?;
+
     let user_info_for_create = UsersForCreate {
         username,
-        email,
+        email: email.clone(),
         title,
         firstname,
         middlename,
@@ -66,6 +86,10 @@ async fn api_register_handler(
 
     let user_record = UsersBmc::create(&ctx, &mm, user_info_for_create, false).await?;
 
+    // -- Ask the new user to verify the email.
+    let user_id = user_record.id.id.to_raw();
+    send_verification_email(&ctx, &mm, &user_id, &email).await?;
+
     let body = Json(json!(user_record));
 
     Ok((StatusCode::CREATED, body))
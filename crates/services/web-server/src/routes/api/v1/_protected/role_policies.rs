@@ -0,0 +1,144 @@
+use axum::{
+    extract::{Path, State},
+    routing::{get, put},
+    Json, Router,
+};
+use std::str::FromStr;
+
+use lib_surrealdb::model::{
+    role_policies::{bmc::RolePoliciesBmc, Permission, Role},
+    ModelManager,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tracing::debug;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{
+    middlewares::permission::{RequirePermission, RolesRead, RolesWrite},
+    routes::{ErrorBody, Result},
+};
+
+#[derive(Debug, Deserialize, ToSchema)]
+struct RolePoliciesForUpdatePayload {
+    require_2fa: bool,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+struct RolePermissionsForUpdatePayload {
+    /// The permissions, as `<resource>:<action>`, e.g. `users:read`.
+    #[schema(value_type = Vec<String>)]
+    permissions: Vec<Permission>,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Path)]
+struct PageParams {
+    role: String,
+}
+
+pub fn route(mm: ModelManager) -> Router {
+    Router::new()
+        .route("/role_policies", get(list_role_policies_handler))
+        .route("/role_policies/:role", put(update_role_policies_handler))
+        .route(
+            "/role_policies/:role/permissions",
+            put(update_role_permissions_handler),
+        )
+        .with_state(mm)
+}
+
+// region:    --- Role Policies
+#[utoipa::path(
+    get,
+    path = "/role_policies",
+    tag = "role_policies",
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 200, description = "The policies of every role. Needs `roles:read`."),
+        (status = "4XX", response = ErrorBody),
+        (status = "5XX", response = ErrorBody),
+    )
+)]
+async fn list_role_policies_handler(
+    State(mm): State<ModelManager>,
+    ctxw: RequirePermission<RolesRead>,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - list_role_policies_handler", "HANDLER");
+    let ctx = ctxw.0;
+
+    let role_policies = RolePoliciesBmc::list(&ctx, &mm).await?;
+
+    // -- Create the success body.
+    let body = Json(json!(role_policies));
+
+    Ok(body)
+}
+
+#[utoipa::path(
+    put,
+    path = "/role_policies/{role}",
+    tag = "role_policies",
+    request_body = RolePoliciesForUpdatePayload,
+    params(PageParams),
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 200, description = "The policies of the role. Needs `roles:write`."),
+        (status = "4XX", response = ErrorBody),
+        (status = "5XX", response = ErrorBody),
+    )
+)]
+async fn update_role_policies_handler(
+    State(mm): State<ModelManager>,
+    ctxw: RequirePermission<RolesWrite>,
+    Path(PageParams { role }): Path<PageParams>,
+    Json(payload): Json<RolePoliciesForUpdatePayload>,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - update_role_policies_handler", "HANDLER");
+    let ctx = ctxw.0;
+
+    let role = Role::from_str(&role)?;
+
+    let role_policies =
+        RolePoliciesBmc::set_require_2fa(&ctx, &mm, role, payload.require_2fa).await?;
+
+    // -- Create the success body.
+    let body = Json(json!(role_policies));
+
+    Ok(body)
+}
+
+/// Replace the permissions granted to a role, e.g., `["users:read", "tasks:read"]`.
+#[utoipa::path(
+    put,
+    path = "/role_policies/{role}/permissions",
+    tag = "role_policies",
+    request_body = RolePermissionsForUpdatePayload,
+    params(PageParams),
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 200, description = "The policies of the role. Needs `roles:write`."),
+        (status = "4XX", response = ErrorBody),
+        (status = "5XX", response = ErrorBody),
+    )
+)]
+async fn update_role_permissions_handler(
+    State(mm): State<ModelManager>,
+    ctxw: RequirePermission<RolesWrite>,
+    Path(PageParams { role }): Path<PageParams>,
+    Json(payload): Json<RolePermissionsForUpdatePayload>,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - update_role_permissions_handler", "HANDLER");
+    let ctx = ctxw.0;
+
+    let role = Role::from_str(&role)?;
+
+    let role_policies =
+        RolePoliciesBmc::set_permissions(&ctx, &mm, role, payload.permissions).await?;
+
+    // -- Create the success body.
+    let body = Json(json!(role_policies));
+
+    Ok(body)
+}
+// endregion: --- Role Policies
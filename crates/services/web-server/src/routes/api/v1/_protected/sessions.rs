@@ -0,0 +1,195 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    routing::{delete, get},
+    Json, Router,
+};
+use lib_surrealdb::{
+    ctx::Ctx,
+    model::{
+        role_policies::{bmc::RolePoliciesBmc, Permission},
+        sessions::bmc::SessionsBmc,
+        ModelManager,
+    },
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tracing::debug;
+use utoipa::IntoParams;
+
+use crate::{
+    middlewares::auth::CtxW,
+    routes::{Error, ErrorBody, Result},
+};
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Path)]
+struct PageParams {
+    user_id: String,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Path)]
+struct SessionPageParams {
+    user_id: String,
+    session_id: String,
+}
+
+pub fn route(mm: ModelManager) -> Router {
+    Router::new()
+        .route("/users/:user_id/sessions", get(list_sessions_handler))
+        .route(
+            "/users/:user_id/sessions/:session_id",
+            delete(revoke_sessions_handler),
+        )
+        .with_state(mm)
+}
+
+// region:    --- Sessions
+/// List the active sessions of the user, flagging the one of the ctx.
+#[utoipa::path(
+    get,
+    path = "/users/{user_id}/sessions",
+    tag = "sessions",
+    params(PageParams),
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 200, description = "The active sessions of the user, `current` being the one of the request. Needs `users:read`, unless on its own user."),
+        (status = "4XX", response = ErrorBody),
+        (status = "5XX", response = ErrorBody),
+    )
+)]
+async fn list_sessions_handler(
+    State(mm): State<ModelManager>,
+    ctxw: CtxW,
+    Path(PageParams { user_id }): Path<PageParams>,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - list_sessions_handler", "HANDLER");
+    let ctx = ctxw.0;
+
+    check_permission(&ctx, &mm, &user_id, Permission::UsersRead).await?;
+
+    let sessions = SessionsBmc::list_by_user(&ctx, &mm, &user_id).await?;
+
+    // -- Create the success body.
+    let sessions: Vec<Value> = sessions
+        .into_iter()
+        .map(|session| {
+            let current = ctx.session_id() == Some(session.id.id.to_raw().as_str());
+            let mut session = json!(session);
+            session["current"] = json!(current);
+            session
+        })
+        .collect();
+    let body = Json(json!(sessions));
+
+    Ok(body)
+}
+
+/// Revoke a session of the user, its jwt is rejected from now on.
+#[utoipa::path(
+    delete,
+    path = "/users/{user_id}/sessions/{session_id}",
+    tag = "sessions",
+    params(SessionPageParams),
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 204, description = "The session is revoked. Needs `users:write`, unless on its own user, and not while impersonating."),
+        (status = "4XX", response = ErrorBody),
+        (status = "5XX", response = ErrorBody),
+    )
+)]
+async fn revoke_sessions_handler(
+    State(mm): State<ModelManager>,
+    ctxw: CtxW,
+    Path(SessionPageParams {
+        user_id,
+        session_id,
+    }): Path<SessionPageParams>,
+) -> Result<StatusCode> {
+    debug!("{:<12} - revoke_sessions_handler", "HANDLER");
+    let ctx = ctxw.0;
+
+    // -- Not while impersonating, the sessions are the user's own.
+    if ctx.impersonator_id().is_some() {
+        return Err(Error::ImpersonationNotAllowed);
+    }
+
+    check_permission(&ctx, &mm, &user_id, Permission::UsersWrite).await?;
+
+    let session = SessionsBmc::get(&ctx, &mm, &session_id)
+        .await?
+        .filter(|session| session.user.id.to_raw() == user_id)
+        .ok_or(Error::DataNotFound)?;
+
+    if session.revoked_on.is_none() {
+        SessionsBmc::revoke(&ctx, &mm, &session_id).await?;
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Check the ctx has `permission`, unless on its own user (not by an api key).
+async fn check_permission(
+    ctx: &Ctx,
+    mm: &ModelManager,
+    user_id: &str,
+    permission: Permission,
+) -> Result<()> {
+    let user_id_from_ctx = ctx.user_id().ok_or(Error::UserIdInCtxNotFound)?;
+
+    if !(user_id_from_ctx == user_id && ctx.api_key_id().is_none())
+        && !RolePoliciesBmc::has_permission(ctx, mm, permission).await?
+    {
+        return Err(Error::YourUserNotAuthorize);
+    }
+
+    Ok(())
+}
+// endregion: --- Sessions
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    type Error = Box<dyn std::error::Error>;
+    type Result<T> = core::result::Result<T, Error>; // For tests.
+
+    use lib_surrealdb::{ctx::CtxBuilder, model::role_policies::Role};
+    use serial_test::serial;
+
+    use super::*;
+
+    #[serial]
+    #[tokio::test]
+    async fn test_revoke_sessions_err_impersonating() -> Result<()> {
+        // -- Setup & Fixtures
+        //    (an admin impersonating demo1)
+        let mm = ModelManager::new().await?;
+        let fx_user_id = "iR1f8i7Wg7jipR3uhDhJ";
+        let fx_ctx = Ctx::new(
+            CtxBuilder::new(fx_user_id)
+                .roles(vec![Role::User])
+                .impersonator_id("fx-impersonator-id"),
+        )?;
+
+        // -- Exec
+        let res = revoke_sessions_handler(
+            State(mm),
+            CtxW(fx_ctx),
+            Path(SessionPageParams {
+                user_id: fx_user_id.to_string(),
+                session_id: "fx-session-id".to_string(),
+            }),
+        )
+        .await;
+
+        // -- Check
+        assert!(matches!(
+            res,
+            Err(crate::routes::Error::ImpersonationNotAllowed)
+        ));
+
+        Ok(())
+    }
+}
+// endregion: --- Tests
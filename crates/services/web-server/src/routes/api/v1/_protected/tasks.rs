@@ -4,15 +4,20 @@ use axum::{
     routing::get,
     Json, Router,
 };
-use lib_surrealdb::model::{users::bmc::UsersBmc, ModelManager};
+use lib_surrealdb::model::ModelManager;
 use serde::Deserialize;
 use serde_json::Value;
 use tracing::debug;
+use utoipa::IntoParams;
 
-use crate::routes::{Error, Result};
-use crate::{middlewares::auth::CtxW, params::PaginationParams};
+use crate::routes::{Error, ErrorBody, Result};
+use crate::{
+    middlewares::permission::{RequirePermission, TasksDelete, TasksRead, TasksWrite},
+    params::PaginationParams,
+};
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Path)]
 struct PageParams {
     task_id: String,
 }
@@ -30,37 +35,49 @@ pub fn route(mm: ModelManager) -> Router {
 }
 
 // region:    --- Tasks
+#[utoipa::path(
+    get,
+    path = "/tasks/{task_id}",
+    tag = "tasks",
+    params(PageParams),
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 200, description = "The task. Needs `tasks:read`."),
+        (status = "4XX", response = ErrorBody),
+        (status = "5XX", response = ErrorBody),
+    )
+)]
 async fn get_tasks_handler(
     State(mm): State<ModelManager>,
-    ctxw: CtxW,
+    ctxw: RequirePermission<TasksRead>,
     Path(PageParams { task_id }): Path<PageParams>,
 ) -> Result<Json<Value>> {
     debug!("{:<12} - get_task_handler", "HANLDER");
     let ctx = ctxw.0;
 
-    // check authorize admin
-    let is_authorized = UsersBmc::is_admin(&ctx, &mm).await?;
-    if !is_authorized {
-        return Err(Error::YourUserNotAuthorize);
-    }
-
     todo!()
 }
 
+#[utoipa::path(
+    get,
+    path = "/tasks",
+    tag = "tasks",
+    params(PaginationParams),
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 200, description = "A page of the tasks. Needs `tasks:read`."),
+        (status = "4XX", response = ErrorBody),
+        (status = "5XX", response = ErrorBody),
+    )
+)]
 async fn list_tasks_handler(
     State(mm): State<ModelManager>,
-    ctxw: CtxW,
+    ctxw: RequirePermission<TasksRead>,
     Query(params): Query<PaginationParams>,
 ) -> Result<Json<Value>> {
     debug!("{:<12} - list_tasks_handler", "HANLDER");
     let ctx = ctxw.0;
 
-    // check authorize admin
-    let is_authorized = UsersBmc::is_admin(&ctx, &mm).await?;
-    if !is_authorized {
-        return Err(Error::YourUserNotAuthorize);
-    }
-
     // -- Get limit and offset from Query Params
     let limit = params.limit;
     let offset = params.offset;
@@ -69,43 +86,66 @@ async fn list_tasks_handler(
     todo!()
 }
 
+#[utoipa::path(
+    post,
+    path = "/tasks",
+    tag = "tasks",
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 201, description = "The task created. Needs `tasks:write`."),
+        (status = "4XX", response = ErrorBody),
+        (status = "5XX", response = ErrorBody),
+    )
+)]
 async fn create_tasks_handler(
     State(mm): State<ModelManager>,
-    ctxw: CtxW,
+    ctxw: RequirePermission<TasksWrite>,
     // Json(payload): Json<UsersForCreatePayload>,
 ) -> Result<(StatusCode, Json<Value>)> {
     debug!("{:<12} - create_tasks_handler", "HANDLER");
     let ctx = ctxw.0;
 
-    // check authorize admin
-    let is_authorized = UsersBmc::is_admin(&ctx, &mm).await?;
-    if !is_authorized {
-        return Err(Error::YourUserNotAuthorize);
-    }
-
     todo!()
 }
 
+#[utoipa::path(
+    delete,
+    path = "/tasks/{task_id}",
+    tag = "tasks",
+    params(PageParams),
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 204, description = "The task is deleted. Needs `tasks:delete`."),
+        (status = "4XX", response = ErrorBody),
+        (status = "5XX", response = ErrorBody),
+    )
+)]
 async fn delete_tasks_handler(
     State(mm): State<ModelManager>,
-    ctxw: CtxW,
+    ctxw: RequirePermission<TasksDelete>,
     Path(PageParams { task_id }): Path<PageParams>,
 ) -> Result<StatusCode> {
     debug!("{:<12} - delete_tasks_handler", "HANDLER");
     let ctx = ctxw.0;
 
-    // check authorize admin
-    let is_authorized = UsersBmc::is_admin(&ctx, &mm).await?;
-    if !is_authorized {
-        return Err(Error::YourUserNotAuthorize);
-    }
-
     todo!()
 }
 
+#[utoipa::path(
+    put,
+    path = "/tasks/{task_id}",
+    tag = "tasks",
+    params(PageParams),
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 200, description = "The task updated. Needs `tasks:write`."),
+        (status = "4XX", response = ErrorBody),
+        (status = "5XX", response = ErrorBody),
+    )
+)]
 async fn update_tasks_handler(
     State(mm): State<ModelManager>,
-    ctxw: CtxW,
+    ctxw: RequirePermission<TasksWrite>,
     Path(PageParams { task_id }): Path<PageParams>,
     // Json(payload): Json<UsersForUpdatePayload>,
 ) -> Result<(StatusCode, Json<Value>)> {
@@ -113,12 +153,6 @@ async fn update_tasks_handler(
     let ctx = ctxw.0;
     let user_id_from_ctx = ctx.user_id().ok_or(Error::UserIdInCtxNotFound)?;
 
-    // check authorize admin
-    let is_authorized = UsersBmc::is_admin(&ctx, &mm).await?;
-    if !is_authorized {
-        return Err(Error::YourUserNotAuthorize);
-    }
-
     todo!()
 }
 
@@ -0,0 +1,192 @@
+use axum::{extract::State, routing::post, Json, Router};
+use lib_auth::totp;
+use lib_surrealdb::{
+    ctx::Ctx,
+    model::{
+        self,
+        users::{bmc::UsersBmc, UsersForTotp},
+        ModelManager,
+    },
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tracing::debug;
+use utoipa::ToSchema;
+
+use crate::{
+    middlewares::auth::CtxW,
+    routes::{Error, ErrorBody, Result},
+};
+
+const RECOVERY_CODE_COUNT: usize = 10;
+
+#[derive(Debug, Deserialize, ToSchema)]
+struct TotpConfirmPayload {
+    code: String,
+}
+
+pub fn route(mm: ModelManager) -> Router {
+    Router::new()
+        .route("/2fa/enroll", post(enroll_totp_handler))
+        .route("/2fa/confirm", post(confirm_totp_handler))
+        .with_state(mm)
+}
+
+// region:    --- Totp
+#[utoipa::path(
+    post,
+    path = "/2fa/enroll",
+    tag = "2fa",
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 200, description = "The `otpauth_uri` and `secret` of the pending second factor, to confirm with `/2fa/confirm`."),
+        (status = "4XX", response = ErrorBody),
+        (status = "5XX", response = ErrorBody),
+    )
+)]
+async fn enroll_totp_handler(State(mm): State<ModelManager>, ctxw: CtxW) -> Result<Json<Value>> {
+    debug!("{:<12} - enroll_totp_handler", "HANDLER");
+    let ctx = ctxw.0;
+    let user = user_from_ctx(&ctx, &mm).await?;
+
+    enroll_totp(&ctx, &mm, &user).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/2fa/confirm",
+    tag = "2fa",
+    request_body = TotpConfirmPayload,
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 200, description = "The second factor is enabled, the body has its `recovery_codes`, shown only once."),
+        (status = "4XX", response = ErrorBody),
+        (status = "5XX", response = ErrorBody),
+    )
+)]
+async fn confirm_totp_handler(
+    State(mm): State<ModelManager>,
+    ctxw: CtxW,
+    Json(payload): Json<TotpConfirmPayload>,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - confirm_totp_handler", "HANDLER");
+    let ctx = ctxw.0;
+    let user = user_from_ctx(&ctx, &mm).await?;
+
+    let recovery_codes = confirm_totp(&ctx, &mm, &user, &payload.code).await?;
+
+    // -- Create the success body.
+    let body = Json(json!({
+        "recovery_codes": recovery_codes,
+    }));
+
+    Ok(body)
+}
+
+/// Generate a new pending secret for `user`, and return its otpauth uri.
+pub(crate) async fn enroll_totp(
+    ctx: &Ctx,
+    mm: &ModelManager,
+    user: &UsersForTotp,
+) -> Result<Json<Value>> {
+    if user.totp_enabled_on.is_some() {
+        return Err(Error::TotpAlreadyEnabled);
+    }
+
+    let secret = totp::generate_secret();
+    UsersBmc::set_totp_pending_secret(ctx, mm, &user.id.id.to_raw(), &secret).await?;
+
+    // -- Create the success body.
+    let body = Json(json!({
+        "otpauth_uri": totp::otpauth_uri(&secret, &user.username),
+        "secret": secret,
+    }));
+
+    Ok(body)
+}
+
+/// Enable the pending secret of `user` with a first code,
+/// and return the recovery codes (shown only once, only their hashes are stored).
+pub(crate) async fn confirm_totp(
+    ctx: &Ctx,
+    mm: &ModelManager,
+    user: &UsersForTotp,
+    code: &str,
+) -> Result<Vec<String>> {
+    let secret = user
+        .totp_pending_secret
+        .as_deref()
+        .ok_or(Error::TotpNotPending)?;
+    let step = totp::validate_totp(secret, code, None).map_err(|_| Error::TotpCodeInvalid)?;
+
+    let recovery_codes = totp::generate_recovery_codes(RECOVERY_CODE_COUNT);
+    let recovery_code_hashes = recovery_codes
+        .iter()
+        .map(|recovery_code| totp::hash_recovery_code(recovery_code))
+        .collect::<totp::Result<Vec<String>>>()?;
+
+    UsersBmc::enable_totp(
+        ctx,
+        mm,
+        &user.id.id.to_raw(),
+        secret,
+        step as i64,
+        recovery_code_hashes,
+    )
+    .await?;
+
+    Ok(recovery_codes)
+}
+
+/// Accept a TOTP code, or else a one-time recovery code, as the second factor of `user`.
+/// Any rejected code is a `TotpCodeInvalid`.
+pub(crate) async fn verify_second_factor(
+    ctx: &Ctx,
+    mm: &ModelManager,
+    user: &UsersForTotp,
+    code: Option<&str>,
+    recovery_code: Option<&str>,
+) -> Result<()> {
+    let user_id = user.id.id.to_raw();
+    let secret = user.totp_secret.as_deref().ok_or(Error::TotpNotEnabled)?;
+
+    let use_result = match (code, recovery_code) {
+        (Some(code), _) => {
+            let last_used_step = user.totp_last_step.map(|step| step as u64);
+            let step = totp::validate_totp(secret, code, last_used_step)
+                .map_err(|_| Error::TotpCodeInvalid)?;
+            UsersBmc::use_totp_step(ctx, mm, &user_id, step as i64).await
+        }
+        (None, Some(recovery_code)) => {
+            let code_hash = totp::hash_recovery_code(recovery_code)?;
+            UsersBmc::use_totp_recovery_code(ctx, mm, &user_id, &code_hash).await
+        }
+        (None, None) => return Err(Error::TotpCodeInvalid),
+    };
+
+    match use_result {
+        Ok(()) => Ok(()),
+        Err(model::Error::TotpCodeAlreadyUsed | model::Error::TotpRecoveryCodeInvalid) => {
+            Err(Error::TotpCodeInvalid)
+        }
+        Err(ex) => Err(ex.into()),
+    }
+}
+
+async fn user_from_ctx(ctx: &Ctx, mm: &ModelManager) -> Result<UsersForTotp> {
+    // -- The second factor is managed by the user, not by an api key.
+    if ctx.api_key_id().is_some() {
+        return Err(Error::YourUserNotAuthorize);
+    }
+    if ctx.impersonator_id().is_some() {
+        return Err(Error::ImpersonationNotAllowed);
+    }
+
+    let user_id = ctx.user_id().ok_or(Error::UserIdInCtxNotFound)?;
+    let user = UsersBmc::get::<UsersForTotp>(ctx, mm, user_id)
+        .await?
+        .ok_or(Error::DataNotFound)?;
+
+    Ok(user)
+}
+// endregion: --- Totp
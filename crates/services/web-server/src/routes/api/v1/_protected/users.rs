@@ -1,62 +1,121 @@
 use axum::{
     extract::{Path, Query, State},
     http::StatusCode,
-    routing::{get, put},
+    routing::{delete, get, post, put},
     Json, Router,
 };
-use lib_surrealdb::model::{
-    users::{
-        bmc::UsersBmc, Users, UsersForCreate, UsersForDelete, UsersForUpdate,
-        UsersForUpdateByAdmin, UsersGet, UsersRecord,
+use lib_auth::pwd;
+use lib_surrealdb::{
+    ctx::Ctx,
+    model::{
+        login_attempts::bmc::LoginAttemptsBmc,
+        role_policies::{bmc::RolePoliciesBmc, Permission},
+        sessions::bmc::SessionsBmc,
+        users::{
+            bmc::UsersBmc, Users, UsersForCreate, UsersForDelete, UsersForImport, UsersForUpdate,
+            UsersForUpdateByAdmin, UsersGet, UsersRecord,
+        },
+        ModelManager,
     },
-    ModelManager,
 };
 use serde::Deserialize;
 use serde_json::{json, Value};
 use tracing::debug;
+use utoipa::{IntoParams, ToSchema};
+use validator::Validate;
 
-use crate::routes::{Error, Result};
-use crate::{middlewares::auth::CtxW, params::PaginationParams};
+use crate::routes::{
+    api::v1::verify_email::send_verification_email,
+    validation::{field_violations, validate_role, validate_title},
+    Error, ErrorBody, Result, ValidJson,
+};
+use crate::{
+    middlewares::{
+        auth::CtxW,
+        permission::{RequirePermission, UsersDelete, UsersRead, UsersWrite},
+    },
+    params::PaginationParams,
+};
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema, Validate)]
 struct UsersForCreatePayload {
+    #[validate(email)]
     username: String,
+    #[validate(email)]
     email: String,
+    #[validate(custom(function = "validate_title"))]
     title: String,
+    #[validate(length(min = 1, max = 100))]
     firstname: String,
+    #[validate(length(max = 100))]
     middlename: Option<String>,
+    #[validate(length(min = 1, max = 100))]
     lastname: String,
     password: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema, Validate)]
+struct UsersForImportPayload {
+    #[validate(email)]
+    username: String,
+    #[validate(email)]
+    email: String,
+    #[serde(default)]
+    email_verified: bool,
+    #[validate(custom(function = "validate_title"))]
+    title: String,
+    #[validate(length(min = 1, max = 100))]
+    firstname: String,
+    #[validate(length(max = 100))]
+    middlename: Option<String>,
+    #[validate(length(min = 1, max = 100))]
+    lastname: String,
+    /// The hash from the other system, tagged with its scheme, e.g. `#legacy-bcrypt#$2b$...`.
+    #[validate(length(min = 1))]
+    password_hash: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema, Validate)]
 struct UsersForUpdatePayload {
+    #[validate(email)]
     pub email: Option<String>,
+    #[validate(custom(function = "validate_title"))]
     pub title: Option<String>,
+    #[validate(length(min = 1, max = 100))]
     pub firstname: Option<String>,
+    #[validate(length(max = 100))]
     pub middlename: Option<String>,
+    #[validate(length(min = 1, max = 100))]
     pub lastname: Option<String>,
     pub image: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema, Validate)]
 struct UsersForUpdateByAdminPayload {
+    #[validate(email)]
     pub username: Option<String>,
+    #[validate(email)]
     pub email: Option<String>,
+    #[validate(custom(function = "validate_title"))]
     pub title: Option<String>,
+    #[validate(length(min = 1, max = 100))]
     pub firstname: Option<String>,
+    #[validate(length(max = 100))]
     pub middlename: Option<String>,
+    #[validate(length(min = 1, max = 100))]
     pub lastname: Option<String>,
     pub image: Option<String>,
+    #[validate(custom(function = "validate_role"))]
     pub role: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 struct UsersForUpdatePasswordPayload {
     pub password: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Path)]
 struct PageParams {
     user_id: String,
 }
@@ -64,6 +123,7 @@ struct PageParams {
 pub fn route(mm: ModelManager) -> Router {
     Router::new()
         .route("/users", get(list_users_handler).post(create_user_handler))
+        .route("/users/import", post(import_users_handler))
         .route(
             "/users/:user_id",
             get(get_users_handler)
@@ -71,6 +131,8 @@ pub fn route(mm: ModelManager) -> Router {
                 .delete(delete_user_handler),
         )
         .route("/users/:user_id/password", put(update_pwd_user_handler))
+        .route("/users/:user_id/unlock", post(unlock_user_handler))
+        .route("/users/:user_id/2fa", delete(reset_2fa_user_handler))
         .route(
             "/users/:user_id/update_by_admin",
             put(update_user_by_admin_handler),
@@ -79,6 +141,18 @@ pub fn route(mm: ModelManager) -> Router {
 }
 
 // region:    --- Users
+#[utoipa::path(
+    get,
+    path = "/users/{user_id}",
+    tag = "users",
+    params(PageParams),
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 200, description = "The user. Needs `users:read`, unless on its own user."),
+        (status = "4XX", response = ErrorBody),
+        (status = "5XX", response = ErrorBody),
+    )
+)]
 async fn get_users_handler(
     State(mm): State<ModelManager>,
     ctxw: CtxW,
@@ -88,9 +162,10 @@ async fn get_users_handler(
     let ctx = ctxw.0;
     let user_id_from_ctx = ctx.user_id().ok_or(Error::UserIdInCtxNotFound)?;
 
-    // check authorize admin
-    let is_authorized = UsersBmc::is_admin(&ctx, &mm).await?;
-    if !(is_authorized || user_id_from_ctx == &user_id) {
+    // check permission, unless on its own user (not by an api key)
+    if !(user_id_from_ctx == &user_id && ctx.api_key_id().is_none())
+        && !RolePoliciesBmc::has_permission(&ctx, &mm, Permission::UsersRead).await?
+    {
         return Err(Error::YourUserNotAuthorize);
     }
 
@@ -104,21 +179,27 @@ async fn get_users_handler(
     Ok(body)
 }
 
+#[utoipa::path(
+    get,
+    path = "/users",
+    tag = "users",
+    params(PaginationParams),
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 200, description = "A page of the users. Needs `users:read`."),
+        (status = "4XX", response = ErrorBody),
+        (status = "5XX", response = ErrorBody),
+    )
+)]
 async fn list_users_handler(
     State(mm): State<ModelManager>,
-    ctxw: CtxW,
+    ctxw: RequirePermission<UsersRead>,
     Query(params): Query<PaginationParams>,
 ) -> Result<Json<Value>> {
     debug!("{:<12} - list_users_handler", "HANLDER");
     let ctx = ctxw.0;
     debug!("{:<12} - list_users_handler {:?}", "HANDLER", ctx);
 
-    // check authorize admin
-    let is_authorized = UsersBmc::is_admin(&ctx, &mm).await?;
-    if !is_authorized {
-        return Err(Error::YourUserNotAuthorize);
-    }
-
     // -- Get limit and offset from Query Params
     let limit = params.limit;
     let offset = params.offset;
@@ -132,20 +213,26 @@ async fn list_users_handler(
     Ok(body)
 }
 
+#[utoipa::path(
+    post,
+    path = "/users",
+    tag = "users",
+    request_body = UsersForCreatePayload,
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 201, description = "The user created, who is mailed a verification link. Needs `users:write`."),
+        (status = "4XX", response = ErrorBody),
+        (status = "5XX", response = ErrorBody),
+    )
+)]
 async fn create_user_handler(
     State(mm): State<ModelManager>,
-    ctxw: CtxW,
-    Json(payload): Json<UsersForCreatePayload>,
+    ctxw: RequirePermission<UsersWrite>,
+    ValidJson(payload): ValidJson<UsersForCreatePayload>,
 ) -> Result<(StatusCode, Json<Value>)> {
     debug!("{:<12} - create_user_handler", "HANDLER");
     let ctx = ctxw.0;
 
-    // check authorize admin
-    let is_authorized = UsersBmc::is_admin(&ctx, &mm).await?;
-    if !is_authorized {
-        return Err(Error::YourUserNotAuthorize);
-    }
-
     let UsersForCreatePayload {
         username,
         email,
@@ -156,6 +243,8 @@ async fn create_user_handler(
         password,
     } = payload;
 
+    pwd::validate_pwd_policy(&password, &[&username, &email])?;
+
     let user_info_for_create = UsersForCreate {
         username,
         email,
@@ -174,20 +263,99 @@ async fn create_user_handler(
     Ok((StatusCode::CREATED, body))
 }
 
+/// Import users with their hashed passwords from another system, e.g.,
+/// `#legacy-bcrypt#$2b$...`. Each user is imported on its own, failures are reported
+/// along the imported users.
+#[utoipa::path(
+    post,
+    path = "/users/import",
+    tag = "users",
+    request_body = Vec<UsersForImportPayload>,
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 200, description = "The `imported` users, and the `failed` ones with their `error`. Needs `users:write`."),
+        (status = "4XX", response = ErrorBody),
+        (status = "5XX", response = ErrorBody),
+    )
+)]
+async fn import_users_handler(
+    State(mm): State<ModelManager>,
+    ctxw: RequirePermission<UsersWrite>,
+    Json(payload): Json<Vec<UsersForImportPayload>>,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - import_users_handler", "HANDLER");
+    let ctx = ctxw.0;
+
+    let mut imported = Vec::new();
+    let mut failed = Vec::new();
+    for user in payload {
+        let username = user.username.clone();
+        if let Err(errors) = user.validate() {
+            failed.push(json!({
+                "username": username,
+                "error": "VALIDATION_FAILED",
+                "fields": field_violations(&errors),
+            }));
+            continue;
+        }
+
+        let users_for_import = UsersForImport {
+            username: user.username,
+            email: user.email,
+            email_verified: user.email_verified,
+            title: user.title,
+            firstname: user.firstname,
+            middlename: user.middlename,
+            lastname: user.lastname,
+            password: user.password_hash,
+        };
+
+        match UsersBmc::import(&ctx, &mm, users_for_import).await {
+            Ok(user_record) => imported.push(json!({
+                "username": username,
+                "id": user_record.id,
+            })),
+            Err(ex) => {
+                // -- Report the client error only, as for a failed request.
+                debug!("{:<12} - import_users_handler - {ex:?}", "HANDLER");
+                let (_status, client_error) = Error::from(ex).client_status_and_error();
+                failed.push(json!({
+                    "username": username,
+                    "error": client_error.as_ref(),
+                }));
+            }
+        }
+    }
+
+    // -- Create the success body.
+    let body = Json(json!({
+        "imported": imported,
+        "failed": failed,
+    }));
+
+    Ok(body)
+}
+
+#[utoipa::path(
+    delete,
+    path = "/users/{user_id}",
+    tag = "users",
+    params(PageParams),
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 204, description = "The user is deleted. Needs `users:delete`."),
+        (status = "4XX", response = ErrorBody),
+        (status = "5XX", response = ErrorBody),
+    )
+)]
 async fn delete_user_handler(
     State(mm): State<ModelManager>,
-    ctxw: CtxW,
+    ctxw: RequirePermission<UsersDelete>,
     Path(PageParams { user_id }): Path<PageParams>,
 ) -> Result<StatusCode> {
     debug!("{:<12} - delete_user_handler", "HANDLER");
     let ctx = ctxw.0;
 
-    // check authorize admin
-    let is_authorized = UsersBmc::is_admin(&ctx, &mm).await?;
-    if !is_authorized {
-        return Err(Error::YourUserNotAuthorize);
-    }
-
     let user_for_delete = UsersForDelete {
         deleted_by: ctx.user_id_thing().ok_or(Error::UserIdInCtxNotFound)?,
     };
@@ -197,22 +365,43 @@ async fn delete_user_handler(
     Ok(StatusCode::NO_CONTENT)
 }
 
+#[utoipa::path(
+    put,
+    path = "/users/{user_id}",
+    tag = "users",
+    request_body = UsersForUpdatePayload,
+    params(PageParams),
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 200, description = "The user updated. Needs `users:write`, unless on its own user, and not the `email` while impersonating."),
+        (status = "4XX", response = ErrorBody),
+        (status = "5XX", response = ErrorBody),
+    )
+)]
 async fn update_user_handler(
     State(mm): State<ModelManager>,
     ctxw: CtxW,
     Path(PageParams { user_id }): Path<PageParams>,
-    Json(payload): Json<UsersForUpdatePayload>,
+    ValidJson(payload): ValidJson<UsersForUpdatePayload>,
 ) -> Result<(StatusCode, Json<Value>)> {
     debug!("{:<12} - update_user_handler", "HANDLER");
     let ctx = ctxw.0;
     let user_id_from_ctx = ctx.user_id().ok_or(Error::UserIdInCtxNotFound)?;
 
-    // check authorize admin
-    let is_authorized = UsersBmc::is_admin(&ctx, &mm).await?;
-    if !(is_authorized || user_id_from_ctx == &user_id) {
+    // check permission, unless on its own user (not by an api key)
+    if !(user_id_from_ctx == &user_id && ctx.api_key_id().is_none())
+        && !RolePoliciesBmc::has_permission(&ctx, &mm, Permission::UsersWrite).await?
+    {
         return Err(Error::YourUserNotAuthorize);
     }
 
+    // -- Not the email while impersonating, it would take over the user by `/password/forgot`.
+    if payload.email.is_some() && ctx.impersonator_id().is_some() {
+        return Err(Error::ImpersonationNotAllowed);
+    }
+
+    let new_email = changed_email(&ctx, &mm, &user_id, payload.email.as_deref()).await?;
+
     let user_for_update = UsersForUpdate {
         email: payload.email,
         title: payload.title,
@@ -224,27 +413,47 @@ async fn update_user_handler(
 
     let user_record = UsersBmc::update::<UsersRecord>(&ctx, &mm, &user_id, user_for_update).await?;
 
+    if let Some(new_email) = new_email {
+        reverify_email(&ctx, &mm, &user_id, &new_email).await?;
+    }
+
     // -- Create the success body.
     let body = Json(json!(user_record));
 
     Ok((StatusCode::OK, body))
 }
 
+#[utoipa::path(
+    put,
+    path = "/users/{user_id}/update_by_admin",
+    tag = "users",
+    request_body = UsersForUpdateByAdminPayload,
+    params(PageParams),
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 200, description = "The user updated. Needs `users:write`, and `roles:write` to set the `role`."),
+        (status = "4XX", response = ErrorBody),
+        (status = "5XX", response = ErrorBody),
+    )
+)]
 async fn update_user_by_admin_handler(
     State(mm): State<ModelManager>,
-    ctxw: CtxW,
+    ctxw: RequirePermission<UsersWrite>,
     Path(PageParams { user_id }): Path<PageParams>,
-    Json(payload): Json<UsersForUpdateByAdminPayload>,
+    ValidJson(payload): ValidJson<UsersForUpdateByAdminPayload>,
 ) -> Result<(StatusCode, Json<Value>)> {
     debug!("{:<12} - update_user_by_admin_handler", "HANDLER");
     let ctx = ctxw.0;
 
-    // check authorize admin
-    let is_authorized = UsersBmc::is_admin(&ctx, &mm).await?;
-    if !is_authorized {
-        return Err(Error::YourUserNotAuthorize);
+    check_role_write(&ctx, &mm, payload.role.as_deref()).await?;
+
+    // -- Not the login identity while impersonating, as `update_user_handler`.
+    if (payload.username.is_some() || payload.email.is_some()) && ctx.impersonator_id().is_some() {
+        return Err(Error::ImpersonationNotAllowed);
     }
 
+    let new_email = changed_email(&ctx, &mm, &user_id, payload.email.as_deref()).await?;
+
     let user_for_update = UsersForUpdateByAdmin {
         username: payload.username,
         email: payload.email,
@@ -259,37 +468,239 @@ async fn update_user_by_admin_handler(
     let user_record =
         UsersBmc::update_by_admin::<UsersRecord>(&ctx, &mm, &user_id, user_for_update).await?;
 
+    if let Some(new_email) = new_email {
+        reverify_email(&ctx, &mm, &user_id, &new_email).await?;
+    }
+
     // -- Create the success body.
     let body = Json(json!(user_record));
 
     Ok((StatusCode::OK, body))
 }
 
+/// Set the password of a user, e.g., by the support. Users change their own with `/me/password`.
+///
+/// Every session of the user is logged out.
+#[utoipa::path(
+    put,
+    path = "/users/{user_id}/password",
+    tag = "users",
+    request_body = UsersForUpdatePasswordPayload,
+    params(PageParams),
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 200, description = "The password is set, and every session of the user is logged out. Needs `users:write`."),
+        (status = "4XX", response = ErrorBody),
+        (status = "5XX", response = ErrorBody),
+    )
+)]
 async fn update_pwd_user_handler(
     State(mm): State<ModelManager>,
-    ctxw: CtxW,
+    ctxw: RequirePermission<UsersWrite>,
     Path(PageParams { user_id }): Path<PageParams>,
     Json(payload): Json<UsersForUpdatePasswordPayload>,
 ) -> Result<StatusCode> {
     debug!("{:<12} - update_pwd_user_handler", "HANDLER");
     let ctx = ctxw.0;
-    let user_id_from_ctx = ctx.user_id().ok_or(Error::UserIdInCtxNotFound)?;
 
-    // check authorize admin
-    let is_authorized = UsersBmc::is_admin(&ctx, &mm).await?;
-    if !(is_authorized || user_id_from_ctx == &user_id) {
-        return Err(Error::YourUserNotAuthorize);
+    // -- Not while impersonating, even with the permission.
+    if ctx.impersonator_id().is_some() {
+        return Err(Error::ImpersonationNotAllowed);
     }
 
     let UsersForUpdatePasswordPayload { password } = payload;
 
-    let Users { password_salt, .. } = UsersBmc::get(&ctx, &mm, &user_id)
+    let Users {
+        username, email, ..
+    } = UsersBmc::get(&ctx, &mm, &user_id)
         .await?
         .ok_or(Error::DataNotFound)?;
 
-    let _ = UsersBmc::update_pwd(&ctx, &mm, &user_id, password, password_salt.0).await?;
+    pwd::validate_pwd_policy(&password, &[&username, &email])?;
+
+    UsersBmc::reset_pwd(&ctx, &mm, &user_id, password).await?;
+    SessionsBmc::revoke_by_user(&ctx, &mm, &user_id).await?;
 
     Ok(StatusCode::OK)
 }
 
+#[utoipa::path(
+    post,
+    path = "/users/{user_id}/unlock",
+    tag = "users",
+    params(PageParams),
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 204, description = "The login of the user is unlocked. Needs `users:write`."),
+        (status = "4XX", response = ErrorBody),
+        (status = "5XX", response = ErrorBody),
+    )
+)]
+async fn unlock_user_handler(
+    State(mm): State<ModelManager>,
+    ctxw: RequirePermission<UsersWrite>,
+    Path(PageParams { user_id }): Path<PageParams>,
+) -> Result<StatusCode> {
+    debug!("{:<12} - unlock_user_handler", "HANDLER");
+    let ctx = ctxw.0;
+
+    let user = UsersBmc::get::<UsersGet>(&ctx, &mm, &user_id)
+        .await?
+        .ok_or(Error::DataNotFound)?;
+
+    let username_key = LoginAttemptsBmc::username_key(&user.username);
+    LoginAttemptsBmc::reset(&ctx, &mm, &username_key).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Remove the second factor of a user, e.g., after a lost device and lost recovery codes.
+#[utoipa::path(
+    delete,
+    path = "/users/{user_id}/2fa",
+    tag = "users",
+    params(PageParams),
+    security(("bearer" = []), ("api_key" = [])),
+    responses(
+        (status = 204, description = "The second factor of the user is removed. Needs `users:write`."),
+        (status = "4XX", response = ErrorBody),
+        (status = "5XX", response = ErrorBody),
+    )
+)]
+async fn reset_2fa_user_handler(
+    State(mm): State<ModelManager>,
+    ctxw: RequirePermission<UsersWrite>,
+    Path(PageParams { user_id }): Path<PageParams>,
+) -> Result<StatusCode> {
+    debug!("{:<12} - reset_2fa_user_handler", "HANDLER");
+    let ctx = ctxw.0;
+
+    UsersBmc::disable_totp(&ctx, &mm, &user_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Setting the role of a user grants the permissions of the role, so it needs `roles:write`.
+async fn check_role_write(ctx: &Ctx, mm: &ModelManager, role: Option<&str>) -> Result<()> {
+    let permission = Permission::RolesWrite;
+    if role.is_some() && !RolePoliciesBmc::has_permission(ctx, mm, permission).await? {
+        return Err(Error::PermissionDenied {
+            permission: permission.as_str(),
+        });
+    }
+
+    Ok(())
+}
 // endregion: --- Users
+
+// region:    --- Email Change
+/// Return the new email if `email` differs from the current email of the user.
+async fn changed_email(
+    ctx: &Ctx,
+    mm: &ModelManager,
+    user_id: &str,
+    email: Option<&str>,
+) -> Result<Option<String>> {
+    let Some(email) = email else {
+        return Ok(None);
+    };
+
+    let user = UsersBmc::get::<UsersGet>(ctx, mm, user_id)
+        .await?
+        .ok_or(Error::DataNotFound)?;
+
+    Ok((user.email != email).then(|| email.to_string()))
+}
+
+/// Clear `email_verified` and ask the user to verify the new email.
+async fn reverify_email(ctx: &Ctx, mm: &ModelManager, user_id: &str, email: &str) -> Result<()> {
+    UsersBmc::unverify_email(ctx, mm, user_id).await?;
+    send_verification_email(ctx, mm, user_id, email).await?;
+
+    Ok(())
+}
+// endregion: --- Email Change
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    type Error = Box<dyn std::error::Error>;
+    type Result<T> = core::result::Result<T, Error>; // For tests.
+
+    use lib_surrealdb::{ctx::CtxBuilder, model::role_policies::Role};
+    use serial_test::serial;
+
+    use super::*;
+
+    #[serial]
+    #[tokio::test]
+    async fn test_check_role_write_err_users_write_only() -> Result<()> {
+        // -- Setup & Fixtures
+        //    (an api key of an admin, scoped to `users:write`)
+        let mm = ModelManager::new().await?;
+        let fx_ctx = Ctx::new(
+            CtxBuilder::new("iR1f8i7Wg7jipR3uhDhJ")
+                .roles(vec![Role::Admin])
+                .api_key("fx-api-key", vec![Permission::UsersWrite]),
+        )?;
+
+        // -- Exec
+        let no_role = check_role_write(&fx_ctx, &mm, None).await;
+        let role = check_role_write(&fx_ctx, &mm, Some("ADMIN")).await;
+
+        // -- Check
+        assert!(no_role.is_ok());
+        let error = role.err().ok_or("Should be denied")?;
+        assert!(matches!(
+            error,
+            crate::routes::Error::PermissionDenied {
+                permission: "roles:write"
+            }
+        ));
+        assert_eq!(error.client_status_and_error().0, StatusCode::FORBIDDEN);
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_update_user_err_impersonating_email() -> Result<()> {
+        // -- Setup & Fixtures
+        //    (an admin impersonating demo1)
+        let mm = ModelManager::new().await?;
+        let fx_user_id = "iR1f8i7Wg7jipR3uhDhJ";
+        let fx_ctx = Ctx::new(
+            CtxBuilder::new(fx_user_id)
+                .roles(vec![Role::User])
+                .impersonator_id("fx-impersonator-id"),
+        )?;
+        let fx_payload = UsersForUpdatePayload {
+            email: Some("fx-attacker@demo.com".to_string()),
+            title: None,
+            firstname: None,
+            middlename: None,
+            lastname: None,
+            image: None,
+        };
+
+        // -- Exec
+        let res = update_user_handler(
+            State(mm),
+            CtxW(fx_ctx),
+            Path(PageParams {
+                user_id: fx_user_id.to_string(),
+            }),
+            ValidJson(fx_payload),
+        )
+        .await;
+
+        // -- Check
+        assert!(matches!(
+            res,
+            Err(crate::routes::Error::ImpersonationNotAllowed)
+        ));
+
+        Ok(())
+    }
+}
+// endregion: --- Tests
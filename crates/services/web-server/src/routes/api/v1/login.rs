@@ -1,53 +1,295 @@
-use axum::{extract::State, routing::post, Json, Router};
+use std::net::SocketAddr;
+
+use axum::{
+    extract::{ConnectInfo, State},
+    http::{header::USER_AGENT, HeaderMap},
+    routing::post,
+    Json, Router,
+};
 use lib_auth::{
+    auth_config,
     pwd::{self, SchemeStatus},
-    token,
+    token::{self, Token},
 };
 use lib_surrealdb::{
     ctx::Ctx,
     model::{
-        users::{bmc::UsersBmc, UsersForLogin},
+        login_attempts::bmc::LoginAttemptsBmc,
+        role_policies::bmc::RolePoliciesBmc,
+        sessions::{bmc::SessionsBmc, SessionsForCreate},
+        users::{bmc::UsersBmc, UsersForLogin, UsersForTotp},
         ModelManager,
     },
 };
+use metrics::counter;
 use serde::Deserialize;
 use serde_json::{json, Value};
 use tracing::debug;
+use utoipa::ToSchema;
 
-use crate::routes::{Error, Result};
+use crate::{
+    config::web_config,
+    middlewares::ReqStamp,
+    routes::{
+        api::v1::_protected::totp::{confirm_totp, enroll_totp, verify_second_factor},
+        Error, ErrorBody, Result,
+    },
+};
 
 pub fn route(mm: ModelManager) -> Router {
     Router::new()
         .route("/login", post(api_login_handler))
+        .route("/login/2fa", post(api_login_2fa_handler))
+        .route("/login/2fa/enroll", post(api_login_2fa_enroll_handler))
+        .route("/login/2fa/confirm", post(api_login_2fa_confirm_handler))
         .with_state(mm)
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 struct LoginPayload {
     username: String,
     password: String,
 }
 
+#[derive(Debug, Deserialize, ToSchema)]
+struct Login2faPayload {
+    mfa_token: String,
+    /// A code of the authenticator app, or else one of the `recovery_code`.
+    code: Option<String>,
+    recovery_code: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+struct Login2faEnrollPayload {
+    mfa_token: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+struct Login2faConfirmPayload {
+    mfa_token: String,
+    code: String,
+}
+
+#[utoipa::path(
+    post,
+    path = "/login",
+    tag = "login",
+    request_body = LoginPayload,
+    responses(
+        (status = 200, description = "The user `data` and its `jwt`, or else the `mfa` token when the second factor is needed."),
+        (status = "4XX", response = ErrorBody),
+        (status = "5XX", response = ErrorBody),
+    )
+)]
 async fn api_login_handler(
     State(mm): State<ModelManager>,
+    req_stamp: ReqStamp,
+    ConnectInfo(client_addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
     // cookies: Cookies,
     Json(payload): Json<LoginPayload>,
 ) -> Result<Json<Value>> {
     debug!("{:<12} - api_login_handler", "HANLDER");
 
     let LoginPayload { username, password } = payload;
-    let root_ctx = Ctx::root_ctx();
+    let root_ctx = Ctx::root_ctx_for_req(req_stamp.uuid);
+
+    let username_key = LoginAttemptsBmc::username_key(&username);
+    let ip_key = LoginAttemptsBmc::ip_key(&client_addr.ip().to_string());
+
+    // -- Reject while locked, before spending any time on the pwd hashing.
+    check_not_locked(&root_ctx, &mm, &[&username_key, &ip_key]).await?;
+
+    let login_result = inner_login(&root_ctx, &mm, username, password).await;
+
+    if let Err(Error::LoginFailUsernameNotFound | Error::LoginFailPwdNotMatching { .. }) =
+        &login_result
+    {
+        record_failures(&root_ctx, &mm, "pwd", &username_key, &ip_key).await?;
+    }
+    let user = login_result?;
+
+    // -- The lock state is kept until the second factor is verified.
+    if let Some(body) = mfa_required(&root_ctx, &mm, &user).await? {
+        return Ok(body);
+    }
+
+    LoginAttemptsBmc::reset(&root_ctx, &mm, &username_key).await?;
+    count_login_attempt("pwd", "success");
+
+    login_success_body(
+        &root_ctx,
+        &mm,
+        user,
+        session_for_create(&headers, client_addr),
+    )
+    .await
+}
+
+#[utoipa::path(
+    post,
+    path = "/login/2fa",
+    tag = "login",
+    request_body = Login2faPayload,
+    responses(
+        (status = 200, description = "The user `data` and its `jwt`."),
+        (status = "4XX", response = ErrorBody),
+        (status = "5XX", response = ErrorBody),
+    )
+)]
+async fn api_login_2fa_handler(
+    State(mm): State<ModelManager>,
+    req_stamp: ReqStamp,
+    ConnectInfo(client_addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(payload): Json<Login2faPayload>,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - api_login_2fa_handler", "HANDLER");
+
+    let Login2faPayload {
+        mfa_token,
+        code,
+        recovery_code,
+    } = payload;
+    let root_ctx = Ctx::root_ctx_for_req(req_stamp.uuid);
+
+    let user = user_from_mfa_token(&root_ctx, &mm, &mfa_token).await?;
+
+    let username_key = LoginAttemptsBmc::username_key(&user.username);
+    let ip_key = LoginAttemptsBmc::ip_key(&client_addr.ip().to_string());
+    check_not_locked(&root_ctx, &mm, &[&username_key, &ip_key]).await?;
+
+    let verify_result = verify_second_factor(
+        &root_ctx,
+        &mm,
+        &user,
+        code.as_deref(),
+        recovery_code.as_deref(),
+    )
+    .await;
+
+    if let Err(Error::TotpCodeInvalid) = &verify_result {
+        record_failures(&root_ctx, &mm, "2fa", &username_key, &ip_key).await?;
+    }
+    verify_result?;
+
+    LoginAttemptsBmc::reset(&root_ctx, &mm, &username_key).await?;
+    count_login_attempt("2fa", "success");
+
+    let user_id = user.id.id.to_raw();
+    let user: UsersForLogin = UsersBmc::get(&root_ctx, &mm, &user_id)
+        .await?
+        .ok_or(Error::MfaTokenInvalid)?;
+
+    login_success_body(
+        &root_ctx,
+        &mm,
+        user,
+        session_for_create(&headers, client_addr),
+    )
+    .await
+}
+
+/// Start the enrollment of a user who must have a second factor but has none yet.
+#[utoipa::path(
+    post,
+    path = "/login/2fa/enroll",
+    tag = "login",
+    request_body = Login2faEnrollPayload,
+    responses(
+        (status = 200, description = "The `otpauth_uri` and `secret` of the pending second factor, to confirm with `/login/2fa/confirm`."),
+        (status = "4XX", response = ErrorBody),
+        (status = "5XX", response = ErrorBody),
+    )
+)]
+async fn api_login_2fa_enroll_handler(
+    State(mm): State<ModelManager>,
+    req_stamp: ReqStamp,
+    Json(payload): Json<Login2faEnrollPayload>,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - api_login_2fa_enroll_handler", "HANDLER");
+
+    let Login2faEnrollPayload { mfa_token } = payload;
+    let root_ctx = Ctx::root_ctx_for_req(req_stamp.uuid);
+
+    let user = user_from_mfa_token(&root_ctx, &mm, &mfa_token).await?;
+
+    enroll_totp(&root_ctx, &mm, &user).await
+}
+
+/// Confirm the enrollment with a first code, and finish the login.
+#[utoipa::path(
+    post,
+    path = "/login/2fa/confirm",
+    tag = "login",
+    request_body = Login2faConfirmPayload,
+    responses(
+        (status = 200, description = "The user `data`, its `jwt`, and the `recovery_codes`, shown only once."),
+        (status = "4XX", response = ErrorBody),
+        (status = "5XX", response = ErrorBody),
+    )
+)]
+async fn api_login_2fa_confirm_handler(
+    State(mm): State<ModelManager>,
+    req_stamp: ReqStamp,
+    ConnectInfo(client_addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(payload): Json<Login2faConfirmPayload>,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - api_login_2fa_confirm_handler", "HANDLER");
+
+    let Login2faConfirmPayload { mfa_token, code } = payload;
+    let root_ctx = Ctx::root_ctx_for_req(req_stamp.uuid);
+
+    let user = user_from_mfa_token(&root_ctx, &mm, &mfa_token).await?;
+
+    let username_key = LoginAttemptsBmc::username_key(&user.username);
+    let ip_key = LoginAttemptsBmc::ip_key(&client_addr.ip().to_string());
+    check_not_locked(&root_ctx, &mm, &[&username_key, &ip_key]).await?;
+
+    let confirm_result = confirm_totp(&root_ctx, &mm, &user, &code).await;
+
+    if let Err(Error::TotpCodeInvalid) = &confirm_result {
+        record_failures(&root_ctx, &mm, "2fa", &username_key, &ip_key).await?;
+    }
+    let recovery_codes = confirm_result?;
+
+    LoginAttemptsBmc::reset(&root_ctx, &mm, &username_key).await?;
+    count_login_attempt("2fa", "success");
+
+    let user_id = user.id.id.to_raw();
+    let user: UsersForLogin = UsersBmc::get(&root_ctx, &mm, &user_id)
+        .await?
+        .ok_or(Error::MfaTokenInvalid)?;
+
+    let Json(mut body) = login_success_body(
+        &root_ctx,
+        &mm,
+        user,
+        session_for_create(&headers, client_addr),
+    )
+    .await?;
+    body["recovery_codes"] = json!(recovery_codes);
+
+    Ok(Json(body))
+}
 
+/// Check the username and the password, and return the user.
+async fn inner_login(
+    root_ctx: &Ctx,
+    mm: &ModelManager,
+    username: String,
+    password: String,
+) -> Result<UsersForLogin> {
     // -- Get the user.
-    let user: UsersForLogin = UsersBmc::first_by_username(&root_ctx, &mm, &username)
+    let user: UsersForLogin = UsersBmc::first_by_username(root_ctx, mm, &username)
         .await?
         .ok_or(Error::LoginFailUsernameNotFound)?;
 
     let user_id = user.id.id.to_raw();
-    let token_salt = user.token_salt;
 
     // -- Validate the password.
-    let Some(hash) = user.password else {
+    let Some(hash) = &user.password else {
         return Err(Error::LoginFailUserHasNoPwd { user_id });
     };
 
@@ -55,31 +297,84 @@ async fn api_login_handler(
 
     let to_hash = pwd::ContentToHash::new(password.clone(), password_salt_uuid);
 
-    let scheme_status =
-        pwd::validate_pwd(to_hash, hash)
-            .await
-            .map_err(|_| Error::LoginFailPwdNotMatching {
-                user_id: user_id.clone(),
-            })?;
+    let scheme_status = pwd::validate_pwd(to_hash, hash.to_string())
+        .await
+        .map_err(|_| Error::LoginFailPwdNotMatching {
+            user_id: user_id.clone(),
+        })?;
+
+    // -- Block login until the email is verified, if required.
+    if web_config().LOGIN_REQUIRE_EMAIL_VERIFIED && user.email_verified.is_none() {
+        return Err(Error::LoginFailEmailNotVerified { user_id });
+    }
 
     // -- Update password scheme if needed
+    //    (a failed upgrade does not fail the login, it is retried on the next one)
     if let SchemeStatus::Outdated = scheme_status {
         debug!("pwd encrypt scheme outdated, upgrading.");
-        UsersBmc::update_pwd(
-            &root_ctx,
-            &mm,
-            user_id.as_str(),
-            password,
-            password_salt_uuid,
-        )
-        .await?;
+        if let Err(ex) =
+            UsersBmc::rehash_pwd(root_ctx, mm, &user_id, password, password_salt_uuid, hash).await
+        {
+            debug!("{:<12} - pwd upgrade failed - {ex:?}", "LOGIN");
+        }
     }
 
+    Ok(user)
+}
+
+/// Return the mfa body if the second factor is enabled for the user or enforced for the role.
+pub(crate) async fn mfa_required(
+    root_ctx: &Ctx,
+    mm: &ModelManager,
+    user: &UsersForLogin,
+) -> Result<Option<Json<Value>>> {
+    let totp_enabled = user.totp_enabled_on.is_some();
+    if totp_enabled || RolePoliciesBmc::require_2fa(root_ctx, mm, &user.role).await? {
+        return Ok(Some(mfa_required_body(user, !totp_enabled)?));
+    }
+
+    Ok(None)
+}
+
+/// The session of a login, from the client of the request.
+pub(crate) fn session_for_create(
+    headers: &HeaderMap,
+    client_addr: SocketAddr,
+) -> SessionsForCreate {
+    let user_agent = headers
+        .get(USER_AGENT)
+        .and_then(|user_agent| user_agent.to_str().ok())
+        .map(str::to_string);
+
+    SessionsForCreate {
+        user_agent,
+        client_ip: Some(client_addr.ip().to_string()),
+        expire_sec: auth_config().TOKEN_DURATION.as_secs() as i64,
+    }
+}
+
+/// Count a login attempt, by `method` (`pwd`, `2fa`, `oidc`) and `result` (`success`, `failure`).
+pub(crate) fn count_login_attempt(method: &'static str, result: &'static str) {
+    counter!("login_attempts_total", "method" => method, "result" => result).increment(1);
+}
+
+/// Create the session of the login, and the body with its jwt.
+pub(crate) async fn login_success_body(
+    root_ctx: &Ctx,
+    mm: &ModelManager,
+    user: UsersForLogin,
+    sessions_for_create: SessionsForCreate,
+) -> Result<Json<Value>> {
+    let user_id = user.id.id.to_raw();
+
+    let session = SessionsBmc::create(root_ctx, mm, &user_id, sessions_for_create).await?;
+    let session_id = session.id.id.to_raw();
+
     // -- Set web token if not send back token via body
     // web::set_token_cookie(&cookies, &user_id, user.token_salt)?;
 
     // -- Generate toekn if not use cookie
-    let jwt = token::encode_jwt(user_id.as_str(), token_salt.as_ref())?;
+    let jwt = token::encode_session_jwt(&user_id, &session_id, user.token_salt.as_ref())?;
 
     // -- Create the success body
     let body = Json(json!({
@@ -101,3 +396,102 @@ async fn api_login_handler(
 
     Ok(body)
 }
+
+/// The body of a login whose password is valid, but which still needs the second factor.
+/// No jwt is issued, only the short-lived mfa token for the `/login/2fa*` endpoints.
+fn mfa_required_body(user: &UsersForLogin, enrollment_required: bool) -> Result<Json<Value>> {
+    let user_id = user.id.id.to_raw();
+    let mfa_token = token::generate_mfa_token(&user_id, user.token_salt.0)?;
+
+    let body = Json(json!({
+        "result": {
+            "success": true,
+        },
+        "mfa": {
+            "token": mfa_token.to_string(),
+            "enrollment_required": enrollment_required,
+        }
+    }));
+
+    Ok(body)
+}
+
+async fn user_from_mfa_token(
+    root_ctx: &Ctx,
+    mm: &ModelManager,
+    mfa_token: &str,
+) -> Result<UsersForTotp> {
+    let mfa_token: Token = mfa_token.parse().map_err(|_| Error::MfaTokenInvalid)?;
+    let user: UsersForTotp = UsersBmc::get(root_ctx, mm, &mfa_token.ident)
+        .await?
+        .ok_or(Error::MfaTokenInvalid)?;
+    token::validate_mfa_token(&mfa_token, user.token_salt.0).map_err(|_| Error::MfaTokenInvalid)?;
+
+    Ok(user)
+}
+
+pub(crate) async fn check_not_locked(
+    root_ctx: &Ctx,
+    mm: &ModelManager,
+    keys: &[&str],
+) -> Result<()> {
+    for key in keys {
+        if let Some(retry_after_sec) = LoginAttemptsBmc::locked_for_sec(root_ctx, mm, key).await? {
+            return Err(Error::LoginFailAccountLocked { retry_after_sec });
+        }
+    }
+
+    Ok(())
+}
+
+async fn record_failures(
+    root_ctx: &Ctx,
+    mm: &ModelManager,
+    method: &'static str,
+    username_key: &str,
+    ip_key: &str,
+) -> Result<()> {
+    count_login_attempt(method, "failure");
+
+    let config = web_config();
+    record_failure(
+        root_ctx,
+        mm,
+        username_key,
+        config.LOGIN_LOCKOUT_USERNAME_THRESHOLD,
+    )
+    .await?;
+    record_failure(root_ctx, mm, ip_key, config.LOGIN_LOCKOUT_IP_THRESHOLD).await?;
+
+    Ok(())
+}
+
+/// Count the failure for `key`, and lock it with an exponential backoff
+/// once `threshold` failures happened in the attempt window.
+pub(crate) async fn record_failure(
+    root_ctx: &Ctx,
+    mm: &ModelManager,
+    key: &str,
+    threshold: i64,
+) -> Result<()> {
+    let config = web_config();
+    let window_sec = config.LOGIN_ATTEMPT_WINDOW.as_secs() as i64;
+    let lockout_base_sec = config.LOGIN_LOCKOUT_BASE.as_secs() as i64;
+    let lockout_max_sec = config.LOGIN_LOCKOUT_MAX.as_secs() as i64;
+    let failed_count = LoginAttemptsBmc::record_failure(root_ctx, mm, key, window_sec).await?;
+
+    let Ok(over_threshold) = u32::try_from(failed_count - threshold) else {
+        return Ok(());
+    };
+
+    let lock_sec = 2_i64
+        .checked_pow(over_threshold)
+        .and_then(|factor| lockout_base_sec.checked_mul(factor))
+        .unwrap_or(lockout_max_sec)
+        .min(lockout_max_sec);
+    debug!("{:<12} - login locked - {key} for {lock_sec}s", "LOGIN");
+
+    LoginAttemptsBmc::lock(root_ctx, mm, key, lock_sec).await?;
+
+    Ok(())
+}
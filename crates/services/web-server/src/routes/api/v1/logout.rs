@@ -3,8 +3,9 @@ use lib_surrealdb::model::ModelManager;
 use serde::Deserialize;
 use serde_json::{json, Value};
 use tracing::debug;
+use utoipa::ToSchema;
 
-use crate::routes::Result;
+use crate::routes::{ErrorBody, Result};
 
 pub fn route(mm: ModelManager) -> Router {
     Router::new()
@@ -13,6 +14,17 @@ pub fn route(mm: ModelManager) -> Router {
 }
 
 // region:    --- Logout
+#[utoipa::path(
+    post,
+    path = "/logout",
+    tag = "login",
+    request_body = LogoutPayload,
+    responses(
+        (status = 200, description = "Whether the client should `logout`."),
+        (status = "4XX", response = ErrorBody),
+        (status = "5XX", response = ErrorBody),
+    )
+)]
 async fn api_logout_handler(
     // cookies: Cookies,
     Json(payload): Json<LogoutPayload>,
@@ -35,7 +47,7 @@ async fn api_logout_handler(
     Ok(body)
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 struct LogoutPayload {
     logout: bool,
 }
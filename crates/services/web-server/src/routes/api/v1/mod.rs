@@ -1,19 +1,57 @@
-use axum::Router;
+use axum::{middleware::from_fn_with_state, Router};
 use lib_surrealdb::model::ModelManager;
+use utoipa::OpenApi;
+
+use crate::middlewares::{mw_rate_limit, RateLimiter};
 
 mod _protected;
 mod login;
 mod logout;
+mod oidc;
+mod password;
+mod verify_email;
 
 pub fn routes_all(mm: ModelManager) -> Router {
     let routes_all = Router::new();
     routes_all
-        .merge(login::route(mm.clone()))
+        .merge(routes_auth(mm.clone()))
         .merge(logout::route(mm.clone()))
         .merge(_protected::route(mm))
 }
 
+/// The public routes of the login, rate limited by client ip.
+fn routes_auth(mm: ModelManager) -> Router {
+    Router::new()
+        .merge(login::route(mm.clone()))
+        .merge(oidc::route(mm.clone()))
+        .merge(password::route(mm.clone()))
+        .merge(verify_email::route(mm))
+        .route_layer(from_fn_with_state(RateLimiter::auth(), mw_rate_limit))
+}
+
 pub fn route(mm: ModelManager) -> Router {
     let route = Router::new();
     route.nest("/v1", routes_all(mm))
 }
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    login::api_login_handler,
+    login::api_login_2fa_handler,
+    login::api_login_2fa_enroll_handler,
+    login::api_login_2fa_confirm_handler,
+    oidc::oidc_authorize_handler,
+    oidc::oidc_callback_handler,
+    password::api_forgot_pwd_handler,
+    password::api_reset_pwd_handler,
+    verify_email::api_verify_email_handler,
+    verify_email::api_resend_verify_email_handler,
+    logout::api_logout_handler,
+))]
+struct ApiDoc;
+
+pub fn openapi() -> utoipa::openapi::OpenApi {
+    let mut openapi = ApiDoc::openapi();
+    openapi.merge(_protected::openapi());
+    utoipa::openapi::OpenApi::default().nest("/v1", openapi)
+}
@@ -0,0 +1,269 @@
+use std::net::SocketAddr;
+
+use axum::{
+    extract::{ConnectInfo, Query, State},
+    http::{
+        header::{COOKIE, SET_COOKIE},
+        HeaderMap, HeaderName,
+    },
+    response::Redirect,
+    routing::get,
+    Json, Router,
+};
+use lib_auth::oidc::{generate_pkce, generate_random, IdTokenClaims};
+use lib_surrealdb::{
+    ctx::Ctx,
+    model::{
+        oidc_logins::bmc::OidcLoginsBmc,
+        users::{bmc::UsersBmc, UsersForCreate, UsersForLogin},
+        ModelManager,
+    },
+};
+use serde::Deserialize;
+use serde_json::Value;
+use tracing::debug;
+use utoipa::IntoParams;
+
+use crate::{
+    config::web_config,
+    middlewares::ReqStamp,
+    oidc,
+    routes::{
+        api::v1::login::{
+            count_login_attempt, login_success_body, mfa_required, session_for_create,
+        },
+        Error, ErrorBody, Result,
+    },
+};
+
+/// The state of the flow started by this browser, so a callback can not be replayed
+/// in another browser (login CSRF).
+const OIDC_STATE_COOKIE: &str = "oidc_state";
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct OidcCallbackParams {
+    state: String,
+    code: Option<String>,
+    error: Option<String>,
+}
+
+pub fn route(mm: ModelManager) -> Router {
+    Router::new()
+        .route("/oidc/authorize", get(oidc_authorize_handler))
+        .route("/oidc/callback", get(oidc_callback_handler))
+        .with_state(mm)
+}
+
+// region:    --- Oidc
+/// Start an authorization code flow, redirecting to the provider login page.
+#[utoipa::path(
+    get,
+    path = "/oidc/authorize",
+    tag = "login",
+    responses(
+        (status = 303, description = "The redirection to the login page of the identity provider, with the `oidc_state` cookie."),
+        (status = "4XX", response = ErrorBody),
+        (status = "5XX", response = ErrorBody),
+    )
+)]
+async fn oidc_authorize_handler(
+    State(mm): State<ModelManager>,
+    req_stamp: ReqStamp,
+) -> Result<([(HeaderName, String); 1], Redirect)> {
+    debug!("{:<12} - oidc_authorize_handler", "HANDLER");
+    let root_ctx = Ctx::root_ctx_for_req(req_stamp.uuid);
+
+    let pkce = generate_pkce();
+    let nonce = generate_random();
+    let oidc_login = OidcLoginsBmc::create(&root_ctx, &mm, &pkce.code_verifier, &nonce).await?;
+    let state = oidc_login.id.id.to_raw();
+
+    let authorization_url = oidc::authorization_url(&state, &nonce, &pkce.code_challenge).await?;
+
+    let max_age_sec = web_config().OIDC_LOGIN_DURATION.as_secs();
+    Ok((
+        [(SET_COOKIE, state_cookie(&state, max_age_sec))],
+        Redirect::to(&authorization_url),
+    ))
+}
+
+/// Finish the flow started by `oidc_authorize_handler`, and log the user in
+/// as `POST /login` does.
+#[utoipa::path(
+    get,
+    path = "/oidc/callback",
+    tag = "login",
+    params(OidcCallbackParams),
+    responses(
+        (status = 200, description = "The user `data` and its `jwt`, or else the `mfa` token when the second factor is needed. Needs the `oidc_state` cookie of the flow."),
+        (status = "4XX", response = ErrorBody),
+        (status = "5XX", response = ErrorBody),
+    )
+)]
+async fn oidc_callback_handler(
+    State(mm): State<ModelManager>,
+    req_stamp: ReqStamp,
+    ConnectInfo(client_addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Query(params): Query<OidcCallbackParams>,
+) -> Result<([(HeaderName, String); 1], Json<Value>)> {
+    debug!("{:<12} - oidc_callback_handler", "HANDLER");
+    let root_ctx = Ctx::root_ctx_for_req(req_stamp.uuid);
+
+    // -- Only the browser which started the flow can finish it.
+    if cookie_value(&headers, OIDC_STATE_COOKIE) != Some(params.state.as_str()) {
+        count_login_attempt("oidc", "failure");
+        return Err(Error::OidcStateNotMatching);
+    }
+    let clear_cookie = [(SET_COOKIE, state_cookie("", 0))];
+
+    // -- The state can only be used once, even when the provider returns an error.
+    let oidc_login = OidcLoginsBmc::consume(
+        &root_ctx,
+        &mm,
+        &params.state,
+        web_config().OIDC_LOGIN_DURATION.as_secs() as i64,
+    )
+    .await?;
+
+    let user_result = async {
+        if let Some(error) = params.error {
+            return Err(Error::OidcProviderError(error));
+        }
+        let code = params
+            .code
+            .ok_or(Error::OidcProviderError("no code in callback".to_string()))?;
+
+        let id_token = oidc::exchange_code(&code, &oidc_login.code_verifier).await?;
+        let claims = oidc::validate_id_token(&id_token, &oidc_login.nonce).await?;
+
+        let user = user_from_claims(&root_ctx, &mm, claims).await?;
+
+        // -- Block login until the email is verified, if required (as `POST /login`).
+        if web_config().LOGIN_REQUIRE_EMAIL_VERIFIED && user.email_verified.is_none() {
+            return Err(Error::LoginFailEmailNotVerified {
+                user_id: user.id.id.to_raw(),
+            });
+        }
+
+        Ok(user)
+    }
+    .await;
+    if user_result.is_err() {
+        count_login_attempt("oidc", "failure");
+    }
+    let user = user_result?;
+
+    if let Some(body) = mfa_required(&root_ctx, &mm, &user).await? {
+        return Ok((clear_cookie, body));
+    }
+    count_login_attempt("oidc", "success");
+
+    let body = login_success_body(
+        &root_ctx,
+        &mm,
+        user,
+        session_for_create(&headers, client_addr),
+    )
+    .await?;
+
+    Ok((clear_cookie, body))
+}
+
+/// The `Set-Cookie` value of the state, only sent back to the callback.
+/// An empty `state` with a zero `max_age_sec` clears it.
+fn state_cookie(state: &str, max_age_sec: u64) -> String {
+    format!(
+        "{OIDC_STATE_COOKIE}={state}; Path=/api/v1/oidc; Max-Age={max_age_sec}; HttpOnly; Secure; SameSite=Lax"
+    )
+}
+
+/// The value of the cookie `name` of the request, if any.
+fn cookie_value<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get_all(COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|cookie| cookie.trim().split_once('='))
+        .find(|(cookie_name, _)| *cookie_name == name)
+        .map(|(_, value)| value)
+}
+
+/// Get the user of the identity. On its first login, the identity is linked to the user
+/// with the same (verified) email, or to a new user if provisioning is enabled.
+async fn user_from_claims(
+    root_ctx: &Ctx,
+    mm: &ModelManager,
+    claims: IdTokenClaims,
+) -> Result<UsersForLogin> {
+    let oidc_subject = format!("{}|{}", claims.iss, claims.sub);
+    if let Some(user) = UsersBmc::first_by_oidc_subject(root_ctx, mm, &oidc_subject).await? {
+        return Ok(user);
+    }
+
+    let email = claims
+        .email
+        .filter(|_| claims.email_verified)
+        .ok_or(Error::OidcEmailNotVerified)?;
+
+    let config = web_config();
+    let user_id = match UsersBmc::first_by_email::<UsersForLogin>(root_ctx, mm, &email).await? {
+        // -- Only a verified local email proves the user owns it, otherwise the identity
+        //    could be linked to an account pre-registered with the same address.
+        Some(user) if user.email_verified.is_some() => user.id.id.to_raw(),
+        Some(_) => return Err(Error::OidcEmailNotVerified),
+        None if config.OIDC_PROVISION_USERS => {
+            // -- The password is never given, the user logs in by the provider
+            //    (or resets it with `/password/forgot`).
+            let user_for_create = UsersForCreate {
+                username: email.clone(),
+                email,
+                title: config.OIDC_PROVISION_TITLE.clone(),
+                firstname: claims.given_name.unwrap_or_default(),
+                middlename: None,
+                lastname: claims.family_name.unwrap_or_default(),
+                password: generate_random(),
+            };
+            let user_record = UsersBmc::create(root_ctx, mm, user_for_create, true).await?;
+            user_record.id.id.to_raw()
+        }
+        None => return Err(Error::OidcUserNotFound),
+    };
+
+    UsersBmc::link_oidc_subject(root_ctx, mm, &user_id, &oidc_subject).await?;
+
+    let user = UsersBmc::get(root_ctx, mm, &user_id)
+        .await?
+        .ok_or(Error::OidcUserNotFound)?;
+
+    Ok(user)
+}
+// endregion: --- Oidc
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+
+    use super::*;
+
+    #[test]
+    fn test_cookie_value() {
+        // -- Setup & Fixtures
+        let mut headers = HeaderMap::new();
+        headers.append(
+            COOKIE,
+            HeaderValue::from_static("theme=dark; oidc_state=fx-state"),
+        );
+        headers.append(COOKIE, HeaderValue::from_static("other=1"));
+
+        // -- Exec & Check
+        assert_eq!(cookie_value(&headers, OIDC_STATE_COOKIE), Some("fx-state"));
+        assert_eq!(cookie_value(&headers, "other"), Some("1"));
+        assert_eq!(cookie_value(&headers, "state"), None);
+        assert_eq!(cookie_value(&HeaderMap::new(), OIDC_STATE_COOKIE), None);
+    }
+}
+// endregion: --- Tests
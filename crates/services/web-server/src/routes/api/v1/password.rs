@@ -0,0 +1,144 @@
+use axum::{extract::State, http::StatusCode, routing::post, Json, Router};
+use lib_auth::{
+    pwd,
+    token::{self, Token},
+};
+use lib_surrealdb::{
+    ctx::{Ctx, CtxBuilder},
+    model::{
+        pwd_resets::{bmc::PwdResetsBmc, PwdResets},
+        users::{bmc::UsersBmc, Users},
+        ModelManager,
+    },
+};
+use serde::Deserialize;
+use tracing::{debug, error};
+use utoipa::ToSchema;
+
+use crate::{
+    config::web_config,
+    mail::{mail_sender, Mail},
+    middlewares::ReqStamp,
+    routes::{Error, ErrorBody, Result},
+};
+
+pub fn route(mm: ModelManager) -> Router {
+    Router::new()
+        .route("/password/forgot", post(api_forgot_pwd_handler))
+        .route("/password/reset", post(api_reset_pwd_handler))
+        .with_state(mm)
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+struct ForgotPwdPayload {
+    username: String,
+}
+
+#[derive(Deserialize, ToSchema)]
+struct ResetPwdPayload {
+    token: String,
+    password: String,
+}
+
+// region:    --- Password
+#[utoipa::path(
+    post,
+    path = "/password/forgot",
+    tag = "password",
+    request_body = ForgotPwdPayload,
+    responses(
+        (status = 202, description = "Accepted, whether the user exists or not. A reset link is mailed to the user, if any."),
+        (status = "4XX", response = ErrorBody),
+        (status = "5XX", response = ErrorBody),
+    )
+)]
+async fn api_forgot_pwd_handler(
+    State(mm): State<ModelManager>,
+    req_stamp: ReqStamp,
+    Json(payload): Json<ForgotPwdPayload>,
+) -> Result<StatusCode> {
+    debug!("{:<12} - api_forgot_pwd_handler", "HANDLER");
+
+    let ForgotPwdPayload { username } = payload;
+    let root_ctx = Ctx::root_ctx_for_req(req_stamp.uuid);
+
+    // -- Always accept, so the response does not leak which usernames exist.
+    let Some(user) = UsersBmc::first_by_username::<Users>(&root_ctx, &mm, &username).await? else {
+        return Ok(StatusCode::ACCEPTED);
+    };
+
+    // -- A failure is only logged, a 5xx would tell the username exists.
+    if let Err(ex) = send_pwd_reset_mail(&root_ctx, &mm, user).await {
+        error!("{:<12} - forgot password mail - {ex:?}", "HANDLER");
+    }
+
+    Ok(StatusCode::ACCEPTED)
+}
+
+/// Create a reset request of the user, and mail its link.
+async fn send_pwd_reset_mail(root_ctx: &Ctx, mm: &ModelManager, user: Users) -> Result<()> {
+    let user_id = user.id.id.to_raw();
+    let pwd_reset = PwdResetsBmc::create(root_ctx, mm, &user_id).await?;
+    let token = token::generate_pwd_reset_token(&pwd_reset.id.id.to_raw(), pwd_reset.token_salt.0)?;
+
+    let mail = Mail {
+        to: user.email,
+        subject: "Reset your password".to_string(),
+        body: format!(
+            "Use the link below to reset your password:\n{}/reset-password?token={token}",
+            web_config().WEB_URL
+        ),
+    };
+    mail_sender().send(mail).await?;
+
+    Ok(())
+}
+
+#[utoipa::path(
+    post,
+    path = "/password/reset",
+    tag = "password",
+    request_body = ResetPwdPayload,
+    responses(
+        (status = 204, description = "The password is reset, and the previous jwts of the user are rejected."),
+        (status = "4XX", response = ErrorBody),
+        (status = "5XX", response = ErrorBody),
+    )
+)]
+async fn api_reset_pwd_handler(
+    State(mm): State<ModelManager>,
+    req_stamp: ReqStamp,
+    Json(payload): Json<ResetPwdPayload>,
+) -> Result<StatusCode> {
+    debug!("{:<12} - api_reset_pwd_handler", "HANDLER");
+
+    let ResetPwdPayload { token, password } = payload;
+    let root_ctx = Ctx::root_ctx_for_req(req_stamp.uuid);
+
+    // -- Validate the token against its reset request.
+    let token: Token = token.parse().map_err(|_| Error::PwdResetTokenInvalid)?;
+    let pwd_reset: PwdResets = PwdResetsBmc::get(&root_ctx, &mm, &token.ident)
+        .await?
+        .ok_or(Error::PwdResetTokenInvalid)?;
+    if pwd_reset.used_on.is_some() {
+        return Err(Error::PwdResetTokenInvalid);
+    }
+    token::validate_pwd_reset_token(&token, pwd_reset.token_salt.0)
+        .map_err(|_| Error::PwdResetTokenInvalid)?;
+
+    // -- Check the new password.
+    let user_id = pwd_reset.user.id.to_raw();
+    let user: Users = UsersBmc::get(&root_ctx, &mm, &user_id)
+        .await?
+        .ok_or(Error::PwdResetTokenInvalid)?;
+    pwd::validate_pwd_policy(&password, &[&user.username, &user.email])?;
+
+    // -- Consume the reset request, then update the password as the user.
+    PwdResetsBmc::consume(&root_ctx, &mm, &token.ident).await?;
+    let user_ctx =
+        Ctx::new(CtxBuilder::new(user_id.as_str())).map_err(|_| Error::UserIdInCtxNotFound)?;
+    UsersBmc::reset_pwd(&user_ctx, &mm, &user_id, password).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+// endregion: --- Password
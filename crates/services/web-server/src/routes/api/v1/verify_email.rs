@@ -0,0 +1,151 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    routing::{get, post},
+    Json, Router,
+};
+use lib_auth::token::{self, Token};
+use lib_surrealdb::{
+    ctx::Ctx,
+    model::{
+        email_verifications::{bmc::EmailVerificationsBmc, EmailVerifications},
+        users::{bmc::UsersBmc, Users},
+        ModelManager,
+    },
+};
+use serde::Deserialize;
+use tracing::debug;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{
+    config::web_config,
+    mail::{mail_sender, Mail},
+    middlewares::ReqStamp,
+    routes::{Error, ErrorBody, Result},
+};
+
+pub fn route(mm: ModelManager) -> Router {
+    Router::new()
+        .route("/verify-email", get(api_verify_email_handler))
+        .route(
+            "/verify-email/resend",
+            post(api_resend_verify_email_handler),
+        )
+        .with_state(mm)
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct VerifyEmailParams {
+    token: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+struct ResendVerifyEmailPayload {
+    username: String,
+}
+
+/// Create a verification request for `email`, and mail its token to the user.
+pub(crate) async fn send_verification_email(
+    ctx: &Ctx,
+    mm: &ModelManager,
+    user_id: &str,
+    email: &str,
+) -> Result<()> {
+    let email_verification = EmailVerificationsBmc::create(ctx, mm, user_id, email).await?;
+    let token = token::generate_email_verification_token(
+        &email_verification.id.id.to_raw(),
+        email_verification.token_salt.0,
+    )?;
+
+    let mail = Mail {
+        to: email.to_string(),
+        subject: "Verify your email".to_string(),
+        body: format!(
+            "Use the link below to verify your email:\n{}/api/v1/verify-email?token={token}",
+            web_config().WEB_URL
+        ),
+    };
+    mail_sender().send(mail).await?;
+
+    Ok(())
+}
+
+// region:    --- Verify Email
+#[utoipa::path(
+    get,
+    path = "/verify-email",
+    tag = "verify_email",
+    params(VerifyEmailParams),
+    responses(
+        (status = 204, description = "The email of the user is verified."),
+        (status = "4XX", response = ErrorBody),
+        (status = "5XX", response = ErrorBody),
+    )
+)]
+async fn api_verify_email_handler(
+    State(mm): State<ModelManager>,
+    req_stamp: ReqStamp,
+    Query(VerifyEmailParams { token }): Query<VerifyEmailParams>,
+) -> Result<StatusCode> {
+    debug!("{:<12} - api_verify_email_handler", "HANDLER");
+
+    let root_ctx = Ctx::root_ctx_for_req(req_stamp.uuid);
+
+    // -- Validate the token against its verification request.
+    let token: Token = token
+        .parse()
+        .map_err(|_| Error::EmailVerificationTokenInvalid)?;
+    let email_verification: EmailVerifications =
+        EmailVerificationsBmc::get(&root_ctx, &mm, &token.ident)
+            .await?
+            .ok_or(Error::EmailVerificationTokenInvalid)?;
+    if email_verification.used_on.is_some() {
+        return Err(Error::EmailVerificationTokenInvalid);
+    }
+    token::validate_email_verification_token(&token, email_verification.token_salt.0)
+        .map_err(|_| Error::EmailVerificationTokenInvalid)?;
+
+    // -- Consume the verification request, then stamp the user.
+    EmailVerificationsBmc::consume(&root_ctx, &mm, &token.ident).await?;
+    let user_id = email_verification.user.id.to_raw();
+    UsersBmc::verify_email(&root_ctx, &mm, &user_id, &email_verification.email).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/verify-email/resend",
+    tag = "verify_email",
+    request_body = ResendVerifyEmailPayload,
+    responses(
+        (status = 202, description = "Accepted, whether the user exists or not. A verification link is mailed to the user, if not verified yet."),
+        (status = "4XX", response = ErrorBody),
+        (status = "5XX", response = ErrorBody),
+    )
+)]
+async fn api_resend_verify_email_handler(
+    State(mm): State<ModelManager>,
+    req_stamp: ReqStamp,
+    Json(payload): Json<ResendVerifyEmailPayload>,
+) -> Result<StatusCode> {
+    debug!("{:<12} - api_resend_verify_email_handler", "HANDLER");
+
+    let ResendVerifyEmailPayload { username } = payload;
+    let root_ctx = Ctx::root_ctx_for_req(req_stamp.uuid);
+
+    // -- Always accept, so the response does not leak which usernames exist.
+    let Some(user) = UsersBmc::first_by_username::<Users>(&root_ctx, &mm, &username).await? else {
+        return Ok(StatusCode::ACCEPTED);
+    };
+    if user.email_verified.is_some() {
+        return Ok(StatusCode::ACCEPTED);
+    }
+
+    let user_id = user.id.id.to_raw();
+    send_verification_email(&root_ctx, &mm, &user_id, &user.email).await?;
+
+    Ok(StatusCode::ACCEPTED)
+}
+// endregion: --- Verify Email
@@ -1,9 +1,19 @@
 use std::sync::Arc;
 
-use crate::routes;
+use crate::{
+    mail, oidc,
+    routes::{
+        self,
+        validation::{field_violations, FieldViolation},
+    },
+};
 use axum::{http::StatusCode, response::IntoResponse};
 use derive_more::From;
-use lib_auth::token;
+use lib_auth::{
+    api_key,
+    pwd::{self, PolicyViolation},
+    token, totp,
+};
 use lib_surrealdb::model;
 use serde::Serialize;
 use serde_with::{serde_as, DisplayFromStr};
@@ -23,6 +33,35 @@ pub enum Error {
     LoginFailPwdNotMatching {
         user_id: String,
     },
+    LoginFailEmailNotVerified {
+        user_id: String,
+    },
+    LoginFailAccountLocked {
+        retry_after_sec: i64,
+    },
+
+    // -- Two-Factor
+    MfaTokenInvalid,
+    TotpAlreadyEnabled,
+    TotpNotEnabled,
+    TotpNotPending,
+    TotpCodeInvalid,
+
+    // -- Password Reset
+    PwdResetTokenInvalid,
+    PwdCurrentNotMatching,
+
+    // -- Email Verification
+    EmailVerificationTokenInvalid,
+
+    // -- Api Key
+    ApiKeyExpireDaysInvalid,
+
+    // -- Oidc
+    OidcProviderError(String),
+    OidcStateNotMatching,
+    OidcEmailNotVerified,
+    OidcUserNotFound,
 
     // -- Data
     DataNotFound,
@@ -34,14 +73,30 @@ pub enum Error {
 
     // -- Api Protected
     YourUserNotAuthorize,
+    PermissionDenied {
+        permission: &'static str,
+    },
+    ImpersonationNotAllowed,
+
+    // -- Validation
+    #[from]
+    Validation(validator::ValidationErrors),
 
     // -- Module
     #[from]
     Model(model::Error),
-    // #[from]
-    // Pwd(pwd::Error),
+    #[from]
+    Pwd(pwd::Error),
     #[from]
     Token(token::Error),
+    #[from]
+    Totp(totp::Error),
+    #[from]
+    ApiKey(api_key::Error),
+    #[from]
+    Mail(mail::Error),
+    #[from]
+    Oidc(oidc::Error),
     // #[from]
     // Rpc(lib_rpc::Error),
 
@@ -85,12 +140,81 @@ impl Error {
             LoginFailUsernameNotFound
             | LoginFailUserHasNoPwd { .. }
             | LoginFailPwdNotMatching { .. } => (StatusCode::FORBIDDEN, ClientError::LOGIN_FAIL),
+            LoginFailEmailNotVerified { .. } => {
+                (StatusCode::FORBIDDEN, ClientError::EMAIL_NOT_VERIFIED)
+            }
+            LoginFailAccountLocked { retry_after_sec } => (
+                StatusCode::TOO_MANY_REQUESTS,
+                ClientError::ACCOUNT_LOCKED {
+                    retry_after_sec: *retry_after_sec,
+                },
+            ),
+
+            // -- Two-Factor
+            MfaTokenInvalid | TotpNotEnabled => {
+                (StatusCode::UNAUTHORIZED, ClientError::MFA_TOKEN_INVALID)
+            }
+            TotpAlreadyEnabled => (StatusCode::BAD_REQUEST, ClientError::TOTP_ALREADY_ENABLED),
+            TotpNotPending | Model(model::Error::TotpNotPending) => {
+                (StatusCode::BAD_REQUEST, ClientError::TOTP_NOT_PENDING)
+            }
+            TotpCodeInvalid => (StatusCode::FORBIDDEN, ClientError::TOTP_CODE_INVALID),
+
+            // -- Password Reset
+            PwdResetTokenInvalid | Model(model::Error::PwdResetAlreadyUsed) => (
+                StatusCode::BAD_REQUEST,
+                ClientError::PWD_RESET_TOKEN_INVALID,
+            ),
+
+            PwdCurrentNotMatching => (StatusCode::FORBIDDEN, ClientError::PWD_CURRENT_NOT_MATCHING),
+
+            // -- Email Verification
+            EmailVerificationTokenInvalid
+            | Model(model::Error::EmailVerificationAlreadyUsed)
+            | Model(model::Error::EmailVerificationEmailChanged) => (
+                StatusCode::BAD_REQUEST,
+                ClientError::EMAIL_VERIFICATION_TOKEN_INVALID,
+            ),
+
+            // -- Api Key
+            ApiKeyExpireDaysInvalid => (
+                StatusCode::BAD_REQUEST,
+                ClientError::API_KEY_EXPIRE_DAYS_INVALID,
+            ),
+
+            // -- Oidc
+            OidcProviderError(_)
+            | OidcStateNotMatching
+            | OidcEmailNotVerified
+            | OidcUserNotFound
+            | Oidc(oidc::Error::IdToken(_))
+            | Model(model::Error::OidcLoginInvalid) => {
+                (StatusCode::FORBIDDEN, ClientError::OIDC_LOGIN_FAIL)
+            }
+            Oidc(oidc::Error::OidcNotConfigured) => {
+                (StatusCode::NOT_FOUND, ClientError::OIDC_NOT_CONFIGURED)
+            }
 
             // -- Auth
             YourUserNotAuthorize => (StatusCode::FORBIDDEN, ClientError::NO_AUTH),
+            PermissionDenied { permission } => (
+                StatusCode::FORBIDDEN,
+                ClientError::PERMISSION_DENIED {
+                    permission: *permission,
+                },
+            ),
+            ImpersonationNotAllowed => (
+                StatusCode::FORBIDDEN,
+                ClientError::IMPERSONATION_NOT_ALLOWED,
+            ),
+            Model(model::Error::RoleAdminLockedOut) => {
+                (StatusCode::BAD_REQUEST, ClientError::ROLE_ADMIN_LOCKED_OUT)
+            }
 
             // -- Data
-            DataNotFound => (StatusCode::NOT_FOUND, ClientError::DATA_NOT_FOUND),
+            DataNotFound | Model(model::Error::RoleNotValid(_)) => {
+                (StatusCode::NOT_FOUND, ClientError::DATA_NOT_FOUND)
+            }
 
             Model(model::Error::EntityNotFound { entity, id }) => (
                 StatusCode::BAD_REQUEST,
@@ -107,6 +231,22 @@ impl Error {
                 ClientError::USERNAME_NOT_VALID_FORMAT,
             ),
 
+            // -- Validation
+            Validation(errors) => (
+                StatusCode::BAD_REQUEST,
+                ClientError::VALIDATION_FAILED {
+                    fields: field_violations(errors),
+                },
+            ),
+
+            // -- Pwd
+            Pwd(pwd::Error::PwdPolicyViolated(violations)) => (
+                StatusCode::BAD_REQUEST,
+                ClientError::PASSWORD_POLICY_VIOLATED {
+                    violations: violations.clone(),
+                },
+            ),
+
             // -- Rpc
             // Rpc(lib_rpc::Error::SerdeJson(detail)) => (
             //     StatusCode::BAD_REQUEST,
@@ -123,18 +263,49 @@ impl Error {
 }
 
 // region:    --- Client Error
-#[derive(Debug, Serialize, strum_macros::AsRefStr)]
+#[derive(Debug, Serialize, strum_macros::AsRefStr, strum_macros::VariantNames)]
 #[serde(tag = "message", content = "detail")]
 #[allow(non_camel_case_types)]
 pub enum ClientError {
     LOGIN_FAIL,
+    EMAIL_NOT_VERIFIED,
+    ACCOUNT_LOCKED { retry_after_sec: i64 },
+    RATE_LIMITED { retry_after_sec: i64 },
+    MFA_TOKEN_INVALID,
+    TOTP_ALREADY_ENABLED,
+    TOTP_NOT_PENDING,
+    TOTP_CODE_INVALID,
     NO_AUTH,
+    PERMISSION_DENIED { permission: &'static str },
+    IMPERSONATION_NOT_ALLOWED,
+    ROLE_ADMIN_LOCKED_OUT,
     INVALID_AUTHORIZATION_HEADER,
     USERNAME_ALREADY_EXISTS,
     USERNAME_NOT_VALID_FORMAT,
+    VALIDATION_FAILED { fields: Vec<FieldViolation> },
+    PASSWORD_POLICY_VIOLATED { violations: Vec<PolicyViolation> },
+    PWD_RESET_TOKEN_INVALID,
+    PWD_CURRENT_NOT_MATCHING,
+    EMAIL_VERIFICATION_TOKEN_INVALID,
+    API_KEY_EXPIRE_DAYS_INVALID,
+    OIDC_LOGIN_FAIL,
+    OIDC_NOT_CONFIGURED,
     ENTITY_NOT_FOUND { entity: &'static str, id: i64 },
     // BAD_REQUEST(String),
+    PAYLOAD_TOO_LARGE,
+    REQUEST_TIMEOUT,
     DATA_NOT_FOUND,
     SERVICE_ERROR,
 }
+
+impl ClientError {
+    /// The seconds the client should wait before retrying, if any.
+    pub fn retry_after_sec(&self) -> Option<i64> {
+        match self {
+            ClientError::ACCOUNT_LOCKED { retry_after_sec }
+            | ClientError::RATE_LIMITED { retry_after_sec } => Some(*retry_after_sec),
+            _ => None,
+        }
+    }
+}
 // endregion: --- Client Error
@@ -1,7 +1,8 @@
 use axum::{http::StatusCode, response::IntoResponse, routing::get, Json, Router};
 use serde::Serialize;
+use utoipa::ToSchema;
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 struct Health {
     healthy: bool,
 }
@@ -11,6 +12,14 @@ pub fn route() -> Router {
     routes.route("/health", get(health))
 }
 
+#[utoipa::path(
+    get,
+    path = "/health",
+    tag = "service",
+    responses(
+        (status = 200, description = "The service is up.", body = Health),
+    )
+)]
 pub(crate) async fn health() -> impl IntoResponse {
     let health = Health { healthy: true };
 
@@ -0,0 +1,42 @@
+use axum::{
+    http::{header::CONTENT_TYPE, StatusCode},
+    middleware::from_fn,
+    response::IntoResponse,
+    routing::get,
+    Router,
+};
+
+use super::ErrorBody;
+use crate::{config::web_config, middlewares::mw_metrics_token, telemetry};
+
+/// Not routed without a `SERVICE_METRICS_TOKEN`.
+pub fn route() -> Router {
+    let routes = Router::new();
+    if web_config().METRICS_TOKEN.is_none() {
+        return routes;
+    }
+
+    routes
+        .route("/metrics", get(metrics))
+        .route_layer(from_fn(mw_metrics_token))
+}
+
+/// The metrics of the service, in the Prometheus text format.
+/// Requires the `SERVICE_METRICS_TOKEN` as bearer token.
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "service",
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "The metrics, in the Prometheus text format.", content_type = "text/plain"),
+        (status = "4XX", response = ErrorBody),
+    )
+)]
+pub(crate) async fn metrics() -> impl IntoResponse {
+    (
+        StatusCode::OK,
+        [(CONTENT_TYPE, "text/plain; version=0.0.4")],
+        telemetry::render_metrics(),
+    )
+}
@@ -1,15 +1,24 @@
 mod api;
 mod error;
 mod health;
+mod metrics;
+mod openapi;
 pub mod static_file;
+mod validation;
 
 use axum::Router;
 use lib_surrealdb::model::ModelManager;
 
 pub use self::error::ClientError;
 pub use self::error::{Error, Result};
+pub use self::openapi::ErrorBody;
+pub use self::validation::ValidJson;
 
 pub fn route(mm: ModelManager) -> Router {
     let routes = Router::new();
-    routes.merge(health::route()).merge(api::route(mm))
+    routes
+        .merge(health::route())
+        .merge(metrics::route())
+        .merge(openapi::route())
+        .merge(api::route(mm))
 }
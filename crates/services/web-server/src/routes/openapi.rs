@@ -0,0 +1,273 @@
+//! The OpenAPI document of the routes, served at `/api/openapi.json` and browsable at `/api/docs`.
+//!
+//! Each handler documents itself with `#[utoipa::path]`, and each routes module composes the
+//! document of its handlers with an `openapi()` next to its `route()`.
+
+use axum::{routing::get, Json, Router};
+use serde_json::Value;
+use strum::VariantNames;
+use utoipa::{
+    openapi::{
+        schema::{ObjectBuilder, Type},
+        security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme},
+        RefOr, Schema,
+    },
+    Modify, OpenApi, PartialSchema, ToResponse, ToSchema,
+};
+use utoipa_scalar::{Scalar, Servable};
+
+use super::{api, health, metrics, ClientError};
+
+pub fn route() -> Router {
+    let routes = Router::new();
+    routes
+        .route("/api/openapi.json", get(openapi_json))
+        .merge(Scalar::with_url("/api/docs", openapi()))
+}
+
+async fn openapi_json() -> Json<utoipa::openapi::OpenApi> {
+    Json(openapi())
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "web-server"),
+    paths(health::health, metrics::metrics),
+    components(schemas(ErrorBody), responses(ErrorBody)),
+    modifiers(&SecurityAddon),
+    tags(
+        (name = "login", description = "Login with a password, a second factor or an identity provider."),
+        (name = "password", description = "Forgotten password reset."),
+        (name = "verify_email", description = "Email verification."),
+        (name = "users", description = "The users, and the session user under `/me`."),
+        (name = "2fa", description = "The second factor of the session user."),
+        (name = "tasks"),
+        (name = "api_keys"),
+        (name = "sessions"),
+        (name = "role_policies"),
+        (name = "audit_logs"),
+        (name = "service", description = "Health and metrics of the service."),
+    )
+)]
+struct ApiDoc;
+
+pub fn openapi() -> utoipa::openapi::OpenApi {
+    let mut openapi = ApiDoc::openapi();
+    openapi.merge(api::openapi());
+    openapi
+}
+
+// region:    --- Error Body
+/// The body of every error response, see `mw_response_map`.
+#[derive(ToSchema, ToResponse)]
+#[response(description = "The error, `error.message` being its `ClientError`.")]
+#[allow(dead_code)]
+pub struct ErrorBody {
+    error: ErrorBodyError,
+}
+
+#[derive(ToSchema)]
+#[allow(dead_code)]
+struct ErrorBodyError {
+    #[schema(inline)]
+    message: ClientErrorMessage,
+    data: ErrorBodyData,
+}
+
+#[derive(ToSchema)]
+#[allow(dead_code)]
+struct ErrorBodyData {
+    /// The request id, as in the `X-Request-Id` header of the response.
+    #[schema(format = Uuid)]
+    req_uuid: String,
+    /// The details of some `ClientError`, e.g. the `retry_after_sec` of `RATE_LIMITED`.
+    #[schema(value_type = Option<Object>)]
+    detail: Option<Value>,
+}
+
+/// The `ClientError` variant names, generated from the enum itself.
+struct ClientErrorMessage;
+
+impl PartialSchema for ClientErrorMessage {
+    fn schema() -> RefOr<Schema> {
+        ObjectBuilder::new()
+            .schema_type(Type::String)
+            .enum_values(Some(ClientError::VARIANTS.iter().copied()))
+            .into()
+    }
+}
+
+impl ToSchema for ClientErrorMessage {}
+// endregion: --- Error Body
+
+struct SecurityAddon;
+
+impl Modify for SecurityAddon {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .build(),
+            ),
+        );
+        components.add_security_scheme(
+            "api_key",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new("X-Api-Key"))),
+        );
+    }
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    type Error = Box<dyn std::error::Error>;
+    type Result<T> = core::result::Result<T, Error>; // For tests.
+
+    use std::{fs, path::Path};
+
+    use super::*;
+
+    const METHODS: [&str; 5] = ["get", "post", "put", "patch", "delete"];
+
+    #[test]
+    fn test_openapi_documents_every_route() -> Result<()> {
+        // -- Setup & Fixtures
+        let routes_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/routes");
+        let mut routes = Vec::new();
+        collect_routes(&routes_dir, "", &mut routes)?;
+        let fx_openapi = serde_json::to_value(openapi())?;
+        let fx_paths = &fx_openapi["paths"];
+
+        // -- Exec
+        let undocumented: Vec<String> = routes
+            .iter()
+            .filter(|(method, path)| !fx_paths[path][method].is_object())
+            .map(|(method, path)| format!("{} {path}", method.to_uppercase()))
+            .collect();
+
+        // -- Check
+        assert!(!routes.is_empty(), "no route found in {routes_dir:?}");
+        assert!(
+            undocumented.is_empty(),
+            "undocumented routes, add them a `#[utoipa::path]`: {undocumented:?}"
+        );
+
+        Ok(())
+    }
+
+    /// Collect the `(method, path)` of every `.route(..)` of the routes sources,
+    /// with the paths in the OpenAPI form (`/api/v1/users/{id}`).
+    ///
+    /// The routes of a directory are under the `.nest("/prefix", ..)` of its `mod.rs`,
+    /// any other nesting is an error, as the prefix of its routes would be unknown.
+    fn collect_routes(
+        dir: &Path,
+        parent_prefix: &str,
+        routes: &mut Vec<(String, String)>,
+    ) -> Result<()> {
+        let mod_path = dir.join("mod.rs");
+        let prefix = match parse_nests(&fs::read_to_string(&mod_path)?)?.as_slice() {
+            [] => parent_prefix.to_string(),
+            [nest] => format!("{parent_prefix}{nest}"),
+            nests => return Err(format!("{mod_path:?} nests several prefixes {nests:?}").into()),
+        };
+
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                collect_routes(&path, &prefix, routes)?;
+                continue;
+            }
+            // -- The docs routes are not part of the api.
+            if path.extension().is_none_or(|ext| ext != "rs") || path.ends_with("openapi.rs") {
+                continue;
+            }
+
+            let src = fs::read_to_string(&path)?;
+            if path != mod_path && !parse_nests(&src)?.is_empty() {
+                return Err(format!("{path:?} nests routes, only a `mod.rs` can").into());
+            }
+            for (method, route_path) in parse_routes(&src)? {
+                routes.push((method, format!("{prefix}{}", openapi_path(&route_path))));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Parse the distinct prefixes of the `.nest("/prefix", ..)` calls,
+    /// the router and its document being nested alike.
+    fn parse_nests(src: &str) -> Result<Vec<String>> {
+        let mut nests = Vec::new();
+
+        for (start, _) in src.match_indices(".nest(") {
+            let rest = src[start + ".nest(".len()..].trim_start();
+            let Some((nest, _)) = rest.strip_prefix('"').and_then(|rest| rest.split_once('"'))
+            else {
+                let call = src[start..].lines().next().unwrap_or_default();
+                return Err(format!("cannot follow the nesting `{call}`").into());
+            };
+            if !nests.iter().any(|n| n == nest) {
+                nests.push(nest.to_string());
+            }
+        }
+
+        Ok(nests)
+    }
+
+    /// Parse the `.route("/path", get(..).post(..))` calls, which may span several lines.
+    fn parse_routes(src: &str) -> Result<Vec<(String, String)>> {
+        let mut routes = Vec::new();
+
+        for (start, _) in src.match_indices(".route(") {
+            let rest = src[start + ".route(".len()..].trim_start();
+            let Some((route_path, rest)) =
+                rest.strip_prefix('"').and_then(|rest| rest.split_once('"'))
+            else {
+                let call = src[start..].lines().next().unwrap_or_default();
+                return Err(format!("cannot follow the route `{call}`").into());
+            };
+
+            // -- The method routers, up to the closing parenthesis of `.route(`.
+            let mut depth = 1;
+            let mut ident = String::new();
+            for c in rest.chars() {
+                if c.is_alphanumeric() || c == '_' {
+                    ident.push(c);
+                    continue;
+                }
+                if c == '(' {
+                    if depth == 1 && METHODS.contains(&ident.as_str()) {
+                        routes.push((ident.clone(), route_path.to_string()));
+                    }
+                    depth += 1;
+                } else if c == ')' {
+                    depth -= 1;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                ident.clear();
+            }
+        }
+
+        Ok(routes)
+    }
+
+    /// `/users/:id` to `/users/{id}`.
+    fn openapi_path(route_path: &str) -> String {
+        route_path
+            .split('/')
+            .map(|segment| match segment.strip_prefix(':') {
+                Some(param) => format!("{{{param}}}"),
+                None => segment.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+}
+// endregion: --- Tests
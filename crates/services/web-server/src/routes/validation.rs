@@ -0,0 +1,216 @@
+//! The validation of the payloads, declared on their types with `#[derive(Validate)]`.
+//!
+//! A `ValidJson` payload is checked before the handler runs, so a bad field is a
+//! `VALIDATION_FAILED` listing every field, rule and message, not an `ASSERT` of the db.
+
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use axum::{
+    extract::{FromRequest, Request},
+    response::{IntoResponse, Response},
+    Json,
+};
+use lib_surrealdb::model::{role_policies::Role, users::TITLES};
+use serde::{de::DeserializeOwned, Serialize};
+use tracing::debug;
+use validator::{Validate, ValidationError, ValidationErrors};
+
+use super::Error;
+
+// region:    --- ValidJson Extractor
+/// A `Json` payload which passed its `Validate` rules.
+pub struct ValidJson<T>(pub T);
+
+#[async_trait]
+impl<T, S> FromRequest<S> for ValidJson<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        debug!("{:<12} - ValidJson", "EXTRACTOR");
+
+        let Json(payload) = Json::<T>::from_request(req, state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+        payload
+            .validate()
+            .map_err(|errors| Error::from(errors).into_response())?;
+
+        Ok(ValidJson(payload))
+    }
+}
+// endregion: --- ValidJson Extractor
+
+// region:    --- Field Violation
+/// A failed rule of a field, as in the `VALIDATION_FAILED` detail.
+#[derive(Clone, Debug, Serialize, PartialEq)]
+pub struct FieldViolation {
+    pub field: String,
+    pub rule: String,
+    pub message: String,
+}
+
+/// Flatten the errors of the payload, sorted by field for a stable body.
+pub fn field_violations(errors: &ValidationErrors) -> Vec<FieldViolation> {
+    let mut violations: Vec<FieldViolation> = errors
+        .field_errors()
+        .into_iter()
+        .flat_map(|(field, errors)| {
+            errors.iter().map(move |error| FieldViolation {
+                field: field.to_string(),
+                rule: error.code.to_string(),
+                message: message(error),
+            })
+        })
+        .collect();
+    violations.sort_by(|a, b| a.field.cmp(&b.field));
+
+    violations
+}
+
+fn message(error: &ValidationError) -> String {
+    if let Some(message) = &error.message {
+        return message.to_string();
+    }
+
+    let param = |name: &str| error.params.get(name).map(ToString::to_string);
+    match (error.code.as_ref(), param("min"), param("max")) {
+        ("email", ..) => "must be an email".to_string(),
+        ("length", Some(min), Some(max)) => format!("must be {min} to {max} characters"),
+        ("length", Some(min), None) => format!("must be at least {min} characters"),
+        ("length", None, Some(max)) => format!("must be at most {max} characters"),
+        (code, ..) => format!("must satisfy {code}"),
+    }
+}
+// endregion: --- Field Violation
+
+// region:    --- Rules
+/// One of the titles the `users` table accepts.
+pub fn validate_title(title: &str) -> Result<(), ValidationError> {
+    if TITLES.contains(&title) {
+        return Ok(());
+    }
+
+    Err(ValidationError::new("title")
+        .with_message(format!("must be one of {}", TITLES.join(", ")).into()))
+}
+
+/// One of the roles, e.g. `USER`.
+pub fn validate_role(role: &str) -> Result<(), ValidationError> {
+    if Role::from_str(role).is_ok() {
+        return Ok(());
+    }
+
+    let roles: Vec<&str> = Role::ALL.iter().map(|role| role.as_str()).collect();
+    Err(ValidationError::new("role")
+        .with_message(format!("must be one of {}", roles.join(", ")).into()))
+}
+// endregion: --- Rules
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    type Error = Box<dyn std::error::Error>;
+    type Result<T> = core::result::Result<T, Error>; // For tests.
+
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Deserialize, Validate)]
+    struct FxPayload {
+        #[validate(email)]
+        username: String,
+        #[validate(custom(function = "validate_title"))]
+        title: String,
+        #[validate(length(min = 1, max = 100))]
+        firstname: String,
+        #[validate(custom(function = "validate_role"))]
+        role: String,
+    }
+
+    fn fx_length_error(min: Option<u64>, max: Option<u64>) -> ValidationError {
+        let mut error = ValidationError::new("length");
+        if let Some(min) = min {
+            error.add_param("min".into(), &min);
+        }
+        if let Some(max) = max {
+            error.add_param("max".into(), &max);
+        }
+        error
+    }
+
+    #[test]
+    fn test_field_violations_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_payload = FxPayload {
+            username: "not-an-email".to_string(),
+            title: "Mr".to_string(),
+            firstname: String::new(),
+            role: "ROOT".to_string(),
+        };
+
+        // -- Exec
+        let errors = fx_payload.validate().err().ok_or("Should not be valid")?;
+        let violations = field_violations(&errors);
+
+        // -- Check
+        let violations: Vec<(&str, &str, &str)> = violations
+            .iter()
+            .map(|v| (v.field.as_str(), v.rule.as_str(), v.message.as_str()))
+            .collect();
+        assert_eq!(
+            violations,
+            [
+                ("firstname", "length", "must be 1 to 100 characters"),
+                ("role", "role", "must be one of USER, ADMIN"),
+                ("title", "title", "must be one of นาย, นางสาว, นาง"),
+                ("username", "email", "must be an email"),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_message_length() {
+        // -- Setup & Fixtures
+        let fx_cases = [
+            (Some(8), None, "must be at least 8 characters"),
+            (None, Some(100), "must be at most 100 characters"),
+            (Some(1), Some(100), "must be 1 to 100 characters"),
+        ];
+
+        for (min, max, fx_message) in fx_cases {
+            // -- Exec & Check
+            assert_eq!(message(&fx_length_error(min, max)), fx_message);
+        }
+    }
+
+    #[test]
+    fn test_validate_title() {
+        // -- Exec & Check
+        assert!(validate_title("นางสาว").is_ok());
+        let error = validate_title("Mr").err();
+        assert_eq!(
+            error.map(|error| error.code.to_string()).as_deref(),
+            Some("title")
+        );
+    }
+
+    #[test]
+    fn test_validate_role() {
+        // -- Exec & Check
+        assert!(validate_role("ADMIN").is_ok());
+        let error = validate_role("admin").err();
+        assert_eq!(
+            error.map(|error| error.code.to_string()).as_deref(),
+            Some("role")
+        );
+    }
+}
+// endregion: --- Tests
@@ -0,0 +1,193 @@
+//! Tracing of the service, with the spans exported by OTLP when `SERVICE_OTEL_ENDPOINT` is set,
+//! and its metrics, rendered in the Prometheus text format by `GET /metrics`.
+//!
+//! The trace context is propagated with the W3C `traceparent` header, read from the
+//! requests, and written to the responses and the outgoing requests.
+
+use std::sync::OnceLock;
+
+use axum::{
+    body::Body,
+    http::{HeaderMap, HeaderName, HeaderValue, Request},
+    response::Response,
+};
+use metrics_exporter_prometheus::{BuildError, Matcher, PrometheusBuilder, PrometheusHandle};
+use opentelemetry::{
+    global,
+    propagation::{Extractor, Injector},
+    trace::{TraceContextExt, TraceError, TracerProvider as _},
+    KeyValue,
+};
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::{propagation::TraceContextPropagator, runtime, trace, Resource};
+use tracing::{field::Empty, info_span, Span};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::{
+    filter::LevelFilter, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer,
+};
+use uuid::Uuid;
+
+use crate::config::WebConfig;
+
+/// Install the tracing subscriber, logging to stdout, and exporting the spans
+/// when the config has an OTLP endpoint (none when the config is not valid).
+pub fn init_tracing(config: Option<&WebConfig>) -> Result<(), TraceError> {
+    global::set_text_map_propagator(TraceContextPropagator::new());
+
+    let otel_config = config.and_then(|config| {
+        Some((
+            config.OTEL_ENDPOINT.as_deref()?,
+            config.OTEL_SERVICE_NAME.clone(),
+        ))
+    });
+    let otel_layer = match otel_config {
+        Some((endpoint, service_name)) => {
+            let tracer_provider =
+                opentelemetry_otlp::new_pipeline()
+                    .tracing()
+                    .with_exporter(
+                        opentelemetry_otlp::new_exporter()
+                            .tonic()
+                            .with_endpoint(endpoint),
+                    )
+                    .with_trace_config(trace::Config::default().with_resource(Resource::new([
+                        KeyValue::new("service.name", service_name),
+                    ])))
+                    .install_batch(runtime::Tokio)?;
+            let tracer = tracer_provider.tracer("web-server");
+            global::set_tracer_provider(tracer_provider);
+
+            // -- The spans are exported from the info level, whatever the `RUST_LOG` of the logs.
+            Some(
+                tracing_opentelemetry::layer()
+                    .with_tracer(tracer)
+                    .with_filter(LevelFilter::INFO),
+            )
+        }
+        None => None,
+    };
+
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer().with_filter(EnvFilter::from_default_env()))
+        .with(otel_layer)
+        .init();
+
+    Ok(())
+}
+
+/// Export the remaining spans, on shutdown.
+pub fn shutdown_tracing() {
+    global::shutdown_tracer_provider();
+}
+
+/// The span of the request `req_id`, continuing the trace of its `traceparent` header.
+pub fn request_span(req: &Request<Body>, req_id: Uuid) -> Span {
+    let method = req.method();
+    let path = req.uri().path();
+    let span = info_span!(
+        "request",
+        otel.name = %format!("{method} {path}"),
+        otel.kind = "server",
+        req_id = %req_id,
+        http.request.method = %method,
+        url.path = %path,
+        http.response.status_code = Empty,
+    );
+
+    let parent_context = global::get_text_map_propagator(|propagator| {
+        propagator.extract(&HeaderExtractor(req.headers()))
+    });
+    span.set_parent(parent_context);
+
+    span
+}
+
+/// Record the status of the response, and add its `traceparent` header.
+pub fn record_response(span: &Span, res: &mut Response) {
+    span.record("http.response.status_code", res.status().as_u16());
+
+    let context = span.context();
+    global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&context, &mut HeaderInjector(res.headers_mut()))
+    });
+}
+
+/// The `traceparent` header of an outgoing request, made in the current span.
+pub fn trace_headers() -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    let context = Span::current().context();
+    global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&context, &mut HeaderInjector(&mut headers))
+    });
+
+    headers
+}
+
+/// The trace id of the current span, `None` when the spans are not exported.
+pub fn current_trace_id() -> Option<String> {
+    let context = Span::current().context();
+    let span = context.span();
+    let span_context = span.span_context();
+
+    span_context
+        .is_valid()
+        .then(|| span_context.trace_id().to_string())
+}
+
+// region:    --- Metrics
+static METRICS_HANDLE: OnceLock<PrometheusHandle> = OnceLock::new();
+
+/// Buckets of the `*_seconds` histograms, from the fast db queries to the slow pwd hashes.
+const SECONDS_BUCKETS: &[f64] = &[
+    0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Install the recorder of the metrics of the service (and of the libs), for `render_metrics`.
+pub fn init_metrics() -> Result<(), BuildError> {
+    let handle = PrometheusBuilder::new()
+        .set_buckets_for_metric(Matcher::Suffix("_seconds".to_string()), SECONDS_BUCKETS)?
+        .install_recorder()?;
+    let _ = METRICS_HANDLE.set(handle);
+
+    Ok(())
+}
+
+/// The metrics in the Prometheus text format (empty when `init_metrics` was not called).
+pub fn render_metrics() -> String {
+    METRICS_HANDLE
+        .get()
+        .map(PrometheusHandle::render)
+        .unwrap_or_default()
+}
+// endregion: --- Metrics
+
+// region:    --- Header Propagation
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(HeaderName::as_str).collect()
+    }
+}
+
+struct HeaderInjector<'a>(&'a mut HeaderMap);
+
+impl Injector for HeaderInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        if value.is_empty() {
+            return;
+        }
+        let Ok(name) = HeaderName::from_bytes(key.as_bytes()) else {
+            return;
+        };
+        let Ok(value) = HeaderValue::from_str(&value) else {
+            return;
+        };
+        self.0.insert(name, value);
+    }
+}
+// endregion: --- Header Propagation